@@ -0,0 +1,29 @@
+//! Embeds a message in a PNG file as a private `ruSt` chunk, then reads it back.
+//!
+//! ```text
+//! cargo run --example embed -- input.png output.png "Hello!"
+//! ```
+
+use pngme::{Chunk, ChunkType, Png};
+use std::fs::File;
+use std::io::BufWriter;
+use std::str::FromStr;
+
+fn main() -> pngme::Result<()> {
+    let mut args = std::env::args().skip(1);
+    let (Some(input), Some(output), Some(message)) = (args.next(), args.next(), args.next()) else {
+        eprintln!("usage: embed <INPUT> <OUTPUT> <MESSAGE>");
+        std::process::exit(2);
+    };
+
+    let mut png = Png::try_from(std::fs::read(&input)?.as_slice())?;
+    let chunk = Chunk::new(ChunkType::from_str("ruSt")?, message.into_bytes());
+    png.insert_chunk_before("IEND", chunk)?;
+    png.write_to(&mut BufWriter::new(File::create(&output)?))?;
+
+    let written = Png::try_from(std::fs::read(&output)?.as_slice())?;
+    for chunk in written.chunks_by_type("ruSt") {
+        println!("{output}: {}", chunk.data_as_string()?);
+    }
+    Ok(())
+}
@@ -0,0 +1,367 @@
+//! Typed views of the animated PNG chunks: `acTL`, `fcTL` and `fdAT`. Only
+//! their fields are parsed and range-checked; frames are never rendered.
+
+use crate::chunk::Chunk;
+use std::error::Error;
+use std::fmt;
+
+/// The largest value a PNG four-byte unsigned integer may hold.
+const MAXIMUM_VALUE: u32 = (1 << 31) - 1;
+
+/// The frame and loop counts from an `acTL` chunk, which marks the file as
+/// an APNG.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct AnimationControl {
+    num_frames: u32,
+    num_plays: u32,
+}
+
+impl TryFrom<&Chunk> for AnimationControl {
+    type Error = crate::Error;
+
+    fn try_from(chunk: &Chunk) -> Result<Self, Self::Error> {
+        let data: [u8; 8] = fixed_length("acTL", chunk)?;
+        let fields = Fields("acTL", &data);
+        let num_frames = fields.u32(0, "num_frames")?;
+        if num_frames == 0 {
+            return Err(ApngError::FieldOutOfRange("acTL", "num_frames", 0).into());
+        }
+        Ok(AnimationControl {
+            num_frames,
+            num_plays: fields.u32(4, "num_plays")?,
+        })
+    }
+}
+
+impl fmt::Display for AnimationControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let frames = match self.num_frames {
+            1 => String::from("1 frame"),
+            n => format!("{n} frames"),
+        };
+        match self.num_plays {
+            0 => write!(f, "{frames}, loops forever"),
+            1 => write!(f, "{frames}, plays once"),
+            n => write!(f, "{frames}, plays {n} times"),
+        }
+    }
+}
+
+impl AnimationControl {
+    pub fn num_frames(&self) -> u32 {
+        self.num_frames
+    }
+
+    /// How many times the animation plays; 0 means it loops forever.
+    pub fn num_plays(&self) -> u32 {
+        self.num_plays
+    }
+}
+
+/// What happens to a frame's region once its delay is over.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DisposeOp {
+    /// Leave the frame as it is.
+    None,
+    /// Clear the region to fully transparent black.
+    Background,
+    /// Restore the region to what it was before the frame was drawn.
+    Previous,
+}
+
+/// How a frame is drawn onto the output buffer.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum BlendOp {
+    /// Overwrite the region, alpha included.
+    Source,
+    /// Alpha-composite the frame over the region.
+    Over,
+}
+
+/// The region, timing and compositing of one frame from an `fcTL` chunk.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct FrameControl {
+    sequence_number: u32,
+    width: u32,
+    height: u32,
+    x_offset: u32,
+    y_offset: u32,
+    delay_num: u16,
+    delay_den: u16,
+    dispose_op: DisposeOp,
+    blend_op: BlendOp,
+}
+
+impl TryFrom<&Chunk> for FrameControl {
+    type Error = crate::Error;
+
+    fn try_from(chunk: &Chunk) -> Result<Self, Self::Error> {
+        let data: [u8; 26] = fixed_length("fcTL", chunk)?;
+        let fields = Fields("fcTL", &data);
+        let (width, height) = (fields.u32(4, "width")?, fields.u32(8, "height")?);
+        if width == 0 {
+            return Err(ApngError::FieldOutOfRange("fcTL", "width", 0).into());
+        }
+        if height == 0 {
+            return Err(ApngError::FieldOutOfRange("fcTL", "height", 0).into());
+        }
+        let dispose_op = match data[24] {
+            0 => DisposeOp::None,
+            1 => DisposeOp::Background,
+            2 => DisposeOp::Previous,
+            op => return Err(ApngError::UnknownDisposeOp(op).into()),
+        };
+        let blend_op = match data[25] {
+            0 => BlendOp::Source,
+            1 => BlendOp::Over,
+            op => return Err(ApngError::UnknownBlendOp(op).into()),
+        };
+        Ok(FrameControl {
+            sequence_number: fields.u32(0, "sequence_number")?,
+            width,
+            height,
+            x_offset: fields.u32(12, "x_offset")?,
+            y_offset: fields.u32(16, "y_offset")?,
+            delay_num: u16::from_be_bytes([data[20], data[21]]),
+            delay_den: u16::from_be_bytes([data[22], data[23]]),
+            dispose_op,
+            blend_op,
+        })
+    }
+}
+
+impl fmt::Display for FrameControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "frame {}: {}x{} at ({}, {}), {:.3}s",
+            self.sequence_number,
+            self.width,
+            self.height,
+            self.x_offset,
+            self.y_offset,
+            self.delay()
+        )
+    }
+}
+
+impl FrameControl {
+    pub fn sequence_number(&self) -> u32 {
+        self.sequence_number
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn x_offset(&self) -> u32 {
+        self.x_offset
+    }
+
+    pub fn y_offset(&self) -> u32 {
+        self.y_offset
+    }
+
+    /// How long the frame is shown, in seconds. A denominator of 0 stands
+    /// for 100, i.e. the numerator counts hundredths of a second.
+    pub fn delay(&self) -> f64 {
+        let den = if self.delay_den == 0 {
+            100
+        } else {
+            self.delay_den
+        };
+        f64::from(self.delay_num) / f64::from(den)
+    }
+
+    pub fn dispose_op(&self) -> DisposeOp {
+        self.dispose_op
+    }
+
+    pub fn blend_op(&self) -> BlendOp {
+        self.blend_op
+    }
+}
+
+/// The compressed image data of a frame after the first, from an `fdAT`
+/// chunk. It is laid out like `IDAT` data behind a sequence number.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct FrameData<'a> {
+    sequence_number: u32,
+    data: &'a [u8],
+}
+
+impl<'a> TryFrom<&'a Chunk> for FrameData<'a> {
+    type Error = crate::Error;
+
+    fn try_from(chunk: &'a Chunk) -> Result<Self, Self::Error> {
+        let data = chunk.data();
+        if data.len() < 4 {
+            return Err(ApngError::TooShort("fdAT", 4, data.len()).into());
+        }
+        Ok(FrameData {
+            sequence_number: Fields("fdAT", data).u32(0, "sequence_number")?,
+            data: &data[4..],
+        })
+    }
+}
+
+impl FrameData<'_> {
+    pub fn sequence_number(&self) -> u32 {
+        self.sequence_number
+    }
+
+    pub fn data(&self) -> &[u8] {
+        self.data
+    }
+}
+
+/// Reads the big-endian integers of a chunk's data, rejecting any above
+/// 2^31 - 1 as the PNG spec requires.
+struct Fields<'a>(&'static str, &'a [u8]);
+
+impl Fields<'_> {
+    fn u32(&self, offset: usize, field: &'static str) -> crate::Result<u32> {
+        let bytes: [u8; 4] = self.1[offset..offset + 4].try_into().unwrap();
+        let value = u32::from_be_bytes(bytes);
+        if value > MAXIMUM_VALUE {
+            return Err(ApngError::FieldOutOfRange(self.0, field, value).into());
+        }
+        Ok(value)
+    }
+}
+
+fn fixed_length<const N: usize>(chunk_type: &'static str, chunk: &Chunk) -> crate::Result<[u8; N]> {
+    chunk
+        .data()
+        .try_into()
+        .map_err(|_| ApngError::WrongLength(chunk_type, N, chunk.data().len()).into())
+}
+
+#[derive(Debug)]
+pub enum ApngError {
+    WrongLength(&'static str, usize, usize),
+    TooShort(&'static str, usize, usize),
+    FieldOutOfRange(&'static str, &'static str, u32),
+    UnknownDisposeOp(u8),
+    UnknownBlendOp(u8),
+}
+
+impl fmt::Display for ApngError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApngError::WrongLength(chunk_type, expected, actual) => write!(
+                f,
+                "{chunk_type} data must be {expected} bytes long, found {actual}"
+            ),
+            ApngError::TooShort(chunk_type, minimum, actual) => write!(
+                f,
+                "{chunk_type} data must be at least {minimum} bytes long, found {actual}"
+            ),
+            ApngError::FieldOutOfRange(chunk_type, field, value) => {
+                write!(f, "{chunk_type} {field} is out of range: {value}")
+            }
+            ApngError::UnknownDisposeOp(op) => write!(f, "Unknown fcTL dispose_op: {op}"),
+            ApngError::UnknownBlendOp(op) => write!(f, "Unknown fcTL blend_op: {op}"),
+        }
+    }
+}
+
+impl Error for ApngError {}
+
+// ----------TESTS-------------//
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk_type::ChunkType;
+    use std::str::FromStr;
+
+    fn chunk(chunk_type: &str, data: Vec<u8>) -> Chunk {
+        Chunk::new(ChunkType::from_str(chunk_type).unwrap(), data)
+    }
+
+    fn frame_control(dispose_op: u8, blend_op: u8) -> Vec<u8> {
+        let mut data = Vec::new();
+        for value in [3u32, 16, 8, 2, 4] {
+            data.extend(value.to_be_bytes());
+        }
+        data.extend(1u16.to_be_bytes());
+        data.extend(0u16.to_be_bytes());
+        data.extend([dispose_op, blend_op]);
+        data
+    }
+
+    #[test]
+    fn test_animation_control() {
+        let actl =
+            AnimationControl::try_from(&chunk("acTL", vec![0, 0, 0, 24, 0, 0, 0, 0])).unwrap();
+        assert_eq!(actl.num_frames(), 24);
+        assert_eq!(actl.num_plays(), 0);
+        assert_eq!(actl.to_string(), "24 frames, loops forever");
+
+        let actl =
+            AnimationControl::try_from(&chunk("acTL", vec![0, 0, 0, 1, 0, 0, 0, 3])).unwrap();
+        assert_eq!(actl.to_string(), "1 frame, plays 3 times");
+    }
+
+    #[test]
+    fn test_animation_control_ranges() {
+        let err = AnimationControl::try_from(&chunk("acTL", vec![0; 8])).unwrap_err();
+        assert_eq!(err.to_string(), "acTL num_frames is out of range: 0");
+
+        let err =
+            AnimationControl::try_from(&chunk("acTL", vec![0, 0, 0, 1, 128, 0, 0, 0])).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "acTL num_plays is out of range: 2147483648"
+        );
+
+        assert!(AnimationControl::try_from(&chunk("acTL", vec![0, 0, 0, 1])).is_err());
+    }
+
+    #[test]
+    fn test_frame_control() {
+        let fctl = FrameControl::try_from(&chunk("fcTL", frame_control(1, 1))).unwrap();
+        assert_eq!(fctl.sequence_number(), 3);
+        assert_eq!((fctl.width(), fctl.height()), (16, 8));
+        assert_eq!((fctl.x_offset(), fctl.y_offset()), (2, 4));
+        assert_eq!(fctl.delay(), 0.01);
+        assert_eq!(fctl.dispose_op(), DisposeOp::Background);
+        assert_eq!(fctl.blend_op(), BlendOp::Over);
+        assert_eq!(fctl.to_string(), "frame 3: 16x8 at (2, 4), 0.010s");
+    }
+
+    #[test]
+    fn test_frame_control_ranges() {
+        let err = FrameControl::try_from(&chunk("fcTL", frame_control(3, 0))).unwrap_err();
+        assert_eq!(err.to_string(), "Unknown fcTL dispose_op: 3");
+        let err = FrameControl::try_from(&chunk("fcTL", frame_control(0, 2))).unwrap_err();
+        assert_eq!(err.to_string(), "Unknown fcTL blend_op: 2");
+
+        let mut data = frame_control(0, 0);
+        data[4..8].copy_from_slice(&[0; 4]);
+        let err = FrameControl::try_from(&chunk("fcTL", data)).unwrap_err();
+        assert_eq!(err.to_string(), "fcTL width is out of range: 0");
+
+        assert!(FrameControl::try_from(&chunk("fcTL", vec![0; 25])).is_err());
+    }
+
+    #[test]
+    fn test_frame_data() {
+        let chunk = chunk("fdAT", vec![0, 0, 0, 5, 120, 156]);
+        let fdat = FrameData::try_from(&chunk).unwrap();
+        assert_eq!(fdat.sequence_number(), 5);
+        assert_eq!(fdat.data(), &[120, 156]);
+
+        let short = Chunk::new(ChunkType::from_str("fdAT").unwrap(), vec![0, 0]);
+        let err = FrameData::try_from(&short).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "fdAT data must be at least 4 bytes long, found 2"
+        );
+    }
+}
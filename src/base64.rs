@@ -0,0 +1,81 @@
+#![allow(dead_code)]
+
+//! Standard-alphabet base64 (RFC 4648) with `=` padding.
+
+use ::base64::engine::general_purpose::STANDARD;
+use ::base64::{DecodeError, Engine};
+use std::error::Error;
+use std::fmt;
+
+pub fn encode(bytes: &[u8]) -> String {
+    STANDARD.encode(bytes)
+}
+
+/// Decodes padded base64, rejecting characters outside the standard alphabet.
+pub fn decode(encoded: &str) -> crate::Result<Vec<u8>> {
+    Ok(STANDARD.decode(encoded).map_err(Base64Error)?)
+}
+
+#[derive(Debug)]
+pub struct Base64Error(DecodeError);
+
+impl fmt::Display for Base64Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid base64: {}", self.0)
+    }
+}
+
+impl Error for Base64Error {}
+
+// ----------TESTS-------------//
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_rfc_vectors() {
+        let vectors = [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ];
+        for (input, expected) in vectors {
+            assert_eq!(encode(input.as_bytes()), expected);
+        }
+    }
+
+    #[test]
+    fn test_decode_rfc_vectors() {
+        let vectors = [
+            ("", ""),
+            ("Zg==", "f"),
+            ("Zm8=", "fo"),
+            ("Zm9v", "foo"),
+            ("Zm9vYg==", "foob"),
+            ("Zm9vYmE=", "fooba"),
+            ("Zm9vYmFy", "foobar"),
+        ];
+        for (input, expected) in vectors {
+            assert_eq!(decode(input).unwrap(), expected.as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        assert!(decode("Zm9").is_err());
+        assert!(decode("Zm9*").is_err());
+        assert!(decode("Zg==Zm9v").is_err());
+        assert!(decode("Z===").is_err());
+    }
+
+    #[test]
+    fn test_encode_binary() {
+        assert_eq!(encode(&[0xfb, 0xff, 0x00, 0x3e]), "+/8APg==");
+        assert_eq!(decode("+/8APg==").unwrap(), [0xfb, 0xff, 0x00, 0x3e]);
+    }
+}
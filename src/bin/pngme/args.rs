@@ -0,0 +1,561 @@
+use clap::{ColorChoice, Parser, ValueEnum};
+use pngme::chunk::CrcAlgorithm;
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+
+///A CLI Application to Embed Messages Into A PNG File!
+#[derive(Debug, Parser)]
+#[command(author, version, about, long_about)]
+pub struct Commands {
+    #[clap(subcommand)]
+    pub command: PngMeArgs,
+    /// Log progress to stderr. Repeat (`-vv`) for per-chunk parse traces. Sets
+    /// the default `RUST_LOG` filter, which takes precedence when set.
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    pub verbose: u8,
+    /// The CRC-32 variant used to verify and write chunk CRCs. PNG requires
+    /// iso-hdlc; the others are for non-conformant files.
+    #[arg(long, value_enum, default_value_t = CrcAlgo::IsoHdlc, global = true)]
+    pub crc_algo: CrcAlgo,
+    /// Reject any chunk whose data is longer than this many bytes while parsing.
+    /// Defaults to the PNG maximum of 2147483647.
+    #[arg(long, value_name = "BYTES", global = true, value_parser = clap::value_parser!(u32).range(..=2_147_483_647))]
+    pub max_chunk_size: Option<u32>,
+    /// Print how long reading, parsing, the operation itself and writing took
+    /// to stderr, with the number of bytes processed.
+    #[arg(long, global = true)]
+    pub stats: bool,
+    /// Before writing a modified file, re-parse the new bytes and abort if any
+    /// chunk does not read back exactly as written with a valid CRC.
+    #[arg(long, global = true)]
+    pub self_check: bool,
+    /// When to color chunk types in `print` and `list` output. `auto` colors
+    /// only a terminal, and not when `NO_COLOR` is set.
+    #[arg(long, value_enum, value_name = "WHEN", default_value_t = ColorChoice::Auto, global = true)]
+    pub color: ColorChoice,
+}
+
+/// Represents the different subcommands that the application can accept.
+/// Each variant corresponds to a different operation that can be performed on a PNG file.
+// Parsed once per run, so the size of the largest variant doesn't matter.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Parser)]
+#[command(about, long_about)]
+pub enum PngMeArgs {
+    /// <FILE_PATH> | Represents the "encode" subcommand, which is used to embed a message into a PNG file.
+    Encode(EncodeArgs),
+    /// <FILE_PATH> | Represents the "decode" subcommand, which is used to extract a message from a PNG file.
+    Decode(DecodeArgs),
+    /// <FILE_PATH> | Represents the "remove" subcommand, which is used to remove a message from a PNG file.
+    Remove(RemoveArgs),
+    /// <FILE_PATH> | Represents the "print" subcommand, which is used to print the chunks of a PNG file.
+    Print(PrintArgs),
+    /// <FILE_PATH> | Represents the "list" subcommand, which is used to print a table of the chunks in a PNG file.
+    List(ListArgs),
+    /// <FILE_PATH> | Represents the "hide" subcommand, which is used to hide a message in the pixels of a PNG file.
+    Hide(HideArgs),
+    /// <FILE_PATH> | Represents the "reveal" subcommand, which is used to extract a message hidden in the pixels of a PNG file.
+    Reveal(RevealArgs),
+    /// <FILE_PATH> | Represents the "validate" subcommand, which is used to check the chunk layout of a PNG file.
+    Validate(ValidateArgs),
+    /// <FILE_PATH> | Represents the "info" subcommand, which is used to print the image properties of a PNG file.
+    Info(InfoArgs),
+    /// <FILE_PATH> | Represents the "count" subcommand, which is used to print how often each chunk type occurs in a PNG file.
+    Count(CountArgs),
+    /// <FILE_PATH> | Represents the "replace" subcommand, which is used to change the message stored in an existing chunk of a PNG file.
+    Replace(ReplaceArgs),
+    /// <FILE_PATH> | Represents the "check" subcommand, which is used to verify the CRC of every chunk in a PNG file.
+    Check(CheckArgs),
+    /// <FILE_PATH> <FILE_PATH> | Represents the "diff" subcommand, which is used to show which chunks differ between two PNG files.
+    Diff(DiffArgs),
+    /// <SOURCE> <TARGET> | Represents the "merge" subcommand, which is used to copy chunks from one PNG file into another.
+    Merge(MergeArgs),
+    /// <FILE_PATH> | Represents the "interactive" subcommand, which is used to view, remove and extract chunks from a prompt.
+    Interactive(InteractiveArgs),
+    /// <FILE_PATH> | Represents the "palette" subcommand, which is used to print the colors in the palette of an indexed PNG file.
+    Palette(PaletteArgs),
+    /// <FILE_PATH> | Represents the "dedup" subcommand, which is used to remove repeated copies of identical ancillary chunks from a PNG file.
+    Dedup(DedupArgs),
+    /// <FILE_PATH> | Represents the "canonicalize" subcommand, which is used to reorder the chunks of a PNG file into the positions the PNG specification expects.
+    Canonicalize(CanonicalizeArgs),
+}
+
+/// Represents the arguments for the "encode" subcommand.
+///
+/// Positionals fill in order, so with the chunk type omitted the message lands
+/// in `chunk_type`; `encode` moves it over before using them.
+#[derive(Debug, Parser)]
+pub struct EncodeArgs {
+    /// The path to the PNG file to encode a message into, or `-` for stdin.
+    pub file_path: PathBuf,
+    /// The type of the chunk to encode the message into. Defaults to
+    /// `PNGME_CHUNK_TYPE` when omitted.
+    pub chunk_type: Option<String>,
+    /// The message to encode into the PNG file.
+    pub message: Option<String>,
+    /// Encode a chunk of type TYPE holding MESSAGE. Repeat to add several chunks
+    /// in one pass over the file; they are added in the order given.
+    #[arg(
+        long = "chunk",
+        value_name = "TYPE=MESSAGE",
+        conflicts_with_all = ["chunk_type", "message", "message_file", "text", "itxt"]
+    )]
+    pub chunks: Vec<String>,
+    /// Read the message from a file instead, or from stdin with `-`. The bytes are
+    /// embedded verbatim.
+    #[arg(long, value_name = "PATH", conflicts_with = "message")]
+    pub message_file: Option<PathBuf>,
+    /// Treat the message as base64 and embed the decoded bytes.
+    #[arg(long, conflicts_with_all = ["chunks", "message_file", "text", "itxt"])]
+    pub base64: bool,
+    /// Encode a spec-compliant tEXt chunk instead, holding a Latin-1 keyword and value.
+    #[arg(long, value_name = "KEYWORD=VALUE", conflicts_with_all = ["chunk_type", "message", "message_file", "encrypt"])]
+    pub text: Option<String>,
+    /// Encode an iTXt chunk instead, holding a Latin-1 keyword and a UTF-8 value.
+    #[arg(
+        long,
+        value_name = "KEYWORD=VALUE",
+        conflicts_with_all = ["chunk_type", "message", "message_file", "encrypt", "text"]
+    )]
+    pub itxt: Option<String>,
+    /// The language tag of the iTXt text, e.g. `en` or `de-DE`.
+    #[arg(long, requires = "itxt", default_value = "")]
+    pub language: String,
+    /// The keyword translated into the iTXt language.
+    #[arg(long, requires = "itxt", default_value = "")]
+    pub translated_keyword: String,
+    /// Compress the iTXt text with zlib.
+    #[arg(long, requires = "itxt")]
+    pub compress_text: bool,
+    /// The path to the output file, or `-` for stdout. If not provided, the original file
+    /// will be overwritten. Required when reading from stdin.
+    #[arg(short, long = "output", value_name = "OUTPUT_FILE")]
+    pub output_file: Option<PathBuf>,
+    /// The output file given positionally after the message, as before
+    /// `--output` existed. Kept so existing scripts keep working.
+    #[arg(
+        hide = true,
+        value_name = "OUTPUT_FILE",
+        requires = "message",
+        conflicts_with_all = ["output_file", "batch", "output_dir"]
+    )]
+    pub legacy_output_file: Option<PathBuf>,
+    /// More PNG files to encode the same chunk into. Each file is written back in
+    /// place unless `--output-dir` is given. Wildcard patterns like `*.png` are
+    /// expanded here and in FILE_PATH.
+    #[arg(long, value_name = "FILE_PATH", num_args = 1.., conflicts_with = "output_file")]
+    pub batch: Vec<PathBuf>,
+    /// Write encoded files into this directory, keeping their file names, instead
+    /// of overwriting them.
+    #[arg(long, value_name = "DIR", conflicts_with = "output_file")]
+    pub output_dir: Option<PathBuf>,
+    /// Encode up to N of the batch files at the same time.
+    #[arg(short, long, value_name = "N", default_value = "1", requires = "batch")]
+    pub jobs: NonZeroUsize,
+    #[command(flatten)]
+    pub backup: BackupArgs,
+    /// Compress the message with zlib before embedding it. decode recognizes
+    /// and inflates compressed messages on its own.
+    #[arg(long, conflicts_with_all = ["text", "itxt"])]
+    pub compress: bool,
+    /// Encrypt the message with AES-256-GCM using a key derived from the password.
+    #[arg(long, requires = "password")]
+    pub encrypt: bool,
+    /// The password to encrypt the message with.
+    #[arg(long, env = "PNGME_PASSWORD", hide_env_values = true)]
+    pub password: Option<String>,
+    /// Append an HMAC-SHA256 of the message to the chunk data so `decode
+    /// --verify` can detect tampering. Applied after compression and encryption.
+    #[arg(long, requires = "key", conflicts_with_all = ["text", "itxt"])]
+    pub sign: bool,
+    /// The key to sign the message with.
+    #[arg(long, env = "PNGME_KEY", hide_env_values = true)]
+    pub key: Option<String>,
+    /// Replace the first existing chunk of the same type instead of adding
+    /// another, so encoding the same message again leaves the file unchanged.
+    #[arg(long)]
+    pub upsert: bool,
+    /// Print how much the file can hold and refuse to write if the message
+    /// does not fit.
+    #[arg(long)]
+    pub check_capacity: bool,
+    /// Print the chunk count and byte size before and after encoding to stderr.
+    #[arg(long)]
+    pub summary: bool,
+    /// Don't warn about chunk types that use the public bit without being
+    /// registered, or standard types holding data they weren't defined for.
+    #[arg(long)]
+    pub no_warn: bool,
+}
+
+/// Represents the arguments for the "decode" subcommand.
+#[derive(Debug, Parser)]
+#[command(allow_missing_positional = true)]
+pub struct DecodeArgs {
+    /// The types of the chunks to decode messages from, e.g. `tEXt zTXt iTXt`.
+    /// Defaults to `PNGME_CHUNK_TYPE` when omitted.
+    #[arg(num_args = 1..)]
+    pub chunk_types: Vec<String>,
+    /// The path to the PNG file to decode a message from, or `-` for stdin. Given
+    /// a directory, every `.png` file in it is decoded, and given a wildcard
+    /// pattern like `images/*.png`, every matching file.
+    pub file_path: PathBuf,
+    /// When decoding a directory, also scan its subdirectories.
+    #[arg(short, long)]
+    pub recursive: bool,
+    /// Don't check chunk CRCs, so messages can be recovered from damaged or
+    /// hand-edited files.
+    #[arg(long)]
+    pub no_verify: bool,
+    /// Don't warn when no chunk of the given type is found.
+    #[arg(short, long)]
+    pub quiet: bool,
+    /// Match chunk types regardless of case, so `text` finds `tEXt`. Case is
+    /// meaningful in PNG: it sets the critical, public and safe-to-copy bits, so
+    /// this can match chunks that mean something else to a decoder.
+    #[arg(long)]
+    pub ignore_case: bool,
+    /// Print the chunk data base64-encoded instead of as text.
+    #[arg(long)]
+    pub base64: bool,
+    /// Split the data of any chunk type at its first NUL byte and print the
+    /// keyword and value separately, as text when printable and hex otherwise.
+    #[arg(long, conflicts_with_all = ["base64", "decrypt"])]
+    pub split_null: bool,
+    /// Escape every byte outside printable ASCII as `\xNN` so control
+    /// characters can't garble the terminal.
+    #[arg(long, conflicts_with = "base64")]
+    pub ascii_only: bool,
+    /// Decrypt messages that were encoded with `--encrypt`.
+    #[arg(long, requires = "password")]
+    pub decrypt: bool,
+    /// The password the message was encrypted with.
+    #[arg(long, env = "PNGME_PASSWORD", hide_env_values = true)]
+    pub password: Option<String>,
+    /// Check the HMAC of messages encoded with `--sign` and report whether
+    /// each signature is valid. Fails if any is not.
+    #[arg(long = "verify", requires = "key")]
+    pub verify_signature: bool,
+    /// The key the message was signed with.
+    #[arg(long, env = "PNGME_KEY", hide_env_values = true)]
+    pub key: Option<String>,
+}
+
+/// Represents the arguments for the "remove" subcommand.
+#[derive(Debug, Parser)]
+#[command(allow_missing_positional = true)]
+pub struct RemoveArgs {
+    /// The type of the chunk to remove the message from. Defaults to
+    /// `PNGME_CHUNK_TYPE` when omitted.
+    pub chunk_type: Option<String>,
+    /// The path to the PNG file to remove a message from, or `-` for stdin.
+    pub file_path: PathBuf,
+    /// The path to the output file, or `-` for stdout. If not provided, the original file
+    /// will be overwritten. Required when reading from stdin.
+    #[arg(short, long = "output", value_name = "OUTPUT_FILE")]
+    pub output_file: Option<PathBuf>,
+    /// Remove every chunk of the given type instead of just the first. Fails if
+    /// there are none, like removing a single chunk.
+    #[arg(short, long)]
+    pub all: bool,
+    /// Remove the Nth chunk of the given type instead of the first, counting from 0.
+    #[arg(short, long, value_name = "N", conflicts_with = "all")]
+    pub index: Option<usize>,
+    /// Match the chunk type regardless of case, so `text` removes `tEXt`. Case
+    /// is meaningful in PNG: it sets the critical, public and safe-to-copy bits,
+    /// so this can remove chunks that mean something else to a decoder.
+    #[arg(long)]
+    pub ignore_case: bool,
+    /// Print the chunk count and byte size before and after removing to stderr.
+    #[arg(long)]
+    pub summary: bool,
+    #[command(flatten)]
+    pub backup: BackupArgs,
+}
+
+/// Options for keeping a copy of a file before it is overwritten in place.
+#[derive(Debug, Clone, Parser)]
+pub struct BackupArgs {
+    /// Copy the original file to `<FILE_PATH><SUFFIX>` before overwriting it.
+    #[arg(long)]
+    pub backup: bool,
+    /// The suffix appended to the file name of the backup.
+    #[arg(
+        long,
+        value_name = "SUFFIX",
+        default_value = ".bak",
+        requires = "backup"
+    )]
+    pub backup_suffix: String,
+    /// Overwrite an existing backup file, and make a read-only output file
+    /// writable before overwriting it.
+    #[arg(long)]
+    pub force: bool,
+}
+
+/// Represents the arguments for the "print" subcommand.
+#[derive(Debug, Parser)]
+pub struct PrintArgs {
+    /// The path to the PNG file to print the chunks from, or `-` for stdin.
+    pub file_path: PathBuf,
+    /// Print each chunk's data as a hex dump instead of text.
+    #[arg(long)]
+    pub hex: bool,
+    /// The maximum number of data bytes to hex dump per chunk.
+    #[arg(long, value_name = "N", requires = "hex")]
+    pub max_bytes: Option<usize>,
+    /// The output format.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text, conflicts_with = "hex")]
+    pub format: OutputFormat,
+    /// Only print ancillary chunks, whose type starts with a lowercase letter.
+    #[arg(long, conflicts_with = "critical_only")]
+    pub ancillary_only: bool,
+    /// Only print critical chunks, whose type starts with an uppercase letter.
+    #[arg(long)]
+    pub critical_only: bool,
+    /// Don't check chunk CRCs, so damaged or hand-edited files can be inspected.
+    #[arg(long)]
+    pub no_verify: bool,
+    /// Print each chunk as a block of labeled type, length, CRC and data lines.
+    #[arg(short, long, conflicts_with_all = ["hex", "format"])]
+    pub detailed: bool,
+    /// Escape every byte outside printable ASCII as `\xNN` so control
+    /// characters can't garble the terminal.
+    #[arg(long, conflicts_with_all = ["hex", "format", "detailed"])]
+    pub ascii_only: bool,
+    /// Keep running and print the chunks again whenever the file changes on
+    /// disk. Stop with Ctrl-C.
+    #[arg(short, long, conflicts_with = "output_file")]
+    pub watch: bool,
+    /// With `--format raw`, the path to write the PNG to instead of stdout.
+    #[arg(
+        short,
+        long = "output",
+        value_name = "OUTPUT_FILE",
+        requires = "format"
+    )]
+    pub output_file: Option<PathBuf>,
+}
+
+/// Represents the arguments for the "list" subcommand.
+#[derive(Debug, Parser)]
+pub struct ListArgs {
+    /// The path to the PNG file to list the chunks of, or `-` for stdin. Given a
+    /// directory, every `.png` file in it is listed, and given a wildcard
+    /// pattern like `images/*.png`, every matching file.
+    pub file_path: PathBuf,
+    /// When listing a directory, also scan its subdirectories.
+    #[arg(short, long)]
+    pub recursive: bool,
+    /// The output format.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+    /// Also print how many bytes the chunks of each type take up in the file.
+    #[arg(long, conflicts_with = "format")]
+    pub sizes: bool,
+    /// Only list chunks whose type matches this regular expression.
+    #[arg(long, value_name = "REGEX")]
+    pub filter: Option<String>,
+    /// Also print a bar chart of how many chunks have data lengths in each
+    /// power-of-two range, to show how fragmented the file is.
+    #[arg(long, conflicts_with = "format")]
+    pub histogram: bool,
+}
+
+/// The values of `--crc-algo`, one for each `CrcAlgorithm`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CrcAlgo {
+    IsoHdlc,
+    Autosar,
+    Bzip2,
+    Cksum,
+    Iscsi,
+    Jamcrc,
+    Mpeg2,
+    Xfer,
+}
+
+impl From<CrcAlgo> for CrcAlgorithm {
+    fn from(algo: CrcAlgo) -> Self {
+        match algo {
+            CrcAlgo::IsoHdlc => CrcAlgorithm::IsoHdlc,
+            CrcAlgo::Autosar => CrcAlgorithm::Autosar,
+            CrcAlgo::Bzip2 => CrcAlgorithm::Bzip2,
+            CrcAlgo::Cksum => CrcAlgorithm::Cksum,
+            CrcAlgo::Iscsi => CrcAlgorithm::Iscsi,
+            CrcAlgo::Jamcrc => CrcAlgorithm::Jamcrc,
+            CrcAlgo::Mpeg2 => CrcAlgorithm::Mpeg2,
+            CrcAlgo::Xfer => CrcAlgorithm::Xfer,
+        }
+    }
+}
+
+/// The output formats of the "print" and "list" subcommands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable plain text.
+    Text,
+    /// A JSON array with one object per chunk.
+    Json,
+    /// The image itself, as a minimal PNG of only its public critical chunks.
+    /// Only `print` supports it.
+    Raw,
+}
+
+/// Represents the arguments for the "hide" subcommand.
+#[derive(Debug, Parser)]
+pub struct HideArgs {
+    /// The path to the PNG file to hide a message in, or `-` for stdin.
+    pub file_path: PathBuf,
+    /// The message to hide in the least significant bits of the image's pixels.
+    pub message: String,
+    /// The path to the output file, or `-` for stdout. If not provided, the original file
+    /// will be overwritten. Required when reading from stdin.
+    #[arg(short, long = "output", value_name = "OUTPUT_FILE")]
+    pub output_file: Option<PathBuf>,
+    /// Print how much the pixels can hold and refuse to write if the message
+    /// does not fit.
+    #[arg(long)]
+    pub check_capacity: bool,
+}
+
+/// Represents the arguments for the "reveal" subcommand.
+#[derive(Debug, Parser)]
+pub struct RevealArgs {
+    /// The path to the PNG file to reveal a hidden message from, or `-` for stdin.
+    pub file_path: PathBuf,
+}
+
+/// Represents the arguments for the "validate" subcommand.
+#[derive(Debug, Parser)]
+pub struct ValidateArgs {
+    /// The path to the PNG file to validate, or `-` for stdin.
+    pub file_path: PathBuf,
+}
+
+/// Represents the arguments for the "info" subcommand.
+#[derive(Debug, Parser)]
+pub struct InfoArgs {
+    /// The path to the PNG file to print the image properties of, or `-` for stdin.
+    pub file_path: PathBuf,
+}
+
+/// Represents the arguments for the "palette" subcommand.
+#[derive(Debug, Parser)]
+pub struct PaletteArgs {
+    /// The path to the PNG file to print the palette of, or `-` for stdin.
+    pub file_path: PathBuf,
+}
+
+/// Represents the arguments for the "dedup" subcommand.
+#[derive(Debug, Parser)]
+pub struct DedupArgs {
+    /// The path to the PNG file to remove duplicate chunks from, or `-` for stdin.
+    pub file_path: PathBuf,
+    /// Only remove duplicates of this chunk type. Critical chunks are never
+    /// removed either way.
+    #[arg(short = 't', long = "type", value_name = "CHUNK_TYPE")]
+    pub chunk_type: Option<String>,
+    /// The path to the output file, or `-` for stdout. If not provided, the original file
+    /// will be overwritten. Required when reading from stdin.
+    #[arg(short, long = "output", value_name = "OUTPUT_FILE")]
+    pub output_file: Option<PathBuf>,
+    #[command(flatten)]
+    pub backup: BackupArgs,
+}
+
+/// Represents the arguments for the "canonicalize" subcommand.
+#[derive(Debug, Parser)]
+pub struct CanonicalizeArgs {
+    /// The path to the PNG file to reorder the chunks of, or `-` for stdin.
+    pub file_path: PathBuf,
+    /// The path to the output file, or `-` for stdout. If not provided, the original file
+    /// will be overwritten. Required when reading from stdin.
+    #[arg(short, long = "output", value_name = "OUTPUT_FILE")]
+    pub output_file: Option<PathBuf>,
+    #[command(flatten)]
+    pub backup: BackupArgs,
+}
+
+/// Represents the arguments for the "count" subcommand.
+#[derive(Debug, Parser)]
+pub struct CountArgs {
+    /// The path to the PNG file to count the chunk types of, or `-` for stdin.
+    pub file_path: PathBuf,
+    /// Print a final line with the total number of chunks.
+    #[arg(short, long)]
+    pub total: bool,
+    /// Only count chunks whose type matches this regular expression.
+    #[arg(long, value_name = "REGEX")]
+    pub filter: Option<String>,
+    /// Also print a bar chart of how many chunks have data lengths in each
+    /// power-of-two range, to show how fragmented the file is.
+    #[arg(long)]
+    pub histogram: bool,
+}
+
+/// Represents the arguments for the "replace" subcommand.
+#[derive(Debug, Parser)]
+pub struct ReplaceArgs {
+    /// The path to the PNG file to replace a message in, or `-` for stdin.
+    pub file_path: PathBuf,
+    /// The type of the chunk whose message is replaced. Only the first chunk of this type is changed.
+    pub chunk_type: String,
+    /// The new message.
+    pub message: String,
+    /// The path to the output file, or `-` for stdout. If not provided, the original file
+    /// will be overwritten. Required when reading from stdin.
+    #[arg(short, long = "output", value_name = "OUTPUT_FILE")]
+    pub output_file: Option<PathBuf>,
+    #[command(flatten)]
+    pub backup: BackupArgs,
+}
+
+/// Represents the arguments for the "check" subcommand.
+#[derive(Debug, Parser)]
+pub struct CheckArgs {
+    /// The path to the PNG file to check, or `-` for stdin.
+    pub file_path: PathBuf,
+}
+
+/// Represents the arguments for the "merge" subcommand.
+#[derive(Debug, Parser)]
+pub struct MergeArgs {
+    /// The path to the PNG file to copy chunks from, or `-` for stdin.
+    pub source: PathBuf,
+    /// The path to the PNG file to copy the chunks into.
+    pub target: PathBuf,
+    /// The types of the chunks to copy, e.g. `iCCP ruSt`. Every chunk of each
+    /// type is copied, in order, ahead of the target's `IEND`.
+    #[arg(required = true, num_args = 1..)]
+    pub chunk_types: Vec<String>,
+    /// Remove the target's own chunks of the copied types first, instead of
+    /// keeping them alongside the copies.
+    #[arg(long)]
+    pub replace: bool,
+    /// The path to the output file, or `-` for stdout. If not provided, the target file
+    /// will be overwritten.
+    #[arg(short, long = "output", value_name = "OUTPUT_FILE")]
+    pub output_file: Option<PathBuf>,
+    #[command(flatten)]
+    pub backup: BackupArgs,
+}
+
+/// Represents the arguments for the "interactive" subcommand.
+#[derive(Debug, Parser)]
+pub struct InteractiveArgs {
+    /// The path to the PNG file to edit. Changes are only written back after
+    /// confirming on quit.
+    pub file_path: PathBuf,
+    #[command(flatten)]
+    pub backup: BackupArgs,
+}
+
+/// Represents the arguments for the "diff" subcommand.
+#[derive(Debug, Parser)]
+pub struct DiffArgs {
+    /// The path to the original PNG file, or `-` for stdin.
+    pub original: PathBuf,
+    /// The path to the PNG file to compare against the original.
+    pub modified: PathBuf,
+}
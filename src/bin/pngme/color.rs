@@ -0,0 +1,99 @@
+//! ANSI colors for the chunk types in `print` and `list` output. Off unless
+//! `main` turns it on for `--color always`, or for `auto` when stdout is a
+//! terminal and `NO_COLOR` is unset.
+
+use pngme::chunk::{Chunk, CrcAlgorithm};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Style {
+    Critical,
+    Ancillary,
+    Error,
+}
+
+impl Style {
+    fn code(self) -> &'static str {
+        match self {
+            Style::Critical => "1;33",
+            Style::Ancillary => "36",
+            Style::Error => "1;31",
+        }
+    }
+}
+
+/// Wraps `text` in the escape codes for `style` when colors are on.
+pub fn paint(text: &str, style: Style) -> String {
+    if ENABLED.load(Ordering::Relaxed) {
+        ansi(text, style)
+    } else {
+        text.to_string()
+    }
+}
+
+/// The chunk's type, red if its CRC doesn't match under `algorithm` and
+/// otherwise colored by whether it is critical or ancillary.
+pub fn chunk_type(chunk: &Chunk, algorithm: CrcAlgorithm) -> String {
+    // Picking the style checksums the whole chunk, so skip it when it'd be thrown away.
+    if !ENABLED.load(Ordering::Relaxed) {
+        return chunk.chunk_type().to_string();
+    }
+    paint(chunk.chunk_type().as_str(), style_of(chunk, algorithm))
+}
+
+fn style_of(chunk: &Chunk, algorithm: CrcAlgorithm) -> Style {
+    if !chunk.verify_crc_with(algorithm) {
+        Style::Error
+    } else if chunk.is_critical() {
+        Style::Critical
+    } else {
+        Style::Ancillary
+    }
+}
+
+fn ansi(text: &str, style: Style) -> String {
+    format!("\x1b[{}m{text}\x1b[0m", style.code())
+}
+
+// ----------TESTS-------------//
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pngme::chunk_type::ChunkType;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_style_of() {
+        let chunk = |chunk_type| Chunk::new(ChunkType::from_str(chunk_type).unwrap(), vec![1]);
+        assert_eq!(
+            style_of(&chunk("IDAT"), CrcAlgorithm::IsoHdlc),
+            Style::Critical
+        );
+        assert_eq!(
+            style_of(&chunk("tEXt"), CrcAlgorithm::IsoHdlc),
+            Style::Ancillary
+        );
+
+        let broken =
+            Chunk::new_with_all_fields(1, ChunkType::from_str("IDAT").unwrap(), vec![1], 0);
+        assert_eq!(style_of(&broken, CrcAlgorithm::IsoHdlc), Style::Error);
+    }
+
+    #[test]
+    fn test_paint() {
+        assert_eq!(ansi("IHDR", Style::Critical), "\x1b[1;33mIHDR\x1b[0m");
+        // Colors are never turned on in tests.
+        assert_eq!(paint("IHDR", Style::Critical), "IHDR");
+
+        let broken =
+            Chunk::new_with_all_fields(1, ChunkType::from_str("IDAT").unwrap(), vec![1], 0);
+        assert_eq!(chunk_type(&broken, CrcAlgorithm::IsoHdlc), "IDAT");
+    }
+}
@@ -0,0 +1,3916 @@
+#![allow(dead_code)]
+
+use log::info;
+use pngme::base64;
+use pngme::chunk::{self, Chunk, CrcAlgorithm, ParseOptions, MAXIMUM_LENGTH};
+use pngme::chunk_type::ChunkType;
+use pngme::crypto;
+use pngme::lsb;
+use pngme::png::{Png, PngDecodeError};
+use pngme::text::{CompressedTextChunk, InternationalTextChunk, TextChunk};
+use pngme::zlib;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::fs::File;
+use std::io::{self, BufRead, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+use std::time::Instant;
+
+use crate::args::{
+    BackupArgs, CanonicalizeArgs, CheckArgs, CountArgs, DecodeArgs, DedupArgs, DiffArgs,
+    EncodeArgs, HideArgs, InfoArgs, InteractiveArgs, ListArgs, MergeArgs, OutputFormat,
+    PaletteArgs, PngMeArgs, PrintArgs, RemoveArgs, ReplaceArgs, RevealArgs, ValidateArgs,
+};
+use crate::color;
+use crate::progress::Tracked;
+use crate::stats::{self, Phase};
+use crate::watch;
+use crate::Result;
+
+/// The environment variable holding the chunk type to use when none is given.
+const CHUNK_TYPE_VAR: &str = "PNGME_CHUNK_TYPE";
+
+static SELF_CHECK: AtomicBool = AtomicBool::new(false);
+
+/// Makes every write re-parse the new file first and refuse to write it if
+/// it does not round-trip, for `--self-check`.
+pub fn set_self_check(enabled: bool) {
+    SELF_CHECK.store(enabled, Ordering::Relaxed);
+}
+
+pub fn run(command: PngMeArgs, options: &ParseOptions) -> Result<ExitCode> {
+    match command {
+        PngMeArgs::Encode(args) => encode(args, options),
+        PngMeArgs::Decode(args) => decode(args, options),
+        PngMeArgs::Remove(args) => remove(args, options).map(|_| ExitCode::SUCCESS),
+        PngMeArgs::Print(args) => print(args, options).map(|_| ExitCode::SUCCESS),
+        PngMeArgs::List(args) => list(args, options).map(|_| ExitCode::SUCCESS),
+        PngMeArgs::Hide(args) => hide(args, options).map(|_| ExitCode::SUCCESS),
+        PngMeArgs::Reveal(args) => reveal(args, options).map(|_| ExitCode::SUCCESS),
+        PngMeArgs::Validate(args) => validate(args, options).map(|_| ExitCode::SUCCESS),
+        PngMeArgs::Info(args) => info(args, options).map(|_| ExitCode::SUCCESS),
+        PngMeArgs::Count(args) => count(args, options).map(|_| ExitCode::SUCCESS),
+        PngMeArgs::Replace(args) => replace(args, options).map(|_| ExitCode::SUCCESS),
+        PngMeArgs::Check(args) => check(args, options),
+        PngMeArgs::Diff(args) => diff(args, options),
+        PngMeArgs::Merge(args) => merge(args, options).map(|_| ExitCode::SUCCESS),
+        PngMeArgs::Interactive(args) => interactive(args, options).map(|_| ExitCode::SUCCESS),
+        PngMeArgs::Palette(args) => palette(args, options).map(|_| ExitCode::SUCCESS),
+        PngMeArgs::Dedup(args) => dedup(args, options).map(|_| ExitCode::SUCCESS),
+        PngMeArgs::Canonicalize(args) => canonicalize(args, options).map(|_| ExitCode::SUCCESS),
+    }
+}
+
+fn encode(mut args: EncodeArgs, options: &ParseOptions) -> Result<ExitCode> {
+    sort_out_positionals(&mut args);
+    if args.batch.is_empty() && args.output_dir.is_none() && !is_pattern(&args.file_path) {
+        let output = output_path(&args.file_path, args.output_file.as_deref())?;
+        let chunks = encode_chunks(&args)?;
+        warn_about_chunk_types(&args, &chunks);
+        encode_file(&args, &args.file_path, output, chunks, options)?;
+
+        if is_std_stream(output) {
+            eprintln!("Secret successfully encoded!");
+        } else {
+            println!("Secret successfully encoded!");
+        }
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let chunks = encode_chunks(&args)?;
+    warn_about_chunk_types(&args, &chunks);
+    let paths: Vec<PathBuf> = std::iter::once(&args.file_path)
+        .chain(&args.batch)
+        .map(|path| {
+            if is_pattern(path) {
+                expand_pattern(path)
+            } else {
+                Ok(vec![path.clone()])
+            }
+        })
+        .collect::<Result<Vec<_>>>()?
+        .concat();
+    let next = AtomicUsize::new(0);
+    let (succeeded, failed) = (AtomicUsize::new(0), AtomicUsize::new(0));
+
+    // Each worker takes the next file until none are left, so one slow file
+    // doesn't hold up the rest. println! locks stdout, so lines never interleave.
+    thread::scope(|scope| {
+        for _ in 0..args.jobs.get().min(paths.len()) {
+            scope.spawn(|| {
+                while let Some(path) = paths.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    let result =
+                        batch_output_path(path, args.output_dir.as_deref()).and_then(|output| {
+                            encode_file(&args, path, &output, chunks.clone(), options)
+                        });
+                    match result {
+                        Ok(()) => {
+                            succeeded.fetch_add(1, Ordering::Relaxed);
+                            println!("{}: encoded", path.display());
+                        }
+                        Err(e) => {
+                            failed.fetch_add(1, Ordering::Relaxed);
+                            eprintln!("{}: {e}", path.display());
+                        }
+                    }
+                }
+            });
+        }
+    });
+
+    let (succeeded, failed) = (succeeded.into_inner(), failed.into_inner());
+    println!("{succeeded} succeeded, {failed} failed.");
+    if failed == 0 {
+        Ok(ExitCode::SUCCESS)
+    } else {
+        Ok(ExitCode::FAILURE)
+    }
+}
+
+/// Builds the chunks described by the encode arguments: one per `--chunk`, in
+/// the order given, or otherwise the single chunk from the other arguments.
+/// Messages are left whole here; `encode_file` splits any that are too long.
+fn encode_chunks(args: &EncodeArgs) -> Result<Vec<Chunk>> {
+    Ok(if args.chunks.is_empty() {
+        vec![encode_chunk(args)?]
+    } else {
+        args.chunks
+            .iter()
+            .map(|spec| {
+                let (chunk_type, message) = spec
+                    .split_once('=')
+                    .ok_or(CommandError::MissingArgument("TYPE=MESSAGE pair"))?;
+                let chunk_type = parse_chunk_type(chunk_type)?;
+                Ok(Chunk::new(
+                    chunk_type,
+                    seal_message(args, message.as_bytes().to_vec())?,
+                ))
+            })
+            .collect::<Result<_>>()?
+    })
+}
+
+/// Splits any chunk too long for the format over several of the same type.
+fn split_oversized(chunks: Vec<Chunk>) -> Vec<Chunk> {
+    chunks
+        .into_iter()
+        .flat_map(|chunk| {
+            if chunk.data().len() > MAXIMUM_LENGTH as usize {
+                Chunk::split(*chunk.chunk_type(), chunk.data(), MAXIMUM_LENGTH as usize)
+            } else {
+                vec![chunk]
+            }
+        })
+        .collect()
+}
+
+/// Fails with `MessageTooLarge` if any message is longer than `capacity`.
+fn ensure_fits(chunks: &[Chunk], capacity: usize) -> Result<()> {
+    match chunks.iter().find(|chunk| chunk.data().len() > capacity) {
+        Some(chunk) => Err(CommandError::MessageTooLarge(chunk.data().len(), capacity).into()),
+        None => Ok(()),
+    }
+}
+
+/// Puts the encode positionals where they belong: a lone one after the file is
+/// the message, and a fourth is the output file from before `--output`.
+fn sort_out_positionals(args: &mut EncodeArgs) {
+    let has_message = args.message.is_some()
+        || args.message_file.is_some()
+        || args.text.is_some()
+        || args.itxt.is_some()
+        || !args.chunks.is_empty();
+    if !has_message {
+        args.message = args.chunk_type.take();
+    }
+    if let Some(output) = args.legacy_output_file.take() {
+        args.output_file = Some(output);
+    }
+}
+
+/// Builds the chunk described by the encode arguments.
+fn encode_chunk(args: &EncodeArgs) -> Result<Chunk> {
+    Ok(match (&args.text, &args.itxt) {
+        (Some(text), _) => TextChunk::from_str(text)?.to_chunk()?,
+        (None, Some(itxt)) => {
+            let (keyword, text) = itxt
+                .split_once('=')
+                .ok_or(CommandError::MissingArgument("KEYWORD=VALUE pair"))?;
+            InternationalTextChunk::new(
+                keyword,
+                &args.language,
+                &args.translated_keyword,
+                text,
+                args.compress_text,
+            )?
+            .to_chunk()?
+        }
+        (None, None) => {
+            // Checked up front so a typo is reported before reading a message file.
+            let chunk_type = parse_chunk_type(&chunk_type_or_default(args.chunk_type.as_deref())?)?;
+            let message = match (&args.message, &args.message_file) {
+                (Some(_), Some(_)) => return Err(CommandError::AmbiguousMessage.into()),
+                (Some(message), None) if args.base64 => base64::decode(message)?,
+                (Some(message), None) => message.as_bytes().to_vec(),
+                (None, Some(path)) => {
+                    if is_std_stream(path) && is_std_stream(&args.file_path) {
+                        return Err(CommandError::StdinUsedTwice.into());
+                    }
+                    read_input(path)?
+                }
+                (None, None) => return Err(CommandError::MissingArgument("message").into()),
+            };
+            Chunk::new(chunk_type, seal_message(args, message)?)
+        }
+    })
+}
+
+/// Compresses `message` when `--compress` was given and then encrypts it when
+/// `--encrypt` was, since encrypted data no longer compresses. `--sign` covers
+/// the result, so tampering is caught before anything is decrypted.
+fn seal_message(args: &EncodeArgs, message: Vec<u8>) -> Result<Vec<u8>> {
+    let message = if args.compress {
+        zlib::compress_message(&message)
+    } else {
+        message
+    };
+    let message = match (args.encrypt, &args.password) {
+        (true, Some(password)) => crypto::encrypt(&message, password)?,
+        (true, None) => return Err(CommandError::PasswordRequired.into()),
+        (false, _) => message,
+    };
+    match (args.sign, &args.key) {
+        (true, Some(key)) => Ok(crypto::sign(&message, key)),
+        (true, None) => Err(CommandError::KeyRequired.into()),
+        (false, _) => Ok(message),
+    }
+}
+
+/// The chunk type given on the command line, or else the one in
+/// `PNGME_CHUNK_TYPE`, which must be a valid chunk type.
+fn chunk_type_or_default(given: Option<&str>) -> Result<String> {
+    let default = env::var_os(CHUNK_TYPE_VAR).map(|value| value.to_string_lossy().into_owned());
+    resolve_chunk_type(given, default)
+}
+
+fn resolve_chunk_type(given: Option<&str>, default: Option<String>) -> Result<String> {
+    match (given, default) {
+        (Some(chunk_type), _) => Ok(chunk_type.to_string()),
+        (None, Some(default)) if ChunkType::from_str(&default).is_ok() => Ok(default),
+        (None, Some(default)) => Err(CommandError::InvalidDefaultChunkType(default).into()),
+        (None, None) => Err(CommandError::ChunkTypeRequired.into()),
+    }
+}
+
+/// Parses a chunk type given on the command line, replacing the bare "invalid
+/// byte" error with an explanation of what chunk types look like.
+fn parse_chunk_type(chunk_type: &str) -> Result<ChunkType> {
+    ChunkType::from_str(chunk_type).map_err(|e| match e {
+        pngme::Error::ChunkType(_) => CommandError::InvalidChunkType(chunk_type.to_string()).into(),
+        e => e.into(),
+    })
+}
+
+/// Prints a warning to stderr for each chunk type in `chunks` that strict
+/// decoders may object to, unless `--no-warn` was given.
+fn warn_about_chunk_types(args: &EncodeArgs, chunks: &[Chunk]) {
+    if args.no_warn {
+        return;
+    }
+    let mut warned = Vec::new();
+    for chunk in chunks {
+        if warned.contains(chunk.chunk_type()) {
+            continue;
+        }
+        if let Some(warning) = chunk_type_warning(chunk) {
+            eprintln!("Warning: {warning}");
+            warned.push(*chunk.chunk_type());
+        }
+    }
+}
+
+/// Why `chunk`'s type may upset strict decoders: it claims to be public
+/// without being registered, or it is a standard type whose data doesn't
+/// follow that type's layout. Only the text types are checked for layout;
+/// any other standard type is assumed not to hold what it should.
+fn chunk_type_warning(chunk: &Chunk) -> Option<String> {
+    let chunk_type = chunk.chunk_type();
+    if !chunk_type.is_public() {
+        return None;
+    }
+    if !chunk_type.is_registered_public() {
+        return Some(format!(
+            "{chunk_type} has the public bit set but is not a registered chunk type; \
+             private chunks use a lowercase second letter, like {}",
+            chunk_type.to_private()
+        ));
+    }
+
+    let conforms = match chunk_type.as_str() {
+        "tEXt" => TextChunk::try_from(chunk).is_ok(),
+        "zTXt" => CompressedTextChunk::try_from(chunk).is_ok(),
+        "iTXt" => InternationalTextChunk::try_from(chunk).is_ok(),
+        _ => false,
+    };
+    (!conforms).then(|| {
+        format!(
+            "{chunk_type} is a standard chunk type ({}) and decoders will expect its \
+             data to follow the specification; use a private type instead",
+            chunk_type.description().unwrap_or_default().to_lowercase()
+        )
+    })
+}
+
+/// Adds `chunks` to the PNG at `path`, in order and ahead of `IEND`, and writes
+/// the result to `output`. With `--upsert` the first chunk of each type replaces
+/// the first one of that type already in the file, if there is one; any more of
+/// that type from the same call are added after it as usual.
+fn encode_file(
+    args: &EncodeArgs,
+    path: &Path,
+    output: &Path,
+    chunks: Vec<Chunk>,
+    options: &ParseOptions,
+) -> Result<()> {
+    let input = read_input(path)?;
+    let mut png: Png = parse_owned(input, options)?;
+    // Only measured when asked for, since it walks every chunk.
+    let before = args.summary.then(|| footprint(&png));
+
+    if args.check_capacity {
+        let capacity = png.embedding_capacity()?;
+        let report = format!(
+            "{}: message is {} bytes; capacity is {capacity}",
+            path.display(),
+            chunks.iter().map(|chunk| chunk.data().len()).sum::<usize>()
+        );
+        if is_std_stream(output) {
+            eprintln!("{report}");
+        } else {
+            println!("{report}");
+        }
+        // Checked before splitting, which would otherwise make anything fit.
+        ensure_fits(&chunks, capacity.chunk())?;
+    }
+
+    let mut upserted: Vec<ChunkType> = Vec::new();
+    for chunk in split_oversized(chunks) {
+        let chunk_type = *chunk.chunk_type();
+        if args.upsert && !upserted.contains(&chunk_type) {
+            upserted.push(chunk_type);
+            png.replace_chunk(chunk_type.as_str(), chunk);
+        } else if png.chunk_by_type("IEND").is_some() {
+            png.insert_chunk_before("IEND", chunk)?;
+        } else {
+            png.append_chunk(chunk)?;
+        }
+    }
+
+    backup_original(path, output, &args.backup)?;
+
+    make_writable(output, &args.backup)?;
+    write_output(output, &png, options)?;
+
+    if let Some(before) = before {
+        eprintln!("{}: {}", path.display(), summary(before, footprint(&png)));
+    }
+    Ok(())
+}
+
+/// The chunk count and byte size of a PNG, as reported by `--summary`.
+fn footprint(png: &Png) -> (usize, u64) {
+    (png.chunks().len(), png.total_size())
+}
+
+/// Describes how the chunk count and byte size changed, e.g.
+/// `chunks 5 -> 6 (+1), bytes 1024 -> 1046 (+22)`.
+fn summary(before: (usize, u64), after: (usize, u64)) -> String {
+    let delta = |from: u64, to: u64| to as i64 - from as i64;
+    format!(
+        "chunks {} -> {} ({:+}), bytes {} -> {} ({:+})",
+        before.0,
+        after.0,
+        delta(before.0 as u64, after.0 as u64),
+        before.1,
+        after.1,
+        delta(before.1, after.1)
+    )
+}
+
+/// Where a batch-encoded file is written: into `output_dir` under the same file
+/// name if given, otherwise back over the input.
+fn batch_output_path(input: &Path, output_dir: Option<&Path>) -> Result<PathBuf> {
+    if is_std_stream(input) {
+        return Err(CommandError::StdinInBatch.into());
+    }
+    match (output_dir, input.file_name()) {
+        (Some(dir), Some(name)) => Ok(dir.join(name)),
+        (Some(_), None) => Err(CommandError::MissingArgument("file name").into()),
+        (None, _) => Ok(input.to_path_buf()),
+    }
+}
+
+fn decode(mut args: DecodeArgs, options: &ParseOptions) -> Result<ExitCode> {
+    if args.chunk_types.is_empty() {
+        args.chunk_types.push(chunk_type_or_default(None)?);
+    }
+    if args.file_path.is_dir() || is_pattern(&args.file_path) {
+        return decode_directory(&args, options);
+    }
+
+    let input = read_input(&args.file_path)?;
+    let png = parse_png(&input, !args.no_verify, options)?;
+
+    if decode_png(&png, &args, None)? {
+        Ok(ExitCode::SUCCESS)
+    } else {
+        Ok(ExitCode::FAILURE)
+    }
+}
+
+/// Decodes every PNG in a directory or matching a wildcard pattern, prefixing
+/// each message with the file it came from. Files that fail to parse or decode
+/// are reported and skipped.
+fn decode_directory(args: &DecodeArgs, options: &ParseOptions) -> Result<ExitCode> {
+    let files = input_files(&args.file_path, args.recursive)?;
+    let (mut matched, mut skipped) = (0, 0);
+
+    for path in &files {
+        let result = read_input(path)
+            .and_then(|input| parse_png(&input, !args.no_verify, options))
+            .and_then(|png| decode_png(&png, args, Some(path)));
+        match result {
+            Ok(true) => matched += 1,
+            Ok(false) => {}
+            Err(e) => {
+                skipped += 1;
+                eprintln!("{}: skipped: {e}", path.display());
+            }
+        }
+    }
+
+    println!(
+        "{matched} of {} files matched, {skipped} skipped.",
+        files.len()
+    );
+    if matched > 0 {
+        Ok(ExitCode::SUCCESS)
+    } else {
+        Ok(ExitCode::FAILURE)
+    }
+}
+
+/// Prints the messages in `png`'s chunks of the requested types, each line
+/// prefixed with `path` when scanning a directory. Returns whether any chunk
+/// matched.
+fn decode_png(png: &Png, args: &DecodeArgs, path: Option<&Path>) -> Result<bool> {
+    let label = args.chunk_types.len() > 1 || path.is_some();
+    let mut found = false;
+    let mut tampered = false;
+
+    for chunk_type in &args.chunk_types {
+        let chunks: Vec<&Chunk> = png
+            .iter()
+            .filter(|chunk| type_is(chunk, chunk_type, args.ignore_case))
+            .collect();
+        if chunks.is_empty() {
+            // Most files in a directory won't have the chunk, so only warn for one file.
+            if !args.quiet && path.is_none() {
+                eprintln!("No chunk of type {chunk_type} found");
+            }
+            continue;
+        }
+        found = true;
+
+        for chunk in chunks {
+            let message = if args.verify_signature {
+                let key = args.key.as_deref().ok_or(CommandError::KeyRequired)?;
+                let (message, valid) = crypto::verify_signature(chunk.data(), key)?;
+                tampered |= !valid;
+                let unsigned = Chunk::new(*chunk.chunk_type(), message.to_vec());
+                let status = if valid { "valid" } else { "invalid" };
+                format!(
+                    "{}\tsignature {status}",
+                    decode_message(&unsigned, args, label)?
+                )
+            } else {
+                decode_message(chunk, args, label)?
+            };
+            match path {
+                Some(path) => println!("{}\t{message}", path.display()),
+                None => println!("{message}"),
+            }
+        }
+    }
+
+    if tampered {
+        return Err(CommandError::SignatureInvalid.into());
+    }
+    Ok(found)
+}
+
+/// Renders a single chunk's message for `decode`.
+fn decode_message(chunk: &Chunk, args: &DecodeArgs, label: bool) -> Result<String> {
+    // The signature is only checked with --verify, but it's never part of the message.
+    if crypto::is_signed(chunk.data()) {
+        let unsigned = Chunk::new(
+            *chunk.chunk_type(),
+            crypto::strip_signature(chunk.data()).to_vec(),
+        );
+        return decode_message(&unsigned, args, label);
+    }
+
+    let render = |bytes: &[u8]| {
+        if args.ascii_only {
+            chunk::escape_non_printable(bytes)
+        } else {
+            String::from_utf8_lossy(bytes).into_owned()
+        }
+    };
+    // Text chunks are decoded to strings before they are labeled, so with
+    // --ascii-only their UTF-8 is escaped instead of the raw bytes.
+    let labeled_text = |label, chunk, message: &dyn fmt::Display| {
+        render(labeled(label, chunk, message).as_bytes())
+    };
+
+    Ok(if args.decrypt {
+        let password = args
+            .password
+            .as_deref()
+            .ok_or(CommandError::PasswordRequired)?;
+        let message = inflated(crypto::decrypt(chunk.data(), password)?)?;
+        if args.base64 {
+            format!("{}\t{}", chunk.chunk_type(), base64::encode(&message))
+        } else {
+            format!("{}\t{}", chunk.chunk_type(), render(&message))
+        }
+    } else if args.base64 {
+        let message = inflated(chunk.data().to_vec())?;
+        format!("{}\t{}", chunk.chunk_type(), base64::encode(&message))
+    } else if args.split_null {
+        labeled_text(label, chunk, &split_at_null(chunk.data()))
+    } else if zlib::is_compressed_message(chunk.data()) {
+        let message = zlib::decompress_message(chunk.data())?;
+        format!("{}\t{}", chunk.chunk_type(), render(&message))
+    } else if crypto::is_encrypted(chunk.data()) {
+        format!("{}\t[encrypted, use --decrypt]", chunk.chunk_type())
+    } else if let Ok(text) = TextChunk::try_from(chunk) {
+        labeled_text(label, chunk, &text)
+    } else if let Ok(text) = InternationalTextChunk::try_from(chunk) {
+        labeled_text(label, chunk, &text)
+    } else if chunk.chunk_type().as_str() == "zTXt" {
+        // Falling back to the raw bytes would print compressed garbage.
+        labeled_text(label, chunk, &CompressedTextChunk::try_from(chunk)?)
+    } else {
+        format!("{}\t{}", chunk.chunk_type(), render(chunk.data()))
+    })
+}
+
+/// Inflates `message` if it was compressed by `encode --compress`.
+fn inflated(message: Vec<u8>) -> Result<Vec<u8>> {
+    if zlib::is_compressed_message(&message) {
+        Ok(zlib::decompress_message(&message)?)
+    } else {
+        Ok(message)
+    }
+}
+
+/// Splits `data` at its first NUL byte into labeled keyword and value lines,
+/// the layout shared by `tEXt`, `zTXt`, `iTXt`, `iCCP`, `sPLT` and many
+/// private chunks.
+fn split_at_null(data: &[u8]) -> String {
+    match data.iter().position(|&byte| byte == 0) {
+        Some(separator) => format!(
+            "Keyword: {}\nValue:   {}",
+            text_or_hex(&data[..separator]),
+            text_or_hex(&data[separator + 1..])
+        ),
+        None => format!(
+            "Keyword: (no NUL separator)\nValue:   {}",
+            text_or_hex(data)
+        ),
+    }
+}
+
+/// The bytes as text when they are UTF-8 without control characters other
+/// than whitespace, otherwise as space-separated hex.
+fn text_or_hex(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(text)
+            if !text
+                .chars()
+                .any(|c| c.is_control() && !matches!(c, '\n' | '\r' | '\t')) =>
+        {
+            text.to_string()
+        }
+        _ => {
+            let hex: Vec<String> = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+            format!("[hex] {}", hex.join(" "))
+        }
+    }
+}
+
+/// Formats `text`, prefixed with the chunk type when decoding several types at
+/// once so the lines can be told apart.
+fn labeled(label: bool, chunk: &Chunk, text: &dyn fmt::Display) -> String {
+    if label {
+        format!("{}\t{text}", chunk.chunk_type())
+    } else {
+        text.to_string()
+    }
+}
+
+fn remove(args: RemoveArgs, options: &ParseOptions) -> Result<()> {
+    let chunk_type = chunk_type_or_default(args.chunk_type.as_deref())?;
+    let output = output_path(&args.file_path, args.output_file.as_deref())?;
+    let input = read_input(&args.file_path)?;
+    let mut png: Png = parse_owned(input, options)?;
+    let before = args.summary.then(|| footprint(&png));
+
+    let matching: Vec<usize> = png
+        .iter()
+        .enumerate()
+        .filter(|(_, chunk)| type_is(chunk, &chunk_type, args.ignore_case))
+        .map(|(index, _)| index)
+        .collect();
+
+    let report = if args.all {
+        if matching.is_empty() {
+            return Err(PngDecodeError::InvalidChunkTypeGiven(chunk_type).into());
+        }
+        for &index in matching.iter().rev() {
+            png.remove_chunk_at(index)?;
+        }
+        backup_original(&args.file_path, output, &args.backup)?;
+        make_writable(output, &args.backup)?;
+        write_output(output, &png, options)?;
+        format!("Removed {} chunk(s) of type {}", matching.len(), chunk_type)
+    } else {
+        let index = matching
+            .get(args.index.unwrap_or(0))
+            .ok_or_else(|| match args.index {
+                Some(nth) => PngDecodeError::NthChunkNotFound(chunk_type.clone(), nth),
+                None => PngDecodeError::InvalidChunkTypeGiven(chunk_type.clone()),
+            })?;
+        let chunk = png.remove_chunk_at(*index)?;
+        backup_original(&args.file_path, output, &args.backup)?;
+        make_writable(output, &args.backup)?;
+        write_output(output, &png, options)?;
+        format!("Removed chunk: {}", chunk)
+    };
+
+    if is_std_stream(output) {
+        eprintln!("{report}");
+    } else {
+        println!("{report}");
+    }
+    if let Some(before) = before {
+        eprintln!(
+            "{}: {}",
+            args.file_path.display(),
+            summary(before, footprint(&png))
+        );
+    }
+
+    Ok(())
+}
+
+fn dedup(args: DedupArgs, options: &ParseOptions) -> Result<()> {
+    let output = output_path(&args.file_path, args.output_file.as_deref())?;
+    let input = read_input(&args.file_path)?;
+    let mut png = parse_owned(input, options)?;
+
+    let removed = match &args.chunk_type {
+        Some(chunk_type) => png.deduplicate_chunks_of_type(parse_chunk_type(chunk_type)?.as_str()),
+        None => png.deduplicate_chunks(),
+    };
+    // Nothing to rewrite in place, but a separate output should still be produced.
+    if removed > 0 || output != args.file_path {
+        backup_original(&args.file_path, output, &args.backup)?;
+        make_writable(output, &args.backup)?;
+        write_output(output, &png, options)?;
+    }
+
+    let report = format!("Removed {removed} duplicate chunk(s)");
+    if is_std_stream(output) {
+        eprintln!("{report}");
+    } else {
+        println!("{report}");
+    }
+    Ok(())
+}
+
+fn canonicalize(args: CanonicalizeArgs, options: &ParseOptions) -> Result<()> {
+    let output = output_path(&args.file_path, args.output_file.as_deref())?;
+    let input = read_input(&args.file_path)?;
+    let mut png = parse_owned(input, options)?;
+
+    let original = png.clone();
+    png.canonicalize()?;
+    let moved = original
+        .iter()
+        .zip(png.iter())
+        .filter(|(before, after)| before != after)
+        .count();
+    // Nothing to rewrite in place, but a separate output should still be produced.
+    if moved > 0 || output != args.file_path {
+        backup_original(&args.file_path, output, &args.backup)?;
+        make_writable(output, &args.backup)?;
+        write_output(output, &png, options)?;
+    }
+
+    let report = format!("Moved {moved} chunk(s)");
+    if is_std_stream(output) {
+        eprintln!("{report}");
+    } else {
+        println!("{report}");
+    }
+    Ok(())
+}
+
+fn replace(args: ReplaceArgs, options: &ParseOptions) -> Result<()> {
+    let output = output_path(&args.file_path, args.output_file.as_deref())?;
+    let input = read_input(&args.file_path)?;
+    let mut png = parse_owned(input, options)?;
+
+    png.replace_chunk_data(&args.chunk_type, args.message.into_bytes())?;
+    backup_original(&args.file_path, output, &args.backup)?;
+    make_writable(output, &args.backup)?;
+    write_output(output, &png, options)?;
+
+    if is_std_stream(output) {
+        eprintln!("Secret successfully replaced!");
+    } else {
+        println!("Secret successfully replaced!");
+    }
+
+    Ok(())
+}
+
+fn print(args: PrintArgs, options: &ParseOptions) -> Result<()> {
+    if !args.watch {
+        return print_png(&args, options);
+    }
+    if is_std_stream(&args.file_path) {
+        return Err(CommandError::Unsupported("Watching stdin").into());
+    }
+    if args.format == OutputFormat::Raw {
+        return Err(CommandError::Unsupported("--watch with --format raw").into());
+    }
+
+    let watcher = watch::FileWatcher::new(&args.file_path)?;
+    loop {
+        // Another tool may be halfway through rewriting the file, so a file
+        // that fails to parse is reported and watched for the next change.
+        if let Err(e) = print_png(&args, options) {
+            eprintln!("Error: {e}");
+        }
+        watcher.wait_for_change(watch::QUIET_PERIOD)?;
+        println!("\n--- {} changed ---\n", args.file_path.display());
+    }
+}
+
+fn print_png(args: &PrintArgs, options: &ParseOptions) -> Result<()> {
+    let input = read_input(&args.file_path)?;
+    let png = parse_png(&input, !args.no_verify, options)?;
+
+    if args.format == OutputFormat::Raw {
+        let baseline = png.baseline();
+        baseline.validate_structure()?;
+        let output = args.output_file.as_deref().unwrap_or(Path::new("-"));
+        return write_output(output, &baseline, options);
+    }
+    if args.output_file.is_some() {
+        return Err(CommandError::Unsupported("--output without --format raw").into());
+    }
+
+    let chunks = png
+        .iter()
+        .enumerate()
+        .filter(|(_, chunk)| !args.ancillary_only || chunk.is_ancillary())
+        .filter(|(_, chunk)| !args.critical_only || chunk.is_critical());
+
+    if args.format == OutputFormat::Json {
+        println!("{}", chunks_as_json(chunks));
+        return Ok(());
+    }
+
+    for (position, (_, chunk)) in chunks.enumerate() {
+        if args.detailed {
+            if position > 0 {
+                println!();
+            }
+            // The type is the first thing in the detailed form.
+            let detailed = format!("{chunk:#}");
+            let painted = color::chunk_type(chunk, options.crc_algorithm());
+            println!(
+                "{}",
+                detailed.replacen(chunk.chunk_type().as_str(), &painted, 1)
+            );
+        } else if args.hex {
+            match chunk.chunk_type().description() {
+                Some(description) => println!(
+                    "{}\t{} bytes\t{description}",
+                    color::chunk_type(chunk, options.crc_algorithm()),
+                    chunk.length()
+                ),
+                None => println!(
+                    "{}\t{} bytes",
+                    color::chunk_type(chunk, options.crc_algorithm()),
+                    chunk.length()
+                ),
+            }
+            match args.max_bytes {
+                Some(max_bytes) => print!("{}", chunk.hex_dump_limited(max_bytes)),
+                None => print!("{}", chunk.hex_dump()),
+            }
+        } else {
+            let data = if args.ascii_only {
+                chunk.data_as_ascii()
+            } else {
+                chunk.data_as_string_lossy()
+            };
+            println!(
+                "{}\t{data}",
+                color::chunk_type(chunk, options.crc_algorithm())
+            );
+        }
+    }
+    Ok(())
+}
+
+fn list(args: ListArgs, options: &ParseOptions) -> Result<()> {
+    if args.format == OutputFormat::Raw {
+        return Err(CommandError::Unsupported("Raw output when listing").into());
+    }
+    let filter = compile_filter(args.filter.as_deref())?;
+    if args.file_path.is_dir() || is_pattern(&args.file_path) {
+        return list_directory(&args, filter.as_ref(), options);
+    }
+
+    let input = read_input(&args.file_path)?;
+    let png = parse_png(&input, true, options)?;
+    list_png(&png, input.len(), &args, filter.as_ref(), options);
+    Ok(())
+}
+
+/// Lists every PNG in a directory or matching a wildcard pattern one after another,
+/// skipping files that fail to parse.
+fn list_directory(args: &ListArgs, filter: Option<&Regex>, options: &ParseOptions) -> Result<()> {
+    if args.format == OutputFormat::Json {
+        return Err(CommandError::Unsupported("JSON output when listing several files").into());
+    }
+
+    let files = input_files(&args.file_path, args.recursive)?;
+    let (mut matched, mut skipped) = (0, 0);
+    for path in &files {
+        let parsed =
+            read_input(path).and_then(|input| Ok((parse_png(&input, true, options)?, input.len())));
+        match parsed {
+            Ok((png, size)) => {
+                println!("{}:", path.display());
+                if list_png(&png, size, args, filter, options) > 0 {
+                    matched += 1;
+                }
+                println!();
+            }
+            Err(e) => {
+                skipped += 1;
+                eprintln!("{}: skipped: {e}", path.display());
+            }
+        }
+    }
+
+    println!(
+        "{matched} of {} files had matching chunks, {skipped} skipped.",
+        files.len()
+    );
+    Ok(())
+}
+
+/// Prints the chunk table for one file and returns how many chunks it listed.
+fn list_png(
+    png: &Png,
+    file_size: usize,
+    args: &ListArgs,
+    filter: Option<&Regex>,
+    options: &ParseOptions,
+) -> usize {
+    let listed: Vec<(usize, &Chunk)> = png
+        .iter()
+        .enumerate()
+        .filter(|(_, chunk)| type_matches(filter, chunk.chunk_type().as_str()))
+        .collect();
+
+    if args.format == OutputFormat::Json {
+        let count = listed.len();
+        println!("{}", chunks_as_json(listed.into_iter()));
+        return count;
+    }
+
+    println!(
+        "{:>5}  {:<4}  {:>10}  {:>10}  Description",
+        "Index", "Type", "Length", "CRC"
+    );
+    for &(index, chunk) in &listed {
+        println!(
+            "{:>5}  {}  {:>10}  {:>10}  {}",
+            index,
+            color::chunk_type(chunk, options.crc_algorithm()),
+            chunk.length(),
+            chunk.crc(),
+            chunk.chunk_type().description().unwrap_or("-")
+        );
+    }
+    println!(
+        "{:>5}  {:<5} {:>10}  {} chunks, {} bytes on disk",
+        "",
+        "Total",
+        listed
+            .iter()
+            .map(|(_, chunk)| u64::from(chunk.length()))
+            .sum::<u64>(),
+        listed.len(),
+        file_size
+    );
+    match png.animation_control() {
+        Some(Ok(animation)) => println!("APNG: {animation}"),
+        Some(Err(e)) => println!("APNG: {e}"),
+        None => {}
+    }
+
+    if args.sizes {
+        println!();
+        println!("{:<4}  {:>10}  {:>6}", "Type", "Bytes", "Share");
+        let total = png.total_size();
+        for (chunk_type, size) in png
+            .size_by_type()
+            .into_iter()
+            .filter(|(chunk_type, _)| type_matches(filter, chunk_type))
+        {
+            println!(
+                "{:<4}  {:>10}  {:>5.1}%",
+                chunk_type,
+                size,
+                size as f64 * 100.0 / total as f64
+            );
+        }
+    }
+
+    if args.histogram {
+        println!();
+        print!(
+            "{}",
+            length_histogram(listed.iter().map(|(_, chunk)| *chunk))
+        );
+    }
+
+    listed.len()
+}
+
+/// The widest bar `length_histogram` draws.
+const HISTOGRAM_WIDTH: usize = 40;
+
+/// Counts chunks by data length in power-of-two buckets (`0`, `1`, `2-3`,
+/// `4-7`, ...) and draws one bar per bucket from the shortest to the longest
+/// length seen, including empty buckets in between.
+fn length_histogram<'a>(chunks: impl Iterator<Item = &'a Chunk>) -> String {
+    // Bucket 0 holds empty chunks and bucket k holds lengths in [2^(k-1), 2^k).
+    let mut counts = [0usize; 33];
+    for chunk in chunks {
+        counts[(u32::BITS - chunk.length().leading_zeros()) as usize] += 1;
+    }
+    let Some(first) = counts.iter().position(|&count| count > 0) else {
+        return String::from("No chunks\n");
+    };
+    let last = counts.iter().rposition(|&count| count > 0).unwrap_or(first);
+    let most = counts[first..=last].iter().copied().max().unwrap_or(1);
+
+    let mut chart = format!("{:>23}  {:>6}\n", "Data length", "Chunks");
+    for (bucket, &count) in counts.iter().enumerate().take(last + 1).skip(first) {
+        let range = match bucket {
+            0 => String::from("0"),
+            1 => String::from("1"),
+            _ => format!("{}-{}", 1u64 << (bucket - 1), (1u64 << bucket) - 1),
+        };
+        let bar = "#".repeat((count * HISTOGRAM_WIDTH).div_ceil(most));
+        chart.push_str(format!("{range:>23}  {count:>6}  {bar}").trim_end());
+        chart.push('\n');
+    }
+    chart
+}
+
+/// One chunk as it appears in `--format json` output.
+#[derive(Serialize)]
+struct ChunkSummary<'a> {
+    index: usize,
+    #[serde(rename = "type")]
+    chunk_type: &'a str,
+    length: u32,
+    crc: u32,
+    data_base64: String,
+}
+
+/// Renders indexed chunks as a pretty-printed JSON array of objects.
+fn chunks_as_json<'a>(chunks: impl Iterator<Item = (usize, &'a Chunk)>) -> String {
+    let summaries: Vec<ChunkSummary> = chunks
+        .map(|(index, chunk)| ChunkSummary {
+            index,
+            chunk_type: chunk.chunk_type().as_str(),
+            length: chunk.length(),
+            crc: chunk.crc(),
+            data_base64: base64::encode(chunk.data()),
+        })
+        .collect();
+    serde_json::to_string_pretty(&summaries).expect("chunk summaries always serialize")
+}
+
+fn hide(args: HideArgs, options: &ParseOptions) -> Result<()> {
+    let output = output_path(&args.file_path, args.output_file.as_deref())?;
+    let input = read_input(&args.file_path)?;
+    let png = parse_owned(input, options)?;
+
+    if args.check_capacity {
+        let capacity = png.embedding_capacity()?;
+        let report = format!(
+            "{}: message is {} bytes; capacity is {capacity}",
+            args.file_path.display(),
+            args.message.len()
+        );
+        if is_std_stream(output) {
+            eprintln!("{report}");
+        } else {
+            println!("{report}");
+        }
+        if let Some(pixels) = capacity.pixels() {
+            if args.message.len() > pixels {
+                return Err(CommandError::MessageTooLarge(args.message.len(), pixels).into());
+            }
+        }
+    }
+
+    let hidden = lsb::hide(&png, args.message.as_bytes())?;
+    write_output(output, &hidden, options)?;
+
+    if is_std_stream(output) {
+        eprintln!("Secret successfully hidden!");
+    } else {
+        println!("Secret successfully hidden!");
+    }
+
+    Ok(())
+}
+
+fn reveal(args: RevealArgs, options: &ParseOptions) -> Result<()> {
+    let input = read_input(&args.file_path)?;
+    let png = parse_owned(input, options)?;
+
+    println!("{}", String::from_utf8_lossy(&lsb::reveal(&png)?));
+    Ok(())
+}
+
+fn validate(args: ValidateArgs, options: &ParseOptions) -> Result<()> {
+    let input = read_input(&args.file_path)?;
+    let png = parse_owned(input, options)?;
+
+    png.validate_structure()?;
+    println!("PNG structure is valid");
+    Ok(())
+}
+
+fn info(args: InfoArgs, options: &ParseOptions) -> Result<()> {
+    let input = read_input(&args.file_path)?;
+    let png = parse_owned(input, options)?;
+
+    println!("{}", png.header()?);
+    if let Some(gamma) = png.gamma() {
+        println!("Gamma: {}", gamma?);
+    }
+    if let Some(dimensions) = png.physical_dimensions() {
+        println!("Resolution: {}", dimensions?);
+    }
+    if let Some(time) = png.time() {
+        println!("Modified: {}", time?);
+    }
+    if let Some(intent) = png.srgb() {
+        println!("Rendering intent: {}", intent?);
+    }
+    if let Some(background) = png.background() {
+        println!("Background: {}", background?);
+    }
+    if let Some(chromaticities) = png.chromaticities() {
+        println!("Chromaticities: {}", chromaticities?);
+    }
+    if let Some(animation) = png.animation_control() {
+        println!("APNG: {}", animation?);
+    }
+    Ok(())
+}
+
+fn palette(args: PaletteArgs, options: &ParseOptions) -> Result<()> {
+    let input = read_input(&args.file_path)?;
+    let png = parse_owned(input, options)?;
+
+    let entries = png
+        .palette()
+        .ok_or_else(|| PngDecodeError::ChunkTypeNotFound("PLTE".to_string()))??;
+    print!("{}", palette_table(&entries));
+    Ok(())
+}
+
+fn palette_table(entries: &[[u8; 3]]) -> String {
+    entries
+        .iter()
+        .enumerate()
+        .map(|(index, [red, green, blue])| {
+            format!("{index:>3}  #{red:02x}{green:02x}{blue:02x}  ({red}, {green}, {blue})\n")
+        })
+        .collect()
+}
+
+fn count(args: CountArgs, options: &ParseOptions) -> Result<()> {
+    let filter = compile_filter(args.filter.as_deref())?;
+    let input = read_input(&args.file_path)?;
+    let png = parse_owned(input, options)?;
+
+    let counts = chunk_type_counts(&png, filter.as_ref());
+    for (chunk_type, count) in &counts {
+        println!("{chunk_type}\t{count}");
+    }
+    if args.total {
+        println!(
+            "Total\t{}",
+            counts.iter().map(|(_, count)| count).sum::<usize>()
+        );
+    }
+    if args.histogram {
+        println!();
+        print!(
+            "{}",
+            length_histogram(
+                png.chunks()
+                    .iter()
+                    .filter(|chunk| type_matches(filter.as_ref(), chunk.chunk_type().as_str()))
+            )
+        );
+    }
+    Ok(())
+}
+
+fn check(args: CheckArgs, options: &ParseOptions) -> Result<ExitCode> {
+    let input = read_input(&args.file_path)?;
+    let png = parse_png(&input, false, options)?;
+
+    let mut corrupted = 0;
+    for (index, chunk) in png.iter().enumerate() {
+        if !chunk.length_matches_data() {
+            corrupted += 1;
+            println!(
+                "{index:>5}  {:<4}  Length mismatch: stored {}, data is {} bytes",
+                chunk.chunk_type(),
+                chunk.length(),
+                chunk.data_len()
+            );
+        } else if chunk.verify_crc_with(options.crc_algorithm()) {
+            println!("{index:>5}  {:<4}  ok", chunk.chunk_type());
+        } else {
+            corrupted += 1;
+            let mut expected = chunk.clone();
+            expected.recompute_crc_with(options.crc_algorithm());
+            println!(
+                "{index:>5}  {:<4}  CRC mismatch: stored {:#010x}, computed {:#010x}",
+                chunk.chunk_type(),
+                chunk.crc(),
+                expected.crc()
+            );
+        }
+    }
+    println!("{} chunks, {corrupted} corrupted", png.chunks().len());
+
+    if corrupted == 0 {
+        Ok(ExitCode::SUCCESS)
+    } else {
+        Ok(ExitCode::FAILURE)
+    }
+}
+
+fn diff(args: DiffArgs, options: &ParseOptions) -> Result<ExitCode> {
+    let original = parse_owned(read_input(&args.original)?, options)?;
+    let modified = parse_owned(read_input(&args.modified)?, options)?;
+
+    let changes = diff_chunks(original.chunks(), modified.chunks());
+    if changes.is_empty() {
+        println!("No differences");
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    println!("--- {}", args.original.display());
+    println!("+++ {}", args.modified.display());
+    let (mut added, mut removed, mut changed) = (0, 0, 0);
+    for change in &changes {
+        match *change {
+            ChunkChange::Removed(index, chunk) => {
+                removed += 1;
+                println!(
+                    "- {index:>8}  {:<4}  {} bytes  crc {:#010x}",
+                    chunk.chunk_type(),
+                    chunk.length(),
+                    chunk.crc()
+                );
+            }
+            ChunkChange::Added(index, chunk) => {
+                added += 1;
+                println!(
+                    "+ {index:>8}  {:<4}  {} bytes  crc {:#010x}",
+                    chunk.chunk_type(),
+                    chunk.length(),
+                    chunk.crc()
+                );
+            }
+            ChunkChange::Modified(old_index, old, new_index, new) => {
+                changed += 1;
+                println!(
+                    "~ {:>8}  {:<4}  {} -> {} bytes ({:+})  crc {:#010x} -> {:#010x}",
+                    format!("{old_index}->{new_index}"),
+                    old.chunk_type(),
+                    old.length(),
+                    new.length(),
+                    i64::from(new.length()) - i64::from(old.length()),
+                    old.crc(),
+                    new.crc()
+                );
+            }
+        }
+    }
+    println!("{added} added, {removed} removed, {changed} modified");
+
+    Ok(ExitCode::FAILURE)
+}
+
+fn merge(args: MergeArgs, options: &ParseOptions) -> Result<()> {
+    let output = output_path(&args.target, args.output_file.as_deref())?;
+    if is_std_stream(&args.source) && is_std_stream(&args.target) {
+        return Err(CommandError::StdinUsedTwice.into());
+    }
+    let source = parse_owned(read_input(&args.source)?, options)?;
+    let mut target = parse_owned(read_input(&args.target)?, options)?;
+
+    // Gather everything first so a missing type leaves the target untouched.
+    let mut copies = Vec::new();
+    for chunk_type in &args.chunk_types {
+        let chunks = source.chunks_by_type(chunk_type);
+        if chunks.is_empty() {
+            return Err(PngDecodeError::ChunkTypeNotFound(chunk_type.clone()).into());
+        }
+        copies.extend(chunks.into_iter().cloned());
+    }
+
+    if args.replace {
+        for chunk_type in &args.chunk_types {
+            target.remove_all_chunks_by_type(chunk_type);
+        }
+    }
+    let copied = copies.len();
+    for chunk in copies {
+        if target.chunk_by_type("IEND").is_some() {
+            target.insert_chunk_before("IEND", chunk)?;
+        } else {
+            target.append_chunk(chunk)?;
+        }
+    }
+
+    backup_original(&args.target, output, &args.backup)?;
+
+    make_writable(output, &args.backup)?;
+    write_output(output, &target, options)?;
+
+    let report = format!("Copied {copied} chunk(s) from {}", args.source.display());
+    if is_std_stream(output) {
+        eprintln!("{report}");
+    } else {
+        println!("{report}");
+    }
+    Ok(())
+}
+
+fn interactive(args: InteractiveArgs, options: &ParseOptions) -> Result<()> {
+    // The prompt reads its commands from stdin, so the PNG can't come from there too.
+    if is_std_stream(&args.file_path) {
+        return Err(
+            CommandError::Unsupported("Reading the PNG from stdin in interactive mode").into(),
+        );
+    }
+    let mut png = parse_owned(read_input(&args.file_path)?, options)?;
+
+    let name = args.file_path.display().to_string();
+    if interactive_session(
+        &mut png,
+        &name,
+        &mut io::stdin().lock(),
+        &mut io::stdout().lock(),
+    )? {
+        backup_original(&args.file_path, &args.file_path, &args.backup)?;
+        make_writable(&args.file_path, &args.backup)?;
+        write_output(&args.file_path, &png, options)?;
+        println!("Saved {name}");
+    } else {
+        println!("No changes written");
+    }
+    Ok(())
+}
+
+const INTERACTIVE_HELP: &str = "\
+Commands:
+  list                 Show the numbered chunk list
+  view <N>             Show chunk N in detail
+  remove <N>           Remove chunk N
+  extract <N> <FILE>   Write the data of chunk N to FILE
+  quit                 Exit, asking whether to save any changes
+  help                 Show this message
+Commands can be shortened to their first letter. Ctrl-D exits without saving.";
+
+/// Runs the prompt loop over `png`, reading commands from `input` until `quit`
+/// or end of input. Mistakes are reported and the loop carries on. Returns
+/// whether the user confirmed saving their changes; end of input never saves.
+fn interactive_session(
+    png: &mut Png,
+    name: &str,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> Result<bool> {
+    let mut modified = false;
+    print_chunk_list(png, out)?;
+
+    loop {
+        let Some(line) = prompt(input, out, "pngme> ")? else {
+            return Ok(false);
+        };
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let result = match words.as_slice() {
+            [] => Ok(()),
+            ["l" | "list"] => print_chunk_list(png, out),
+            ["v" | "view", n] => chunk_number(png, n)
+                .and_then(|index| Ok(writeln!(out, "{:#}", png.chunks()[index])?)),
+            ["r" | "remove", n] => chunk_number(png, n).and_then(|index| {
+                let chunk = png.remove_chunk_at(index)?;
+                modified = true;
+                Ok(writeln!(
+                    out,
+                    "Removed chunk {index} ({})",
+                    chunk.chunk_type()
+                )?)
+            }),
+            ["x" | "extract", n, path] => chunk_number(png, n).and_then(|index| {
+                let data = png.chunks()[index].data();
+                fs::write(path, data)?;
+                Ok(writeln!(out, "Wrote {} bytes to {path}", data.len())?)
+            }),
+            ["q" | "quit"] if !modified => return Ok(false),
+            ["q" | "quit"] => {
+                let answer = prompt(input, out, &format!("Save changes to {name}? [y/N] "))?;
+                return Ok(answer.is_some_and(|answer| {
+                    matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes")
+                }));
+            }
+            ["h" | "help" | "?"] => Ok(writeln!(out, "{INTERACTIVE_HELP}")?),
+            _ => Ok(writeln!(
+                out,
+                "Unknown command {:?}, type help for a list",
+                line.trim()
+            )?),
+        };
+        if let Err(e) = result {
+            writeln!(out, "Error: {e}")?;
+        }
+    }
+}
+
+/// Prints `message` and reads one line, or `None` at end of input.
+fn prompt(input: &mut impl BufRead, out: &mut impl Write, message: &str) -> Result<Option<String>> {
+    write!(out, "{message}")?;
+    out.flush()?;
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        writeln!(out)?;
+        return Ok(None);
+    }
+    Ok(Some(line))
+}
+
+fn print_chunk_list(png: &Png, out: &mut impl Write) -> Result<()> {
+    for (index, chunk) in png.iter().enumerate() {
+        writeln!(
+            out,
+            "{index:>5}  {:<4}  {:>10} bytes  {}",
+            chunk.chunk_type(),
+            chunk.length(),
+            chunk.chunk_type().description().unwrap_or("-")
+        )?;
+    }
+    Ok(())
+}
+
+/// Parses a chunk number as shown by the list, counting from 0.
+fn chunk_number(png: &Png, n: &str) -> Result<usize> {
+    let index: usize = n
+        .parse()
+        .map_err(|_| CommandError::InvalidChunkNumber(n.to_string()))?;
+    if index >= png.chunks().len() {
+        return Err(PngDecodeError::IndexOutOfRange(index, png.chunks().len()).into());
+    }
+    Ok(index)
+}
+
+/// One difference between two chunk lists, with the chunks' indices.
+#[derive(Debug, PartialEq)]
+enum ChunkChange<'a> {
+    Added(usize, &'a Chunk),
+    Removed(usize, &'a Chunk),
+    Modified(usize, &'a Chunk, usize, &'a Chunk),
+}
+
+/// Lines the two lists up by the longest common run of chunk types, so a chunk
+/// inserted or dropped in the middle doesn't shift everything after it. Aligned
+/// chunks whose data or CRC differ are reported as modified.
+fn diff_chunks<'a>(old: &'a [Chunk], new: &'a [Chunk]) -> Vec<ChunkChange<'a>> {
+    let old_types: Vec<&ChunkType> = old.iter().map(Chunk::chunk_type).collect();
+    let new_types: Vec<&ChunkType> = new.iter().map(Chunk::chunk_type).collect();
+
+    // Matching runs at either end need no search, which usually leaves only a
+    // short stretch in the middle.
+    let prefix = old_types
+        .iter()
+        .zip(&new_types)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = old_types[prefix..]
+        .iter()
+        .rev()
+        .zip(new_types[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut aligned: Vec<(usize, usize)> = (0..prefix).map(|i| (i, i)).collect();
+    common_types(
+        &old_types[prefix..old.len() - suffix],
+        &new_types[prefix..new.len() - suffix],
+        (prefix, prefix),
+        &mut aligned,
+    );
+    aligned.extend((1..=suffix).rev().map(|k| (old.len() - k, new.len() - k)));
+
+    let mut changes = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    for (next_i, next_j) in aligned.into_iter().chain([(old.len(), new.len())]) {
+        changes.extend((i..next_i).map(|i| ChunkChange::Removed(i, &old[i])));
+        changes.extend((j..next_j).map(|j| ChunkChange::Added(j, &new[j])));
+        if next_i < old.len() && old[next_i] != new[next_j] {
+            changes.push(ChunkChange::Modified(
+                next_i,
+                &old[next_i],
+                next_j,
+                &new[next_j],
+            ));
+        }
+        (i, j) = (next_i + 1, next_j + 1);
+    }
+    changes
+}
+
+/// Appends the positions, shifted by `offset`, of a longest common subsequence
+/// of `old` and `new` to `aligned`, in order. Uses Hirschberg's algorithm, so
+/// memory stays linear in the input rather than the product of the two.
+fn common_types(
+    old: &[&ChunkType],
+    new: &[&ChunkType],
+    offset: (usize, usize),
+    aligned: &mut Vec<(usize, usize)>,
+) {
+    if old.is_empty() || new.is_empty() {
+        return;
+    }
+    if old.len() == 1 {
+        if let Some(j) = new.iter().position(|chunk_type| *chunk_type == old[0]) {
+            aligned.push((offset.0, offset.1 + j));
+        }
+        return;
+    }
+
+    // Split `new` where the best match of the first half of `old` against its
+    // start, plus the second half against its end, is longest. The earliest
+    // such split wins, so removals are preferred over additions on a tie.
+    let mid = old.len() / 2;
+    let forward = common_lengths(old[..mid].iter(), new.iter());
+    let backward = common_lengths(old[mid..].iter().rev(), new.iter().rev());
+    let split = (0..=new.len())
+        .max_by_key(|&k| (forward[k] + backward[new.len() - k], std::cmp::Reverse(k)))
+        .expect("the range always includes 0");
+
+    common_types(&old[..mid], &new[..split], offset, aligned);
+    common_types(
+        &old[mid..],
+        &new[split..],
+        (offset.0 + mid, offset.1 + split),
+        aligned,
+    );
+}
+
+/// The length of the longest common subsequence of all of `old` and each
+/// prefix of `new`, computed one row at a time.
+fn common_lengths<'a>(
+    old: impl Iterator<Item = &'a &'a ChunkType>,
+    new: impl Iterator<Item = &'a &'a ChunkType> + Clone,
+) -> Vec<usize> {
+    let mut row = vec![0; new.clone().count() + 1];
+    for a in old {
+        let mut diagonal = 0;
+        for (k, b) in new.clone().enumerate() {
+            let above = row[k + 1];
+            row[k + 1] = if a == b {
+                diagonal + 1
+            } else {
+                above.max(row[k])
+            };
+            diagonal = above;
+        }
+    }
+    row
+}
+
+/// Counts the chunks of each type, most frequent first. Ties keep the
+/// alphabetical order of the map.
+fn chunk_type_counts(png: &Png, filter: Option<&Regex>) -> Vec<(String, usize)> {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for chunk in png
+        .chunks()
+        .iter()
+        .filter(|chunk| type_matches(filter, chunk.chunk_type().as_str()))
+    {
+        *counts.entry(chunk.chunk_type().to_string()).or_default() += 1;
+    }
+
+    let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+    counts.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+    counts
+}
+
+/// Compiles a `--filter` pattern up front so a bad pattern is reported before
+/// any input is read.
+fn compile_filter(pattern: Option<&str>) -> Result<Option<Regex>> {
+    Ok(pattern.map(Regex::new).transpose()?)
+}
+
+/// Whether `chunk` is of `chunk_type`, or with `--ignore-case` of any type
+/// spelled the same apart from case.
+fn type_is(chunk: &Chunk, chunk_type: &str, ignore_case: bool) -> bool {
+    if ignore_case {
+        chunk.chunk_type().eq_ignore_case(chunk_type)
+    } else {
+        chunk.chunk_type().as_str() == chunk_type
+    }
+}
+
+fn type_matches(filter: Option<&Regex>, chunk_type: &str) -> bool {
+    filter.is_none_or(|filter| filter.is_match(chunk_type))
+}
+
+/// Returns whether `path` contains any wildcard characters, which shells that
+/// don't expand them pass through as is.
+fn is_pattern(path: &Path) -> bool {
+    path.to_string_lossy().contains(['*', '?', '['])
+}
+
+/// The existing paths matching the wildcard `pattern`, sorted. Matching
+/// nothing is an error.
+fn expand_pattern(pattern: &Path) -> Result<Vec<PathBuf>> {
+    let mut matches = glob::glob(&pattern.to_string_lossy())?
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(io::Error::from)?;
+    if matches.is_empty() {
+        return Err(CommandError::NoFilesMatch(pattern.display().to_string()).into());
+    }
+    matches.sort();
+    Ok(matches)
+}
+
+/// The files matching `path` if it is a wildcard pattern, otherwise the PNGs
+/// in the directory `path` as found by `png_files`.
+fn input_files(path: &Path, recursive: bool) -> Result<Vec<PathBuf>> {
+    if is_pattern(path) {
+        expand_pattern(path)
+    } else {
+        png_files(path, recursive)
+    }
+}
+
+/// The `.png` files directly inside `dir`, sorted by path, plus those in its
+/// subdirectories when `recursive` is set. Symlinked directories are not
+/// followed, so a link cycle can't make the walk loop forever.
+fn png_files(dir: &Path, recursive: bool) -> Result<Vec<PathBuf>> {
+    let mut entries = fs::read_dir(dir)?.collect::<io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.path());
+
+    let mut files = Vec::new();
+    for entry in entries {
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            if recursive {
+                files.extend(png_files(&path, true)?);
+            }
+        } else if path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("png"))
+        {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+/// Parses `input`, skipping CRC validation when `verify` is false so data can
+/// be recovered from files whose CRCs were broken by hand edits.
+fn parse_png(input: &[u8], verify: bool, options: &ParseOptions) -> Result<Png> {
+    let start = Instant::now();
+    let png = if verify {
+        Png::parse_with(input, options)?
+    } else {
+        Png::try_from_unchecked_with(input, options)?
+    };
+    stats::record(Phase::Parse, start.elapsed(), input.len() as u64);
+    Ok(png)
+}
+
+/// Parses `input` with CRCs checked, letting the chunks share its buffer.
+fn parse_owned(input: Vec<u8>, options: &ParseOptions) -> Result<Png> {
+    let start = Instant::now();
+    let size = input.len() as u64;
+    let png = Png::parse_owned_with(input, options)?;
+    stats::record(Phase::Parse, start.elapsed(), size);
+    Ok(png)
+}
+
+/// Reads the whole input file, or all of stdin when `path` is `-`.
+fn read_input(path: &Path) -> Result<Vec<u8>> {
+    let start = Instant::now();
+    let input = if is_std_stream(path) {
+        let mut input = Vec::new();
+        io::stdin().lock().read_to_end(&mut input)?;
+        input
+    } else {
+        let file = File::open(path)?;
+        let size = file.metadata()?.len();
+        let mut input = Vec::with_capacity(size as usize);
+        Tracked::new(file, "Reading", size).read_to_end(&mut input)?;
+        input
+    };
+    stats::record(Phase::Read, start.elapsed(), input.len() as u64);
+    info!("Read {} bytes from {}", input.len(), path.display());
+    Ok(input)
+}
+
+/// Streams `png` to the output file, or to stdout when `path` is `-`. Chunks
+/// are built with ISO-HDLC CRCs, so under any other `--crc-algo` every CRC is
+/// recomputed with that one on the way out.
+fn write_output(path: &Path, png: &Png, options: &ParseOptions) -> Result<()> {
+    let mut rechecksummed = None;
+    let png = match options.crc_algorithm() {
+        CrcAlgorithm::IsoHdlc => png,
+        algorithm => rechecksummed.insert({
+            let mut png = png.clone();
+            png.recompute_crcs_with(algorithm);
+            png
+        }),
+    };
+    if SELF_CHECK.load(Ordering::Relaxed) {
+        png.round_trip_ok_with(options.crc_algorithm())?;
+    }
+    info!(
+        "Writing {} chunks to {}",
+        png.chunks().len(),
+        path.display()
+    );
+    let start = Instant::now();
+    let size = png.total_size();
+    if is_std_stream(path) {
+        let mut stdout = Tracked::new(io::stdout().lock(), "Writing", size);
+        png.write_to(&mut stdout)?;
+        stdout.flush()?;
+    } else {
+        let mut writer = Tracked::new(BufWriter::new(File::create(path)?), "Writing", size);
+        png.write_to(&mut writer)?;
+        writer.flush()?;
+    }
+    stats::record(Phase::Write, start.elapsed(), size);
+    Ok(())
+}
+
+/// Copies `input` to a backup file when `--backup` was given and `output` is
+/// about to overwrite it. Existing backups are only replaced with `--force`.
+fn backup_original(input: &Path, output: &Path, args: &BackupArgs) -> Result<()> {
+    if !args.backup || is_std_stream(input) || input != output {
+        return Ok(());
+    }
+
+    let mut backup = input.as_os_str().to_owned();
+    backup.push(&args.backup_suffix);
+    let backup = PathBuf::from(backup);
+
+    if backup.exists() && !args.force {
+        return Err(CommandError::BackupExists(backup).into());
+    }
+    fs::copy(input, &backup)?;
+    info!("Backed up {} to {}", input.display(), backup.display());
+    Ok(())
+}
+
+/// Refuses to overwrite a read-only `output` unless `--force` was given, in
+/// which case the owner is given write permission first.
+fn make_writable(output: &Path, args: &BackupArgs) -> Result<()> {
+    if is_std_stream(output) {
+        return Ok(());
+    }
+    let mut permissions = match fs::metadata(output) {
+        Ok(metadata) if metadata.permissions().readonly() => metadata.permissions(),
+        _ => return Ok(()),
+    };
+    if !args.force {
+        return Err(CommandError::ReadOnlyOutput(output.to_path_buf()).into());
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        permissions.set_mode(permissions.mode() | 0o200);
+    }
+    #[cfg(not(unix))]
+    #[allow(clippy::permissions_set_readonly_false)]
+    permissions.set_readonly(false);
+
+    fs::set_permissions(output, permissions)?;
+    info!("Made {} writable", output.display());
+    Ok(())
+}
+
+/// Picks where a rewritten PNG goes: the explicit output if given, otherwise
+/// back over the input file. Input read from stdin can't be written back.
+fn output_path<'a>(input: &'a Path, output: Option<&'a Path>) -> Result<&'a Path> {
+    match output {
+        Some(output) => Ok(output),
+        None if is_std_stream(input) => Err(CommandError::OutputRequired.into()),
+        None => Ok(input),
+    }
+}
+
+fn is_std_stream(path: &Path) -> bool {
+    path == Path::new("-")
+}
+
+#[derive(Debug)]
+pub enum CommandError {
+    MissingArgument(&'static str),
+    OutputRequired,
+    PasswordRequired,
+    KeyRequired,
+    SignatureInvalid,
+    AmbiguousMessage,
+    StdinUsedTwice,
+    StdinInBatch,
+    BackupExists(PathBuf),
+    MessageTooLarge(usize, usize),
+    Unsupported(&'static str),
+    InvalidChunkNumber(String),
+    InvalidChunkType(String),
+    ReadOnlyOutput(PathBuf),
+    ChunkTypeRequired,
+    InvalidDefaultChunkType(String),
+    NoFilesMatch(String),
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandError::MissingArgument(name) => write!(f, "A {name} is required"),
+            CommandError::OutputRequired => write!(
+                f,
+                "Input was read from stdin, so an output file must be given with --output"
+            ),
+            CommandError::PasswordRequired => write!(
+                f,
+                "A password is required, pass --password or set PNGME_PASSWORD"
+            ),
+            CommandError::KeyRequired => {
+                write!(f, "A key is required, pass --key or set PNGME_KEY")
+            }
+            CommandError::SignatureInvalid => write!(
+                f,
+                "Signature invalid: wrong key or the data has been tampered with"
+            ),
+            CommandError::ChunkTypeRequired => write!(
+                f,
+                "A chunk type is required, pass one or set {CHUNK_TYPE_VAR}"
+            ),
+            CommandError::InvalidDefaultChunkType(chunk_type) => write!(
+                f,
+                "{CHUNK_TYPE_VAR}: {}",
+                CommandError::InvalidChunkType(chunk_type.clone())
+            ),
+            CommandError::AmbiguousMessage => {
+                write!(f, "Give either a message or --message-file, not both")
+            }
+            CommandError::StdinUsedTwice => {
+                write!(f, "Only one of the inputs can be read from stdin")
+            }
+            CommandError::StdinInBatch => write!(
+                f,
+                "Reading from stdin is not supported when encoding several files"
+            ),
+            CommandError::BackupExists(path) => write!(
+                f,
+                "The backup file {} already exists, pass --force to overwrite it",
+                path.display()
+            ),
+            CommandError::MessageTooLarge(size, capacity) => write!(
+                f,
+                "The message is {size} bytes but only {capacity} bytes fit"
+            ),
+            CommandError::Unsupported(what) => write!(f, "{what} is not supported"),
+            CommandError::NoFilesMatch(pattern) => write!(f, "No files match {pattern}"),
+            CommandError::InvalidChunkType(chunk_type) => {
+                write!(f, "Invalid chunk type {chunk_type:?}: ")?;
+                match chunk_type
+                    .chars()
+                    .enumerate()
+                    .find(|(_, c)| !c.is_ascii_alphabetic())
+                {
+                    Some((i, c)) => {
+                        write!(f, "{c:?} at position {} is not an ASCII letter", i + 1)?
+                    }
+                    None => write!(f, "it has {} letters", chunk_type.chars().count())?,
+                }
+                write!(
+                    f,
+                    "\nChunk types are exactly four ASCII letters (A-Z, a-z), and each letter's case \
+                     carries meaning: an uppercase first letter marks the chunk critical and a \
+                     lowercase one ancillary, and a lowercase second letter marks it private. \
+                     Custom chunks are usually ancillary and private, e.g. ruSt."
+                )
+            }
+            CommandError::ReadOnlyOutput(path) => write!(
+                f,
+                "{} is read-only, pass --force to overwrite it",
+                path.display()
+            ),
+            CommandError::InvalidChunkNumber(n) => {
+                write!(f, "{n:?} is not a chunk number from the list")
+            }
+        }
+    }
+}
+
+impl Error for CommandError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use pngme::chunk::ChunkError;
+    use std::num::NonZeroUsize;
+    use std::path::PathBuf;
+
+    /// A private copy of `test.png` for one test, removed when dropped, so
+    /// tests that write to their input don't race the ones reading it.
+    struct TestPng(PathBuf);
+
+    impl TestPng {
+        fn new(test: &str) -> Self {
+            let path =
+                std::env::temp_dir().join(format!("pngme_{test}_{}.png", std::process::id()));
+            fs::copy("test.png", &path).unwrap();
+            Self(path)
+        }
+
+        fn path(&self) -> PathBuf {
+            self.0.clone()
+        }
+
+        fn arg(&self) -> &str {
+            self.0.to_str().unwrap()
+        }
+    }
+
+    impl Drop for TestPng {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    fn no_backup() -> BackupArgs {
+        BackupArgs {
+            backup: false,
+            backup_suffix: String::from(".bak"),
+            force: false,
+        }
+    }
+
+    /// Encodes `message` in a `chunk_type` chunk of `file_path` with every option off.
+    fn encode_args(file_path: PathBuf, chunk_type: &str, message: &str) -> EncodeArgs {
+        EncodeArgs {
+            file_path,
+            chunk_type: Some(String::from(chunk_type)),
+            message: Some(String::from(message)),
+            chunks: vec![],
+            message_file: None,
+            base64: false,
+            text: None,
+            itxt: None,
+            language: String::new(),
+            translated_keyword: String::new(),
+            compress_text: false,
+            output_file: None,
+            legacy_output_file: None,
+            batch: vec![],
+            output_dir: None,
+            check_capacity: false,
+            summary: false,
+            no_warn: false,
+            upsert: false,
+            jobs: NonZeroUsize::MIN,
+            backup: no_backup(),
+            compress: false,
+            encrypt: false,
+            password: None,
+            sign: false,
+            key: None,
+        }
+    }
+
+    /// Decodes the `chunk_types` chunks of `file_path` with every option off.
+    fn decode_args(file_path: PathBuf, chunk_types: &[&str]) -> DecodeArgs {
+        DecodeArgs {
+            chunk_types: chunk_types.iter().map(|&t| String::from(t)).collect(),
+            file_path,
+            recursive: false,
+            no_verify: false,
+            quiet: false,
+            ignore_case: false,
+            base64: false,
+            split_null: false,
+            ascii_only: false,
+            decrypt: false,
+            password: None,
+            verify_signature: false,
+            key: None,
+        }
+    }
+
+    /// The lines `decode` would print for `args`, without printing them.
+    fn decoded(args: &DecodeArgs) -> Vec<String> {
+        let png = Png::try_from(fs::read(&args.file_path).unwrap().as_slice()).unwrap();
+        png.chunks()
+            .iter()
+            .filter(|chunk| args.chunk_types.contains(&chunk.chunk_type().to_string()))
+            .map(|chunk| decode_message(chunk, args, false).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_encode() {
+        let test_png = TestPng::new("encode");
+        let args = encode_args(test_png.path(), "ruSt", "Test message");
+        assert!(encode(args, &ParseOptions::default()).is_ok());
+
+        let args = decode_args(test_png.path(), &["ruSt"]);
+        assert_eq!(decoded(&args), ["ruSt\tTest message"]);
+    }
+
+    #[test]
+    fn test_summary() {
+        assert_eq!(
+            summary((5, 1024), (6, 1046)),
+            "chunks 5 -> 6 (+1), bytes 1024 -> 1046 (+22)"
+        );
+        assert_eq!(
+            summary((6, 1046), (5, 1024)),
+            "chunks 6 -> 5 (-1), bytes 1046 -> 1024 (-22)"
+        );
+        assert_eq!(
+            summary((5, 1024), (5, 1024)),
+            "chunks 5 -> 5 (+0), bytes 1024 -> 1024 (+0)"
+        );
+    }
+
+    #[test]
+    fn test_encode_batch() {
+        let dir = std::env::temp_dir().join("pngme_test_batch");
+        let output_dir = dir.join("out");
+        fs::create_dir_all(&output_dir).unwrap();
+
+        let png = Png::from_chunks(vec![
+            Chunk::new(ChunkType::from_str("IHDR").unwrap(), vec![]),
+            Chunk::new(ChunkType::from_str("IEND").unwrap(), vec![]),
+        ]);
+        fs::write(dir.join("a.png"), png.as_bytes()).unwrap();
+        fs::write(dir.join("b.png"), png.as_bytes()).unwrap();
+
+        let args = EncodeArgs {
+            batch: vec![dir.join("b.png"), dir.join("missing.png")],
+            output_dir: Some(output_dir.clone()),
+            ..encode_args(dir.join("a.png"), "ruSt", "Watermark")
+        };
+        assert_eq!(
+            encode(args, &ParseOptions::default()).unwrap(),
+            ExitCode::FAILURE
+        );
+
+        for name in ["a.png", "b.png"] {
+            let png = Png::try_from(fs::read(output_dir.join(name)).unwrap().as_slice()).unwrap();
+            assert_eq!(png.chunk_by_type("ruSt").unwrap().data(), b"Watermark");
+        }
+        assert!(!output_dir.join("missing.png").exists());
+    }
+
+    #[test]
+    fn test_encode_batch_parallel() {
+        let dir = std::env::temp_dir().join("pngme_test_batch_parallel");
+        let output_dir = dir.join("out");
+        fs::create_dir_all(&output_dir).unwrap();
+
+        let png = Png::from_chunks(vec![
+            Chunk::new(ChunkType::from_str("IHDR").unwrap(), vec![]),
+            Chunk::new(ChunkType::from_str("IEND").unwrap(), vec![]),
+        ]);
+        let names: Vec<String> = (0..12).map(|i| format!("{i}.png")).collect();
+        for name in &names {
+            fs::write(dir.join(name), png.as_bytes()).unwrap();
+        }
+        fs::write(dir.join("5.png"), b"not a png").unwrap();
+
+        let paths: Vec<String> = names
+            .iter()
+            .map(|name| dir.join(name).display().to_string())
+            .collect();
+        let mut argv = vec![
+            "encode",
+            paths[0].as_str(),
+            "ruSt",
+            "Watermark",
+            "--jobs",
+            "4",
+        ];
+        argv.extend(["--output-dir", output_dir.to_str().unwrap(), "--batch"]);
+        argv.extend(paths[1..].iter().map(String::as_str));
+        let args = EncodeArgs::parse_from(argv);
+        assert_eq!(args.jobs.get(), 4);
+
+        // The broken file fails on its own without stopping the others.
+        assert_eq!(
+            encode(args, &ParseOptions::default()).unwrap(),
+            ExitCode::FAILURE
+        );
+        for name in names.iter().filter(|name| *name != "5.png") {
+            let png = Png::try_from(fs::read(output_dir.join(name)).unwrap().as_slice()).unwrap();
+            assert_eq!(png.chunk_by_type("ruSt").unwrap().data(), b"Watermark");
+        }
+        assert!(!output_dir.join("5.png").exists());
+    }
+
+    #[test]
+    fn test_encode_stdin_requires_output() {
+        let args = encode_args(PathBuf::from("-"), "tEXt", "Test message");
+        assert!(encode(args, &ParseOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_encode_positional_output() {
+        let test_png = TestPng::new("encode_positional_output");
+        let output = std::env::temp_dir().join("pngme_test_positional_output.png");
+        let output_arg = output.to_str().unwrap();
+        let args = EncodeArgs::parse_from(["encode", test_png.arg(), "ruSt", "moved", output_arg]);
+        assert!(encode(args, &ParseOptions::default()).is_ok());
+
+        let args = decode_args(output.clone(), &["ruSt"]);
+        assert_eq!(decoded(&args), ["ruSt\tmoved"]);
+        let args = decode_args(test_png.path(), &["ruSt"]);
+        assert!(decoded(&args).is_empty());
+        fs::remove_file(&output).unwrap();
+
+        assert!(EncodeArgs::try_parse_from([
+            "encode",
+            test_png.arg(),
+            "ruSt",
+            "moved",
+            output_arg,
+            "-o",
+            output_arg,
+        ])
+        .is_err());
+
+        // A lone positional after the file is the message, with the default type.
+        let mut args = EncodeArgs::parse_from(["encode", test_png.arg(), "moved"]);
+        sort_out_positionals(&mut args);
+        assert_eq!(args.chunk_type, None);
+        assert_eq!(args.message.as_deref(), Some("moved"));
+    }
+
+    #[test]
+    fn test_encode_decode_base64() {
+        let test_png = TestPng::new("encode_decode_base64");
+        let output = std::env::temp_dir().join("pngme_test_base64.png");
+        let args = EncodeArgs {
+            base64: true,
+            output_file: Some(output.clone()),
+            ..encode_args(test_png.path(), "ruSt", "+/8APg==")
+        };
+        assert!(encode(args, &ParseOptions::default()).is_ok());
+
+        let png = Png::try_from(fs::read(&output).unwrap().as_slice()).unwrap();
+        assert_eq!(
+            png.chunk_by_type("ruSt").unwrap().data(),
+            [0xfb, 0xff, 0x00, 0x3e]
+        );
+
+        let args = DecodeArgs {
+            base64: true,
+            ..decode_args(output, &["ruSt"])
+        };
+        assert_eq!(decoded(&args), ["ruSt\t+/8APg=="]);
+        assert_eq!(
+            decode(args, &ParseOptions::default()).unwrap(),
+            ExitCode::SUCCESS
+        );
+    }
+
+    #[test]
+    fn test_encode_decode_encrypted() {
+        let test_png = TestPng::new("encode_decode_encrypted");
+        let output = std::env::temp_dir().join("pngme_test_encrypted.png");
+        let args = EncodeArgs {
+            output_file: Some(output.clone()),
+            encrypt: true,
+            password: Some(String::from("hunter2")),
+            ..encode_args(test_png.path(), "ruSt", "Test message")
+        };
+        assert!(encode(args, &ParseOptions::default()).is_ok());
+
+        let args = DecodeArgs {
+            decrypt: true,
+            password: Some(String::from("hunter3")),
+            ..decode_args(output.clone(), &["ruSt"])
+        };
+        assert!(decode(args, &ParseOptions::default()).is_err());
+
+        let args = DecodeArgs {
+            decrypt: true,
+            password: Some(String::from("hunter2")),
+            ..decode_args(output, &["ruSt"])
+        };
+        assert_eq!(decoded(&args), ["ruSt\tTest message"]);
+        assert_eq!(
+            decode(args, &ParseOptions::default()).unwrap(),
+            ExitCode::SUCCESS
+        );
+    }
+
+    #[test]
+    fn test_chunk_type_warning() {
+        let chunk = |chunk_type: &str, data: &[u8]| {
+            Chunk::new(ChunkType::from_str(chunk_type).unwrap(), data.to_vec())
+        };
+
+        assert_eq!(chunk_type_warning(&chunk("ruSt", b"secret")), None);
+        assert_eq!(chunk_type_warning(&chunk("tEXt", b"Title\0Dice")), None);
+
+        let warning = chunk_type_warning(&chunk("RUSt", b"secret")).unwrap();
+        assert!(warning.contains("not a registered chunk type"));
+        assert!(warning.contains("like RuSt"));
+
+        let warning = chunk_type_warning(&chunk("tEXt", b"no keyword separator")).unwrap();
+        assert!(warning.contains("standard chunk type (textual data)"));
+        assert!(chunk_type_warning(&chunk("gAMA", b"secret")).is_some());
+    }
+
+    #[test]
+    fn test_encode_decode_signed() {
+        let test_png = TestPng::new("encode_decode_signed");
+        let output = std::env::temp_dir().join("pngme_test_signed.png");
+        let output_arg = output.to_str().unwrap();
+        let args = EncodeArgs::parse_from([
+            "encode",
+            test_png.arg(),
+            "ruSt",
+            "Test message",
+            "--sign",
+            "--key",
+            "hunter2",
+            "-o",
+            output_arg,
+        ]);
+        assert!(encode(args, &ParseOptions::default()).is_ok());
+
+        let png = Png::try_from(fs::read(&output).unwrap().as_slice()).unwrap();
+        let data = png.chunk_by_type("ruSt").unwrap().data();
+        assert!(crypto::is_signed(data));
+
+        let args =
+            DecodeArgs::parse_from(["decode", "ruSt", output_arg, "--verify", "--key", "hunter2"]);
+        assert_eq!(
+            decode(args, &ParseOptions::default()).unwrap(),
+            ExitCode::SUCCESS
+        );
+
+        let args =
+            DecodeArgs::parse_from(["decode", "ruSt", output_arg, "--verify", "--key", "hunter3"]);
+        assert!(decode(args, &ParseOptions::default()).is_err());
+
+        // Without --verify the signature goes unchecked but isn't printed either.
+        let args = decode_args(output, &["ruSt"]);
+        assert_eq!(decoded(&args), ["ruSt\tTest message"]);
+
+        let args =
+            DecodeArgs::parse_from(["decode", "IHDR", test_png.arg(), "--verify", "--key", "k"]);
+        assert!(decode(args, &ParseOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_encode_decode_signed_and_encrypted() {
+        let test_png = TestPng::new("encode_decode_signed_and_encrypted");
+        let output = std::env::temp_dir().join("pngme_test_signed_encrypted.png");
+        let args = EncodeArgs {
+            output_file: Some(output.clone()),
+            encrypt: true,
+            password: Some(String::from("hunter2")),
+            sign: true,
+            key: Some(String::from("k")),
+            ..encode_args(test_png.path(), "ruSt", "Test message")
+        };
+        assert!(encode(args, &ParseOptions::default()).is_ok());
+
+        let args = DecodeArgs {
+            decrypt: true,
+            password: Some(String::from("hunter2")),
+            ..decode_args(output.clone(), &["ruSt"])
+        };
+        assert_eq!(decoded(&args), ["ruSt\tTest message"]);
+
+        let args = DecodeArgs {
+            verify_signature: true,
+            key: Some(String::from("k")),
+            ..args
+        };
+        assert_eq!(
+            decode(args, &ParseOptions::default()).unwrap(),
+            ExitCode::SUCCESS
+        );
+        fs::remove_file(&output).unwrap();
+    }
+
+    #[test]
+    fn test_decode_multiple_types() {
+        let test_png = TestPng::new("decode_multiple_types");
+        let args = DecodeArgs {
+            quiet: true,
+            ..decode_args(test_png.path(), &["IHDR", "NoNe", "gAMA"])
+        };
+        assert_eq!(
+            decode(args, &ParseOptions::default()).unwrap(),
+            ExitCode::SUCCESS
+        );
+
+        let args = DecodeArgs {
+            quiet: true,
+            ..decode_args(test_png.path(), &["NoNe", "NoPe"])
+        };
+        assert_eq!(
+            decode(args, &ParseOptions::default()).unwrap(),
+            ExitCode::FAILURE
+        );
+    }
+
+    /// A directory with two PNGs holding a `ruSt` chunk, one without, a corrupt
+    /// `.png` and a non-PNG file, spread over a subdirectory.
+    fn testing_directory(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("sub")).unwrap();
+
+        let png = |chunk_types: &[&str]| {
+            Png::from_chunks(
+                chunk_types
+                    .iter()
+                    .map(|chunk_type| {
+                        Chunk::new(ChunkType::from_str(chunk_type).unwrap(), b"hi".to_vec())
+                    })
+                    .collect(),
+            )
+            .as_bytes()
+        };
+        fs::write(dir.join("a.png"), png(&["IHDR", "ruSt", "IEND"])).unwrap();
+        fs::write(dir.join("broken.png"), b"not a png").unwrap();
+        fs::write(dir.join("notes.txt"), b"not a png either").unwrap();
+        fs::write(dir.join("sub").join("b.png"), png(&["IHDR", "IEND"])).unwrap();
+        fs::write(
+            dir.join("sub").join("c.PNG"),
+            png(&["IHDR", "ruSt", "IEND"]),
+        )
+        .unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_png_files() {
+        let dir = testing_directory("pngme_test_png_files");
+        let names = |files: Vec<PathBuf>| -> Vec<PathBuf> {
+            files
+                .iter()
+                .map(|file| file.strip_prefix(&dir).unwrap().to_path_buf())
+                .collect()
+        };
+
+        assert_eq!(
+            names(png_files(&dir, false).unwrap()),
+            [PathBuf::from("a.png"), PathBuf::from("broken.png")]
+        );
+        assert_eq!(
+            names(png_files(&dir, true).unwrap()),
+            [
+                PathBuf::from("a.png"),
+                PathBuf::from("broken.png"),
+                PathBuf::from("sub/b.png"),
+                PathBuf::from("sub/c.PNG"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decode_and_list_directory() {
+        let dir = testing_directory("pngme_test_scan_directory");
+
+        let args = DecodeArgs {
+            recursive: true,
+            ..decode_args(dir.clone(), &["ruSt"])
+        };
+        assert_eq!(
+            decode(args, &ParseOptions::default()).unwrap(),
+            ExitCode::SUCCESS
+        );
+
+        let args = DecodeArgs {
+            recursive: true,
+            ..decode_args(dir.clone(), &["NoNe"])
+        };
+        assert_eq!(
+            decode(args, &ParseOptions::default()).unwrap(),
+            ExitCode::FAILURE
+        );
+
+        let args = ListArgs {
+            file_path: dir.clone(),
+            recursive: true,
+            format: OutputFormat::Text,
+            sizes: false,
+            filter: Some(String::from("ruSt")),
+            histogram: false,
+        };
+        assert!(list(args, &ParseOptions::default()).is_ok());
+
+        let args = ListArgs {
+            file_path: dir,
+            recursive: false,
+            format: OutputFormat::Json,
+            sizes: false,
+            filter: None,
+            histogram: false,
+        };
+        assert!(matches!(
+            list(args, &ParseOptions::default()).unwrap_err(),
+            crate::Error::Command(CommandError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_encode_check_capacity() {
+        let input = std::env::temp_dir().join("pngme_test_capacity_in.png");
+        let output = std::env::temp_dir().join("pngme_test_capacity_out.png");
+        let png = Png::from_chunks(vec![
+            Chunk::new(
+                ChunkType::from_str("IHDR").unwrap(),
+                vec![0, 0, 0, 8, 0, 0, 0, 8, 8, 2, 0, 0, 0],
+            ),
+            Chunk::new(ChunkType::from_str("IEND").unwrap(), vec![]),
+        ]);
+        fs::write(&input, png.as_bytes()).unwrap();
+
+        let args = EncodeArgs {
+            output_file: Some(output.clone()),
+            check_capacity: true,
+            ..encode_args(input, "ruSt", "fits")
+        };
+        assert!(encode(args, &ParseOptions::default()).is_ok());
+
+        let png = Png::try_from(fs::read(&output).unwrap().as_slice()).unwrap();
+        assert_eq!(png.chunk_by_type("ruSt").unwrap().data(), b"fits");
+    }
+
+    #[test]
+    fn test_encode_with_other_crc_algorithm() {
+        let input = std::env::temp_dir().join("pngme_test_bzip2_in.png");
+        let output = std::env::temp_dir().join("pngme_test_bzip2_out.png");
+        let mut png = Png::from_chunks(vec![
+            Chunk::new(ChunkType::from_str("IHDR").unwrap(), vec![]),
+            Chunk::new(ChunkType::from_str("IEND").unwrap(), vec![]),
+        ]);
+        png.recompute_crcs_with(CrcAlgorithm::Bzip2);
+        fs::write(&input, png.as_bytes()).unwrap();
+        let options = ParseOptions::default().with_crc_algorithm(CrcAlgorithm::Bzip2);
+
+        let args = EncodeArgs {
+            output_file: Some(output.clone()),
+            ..encode_args(input, "ruSt", "bzip2")
+        };
+        // The new chunk is checksummed the same way as the ones read in.
+        assert!(encode(args, &options).is_ok());
+
+        let bytes = fs::read(&output).unwrap();
+        assert!(Png::try_from(bytes.as_slice()).is_err());
+        let png = Png::parse_with(&bytes, &options).unwrap();
+        assert_eq!(png.chunk_by_type("ruSt").unwrap().data(), b"bzip2");
+    }
+
+    #[test]
+    fn test_ensure_fits_checks_whole_messages() {
+        let chunk_type = ChunkType::from_str("ruSt").unwrap();
+        let chunks = vec![
+            Chunk::new(chunk_type, b"fits".to_vec()),
+            Chunk::new(chunk_type, b"too long".to_vec()),
+        ];
+
+        assert!(ensure_fits(&chunks, 8).is_ok());
+        assert!(matches!(
+            ensure_fits(&chunks, 4),
+            Err(crate::Error::Command(CommandError::MessageTooLarge(8, 4)))
+        ));
+        // Split pieces would each fit, so the check must run before splitting.
+        assert!(ensure_fits(&Chunk::split(chunk_type, b"too long", 4), 4).is_ok());
+    }
+
+    #[test]
+    fn test_encode_multiple_chunks() {
+        let test_png = TestPng::new("encode_multiple_chunks");
+        let output = std::env::temp_dir().join("pngme_test_encode_multiple.png");
+        let args = EncodeArgs::parse_from([
+            "encode",
+            test_png.arg(),
+            "--chunk",
+            "ruSt=first",
+            "--chunk",
+            "auTh=Ferris",
+            "--chunk",
+            "ruSt=a=b",
+            "--output",
+            output.to_str().unwrap(),
+        ]);
+        assert!(encode(args, &ParseOptions::default()).is_ok());
+
+        let png = Png::try_from(fs::read(&output).unwrap().as_slice()).unwrap();
+        let added: Vec<(String, &[u8])> = png
+            .chunks()
+            .iter()
+            .rev()
+            .skip(1)
+            .take(3)
+            .rev()
+            .map(|chunk| (chunk.chunk_type().to_string(), chunk.data()))
+            .collect();
+        assert_eq!(
+            added,
+            [
+                (String::from("ruSt"), b"first".as_slice()),
+                (String::from("auTh"), b"Ferris".as_slice()),
+                (String::from("ruSt"), b"a=b".as_slice()),
+            ]
+        );
+        assert_eq!(png.chunks().last().unwrap().chunk_type().as_str(), "IEND");
+    }
+
+    #[test]
+    fn test_remove_read_only_output() {
+        let path = std::env::temp_dir().join("pngme_test_read_only.png");
+        if path.exists() {
+            let mut permissions = fs::metadata(&path).unwrap().permissions();
+            #[allow(clippy::permissions_set_readonly_false)]
+            permissions.set_readonly(false);
+            fs::set_permissions(&path, permissions).unwrap();
+        }
+        let png = Png::from_chunks(vec![
+            Chunk::new(ChunkType::from_str("IHDR").unwrap(), vec![]),
+            Chunk::new(ChunkType::from_str("ruSt").unwrap(), b"hi".to_vec()),
+            Chunk::new(ChunkType::from_str("IEND").unwrap(), vec![]),
+        ]);
+        fs::write(&path, png.as_bytes()).unwrap();
+        let mut permissions = fs::metadata(&path).unwrap().permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(&path, permissions).unwrap();
+
+        let remove_args = |force| RemoveArgs {
+            chunk_type: Some(String::from("ruSt")),
+            file_path: path.clone(),
+            output_file: None,
+            all: false,
+            index: None,
+            ignore_case: false,
+            summary: false,
+            backup: BackupArgs {
+                force,
+                ..no_backup()
+            },
+        };
+        let err = remove(remove_args(false), &ParseOptions::default()).unwrap_err();
+        assert!(
+            matches!(err, crate::Error::Command(CommandError::ReadOnlyOutput(_))),
+            "{err}"
+        );
+        assert!(err.to_string().contains("read-only, pass --force"));
+        assert_eq!(fs::read(&path).unwrap(), png.as_bytes());
+
+        remove(remove_args(true), &ParseOptions::default()).unwrap();
+        assert!(!fs::metadata(&path).unwrap().permissions().readonly());
+        let png = Png::try_from(fs::read(&path).unwrap().as_slice()).unwrap();
+        assert!(png.chunk_by_type("ruSt").is_none());
+    }
+
+    #[test]
+    fn test_encode_upsert() {
+        let path = std::env::temp_dir().join("pngme_test_upsert.png");
+        let png = Png::from_chunks(vec![
+            Chunk::new(ChunkType::from_str("IHDR").unwrap(), vec![]),
+            Chunk::new(ChunkType::from_str("IEND").unwrap(), vec![]),
+        ]);
+        fs::write(&path, png.as_bytes()).unwrap();
+
+        let path_arg = path.to_str().unwrap();
+        for message in ["first", "second", "second"] {
+            let args = EncodeArgs::parse_from(["encode", path_arg, "ruSt", message, "--upsert"]);
+            assert!(encode(args, &ParseOptions::default()).is_ok());
+        }
+        let png = Png::try_from(fs::read(&path).unwrap().as_slice()).unwrap();
+        let types: Vec<&str> = png.iter().map(|c| c.chunk_type().as_str()).collect();
+        assert_eq!(types, ["IHDR", "ruSt", "IEND"]);
+        assert_eq!(png.chunk_by_type("ruSt").unwrap().data(), b"second");
+
+        // Chunks of the same type in one call don't replace each other.
+        let args = EncodeArgs::parse_from([
+            "encode", path_arg, "--chunk", "ruSt=a", "--chunk", "ruSt=b", "--upsert",
+        ]);
+        assert!(encode(args, &ParseOptions::default()).is_ok());
+        let png = Png::try_from(fs::read(&path).unwrap().as_slice()).unwrap();
+        let messages: Vec<&[u8]> = png
+            .chunks_by_type("ruSt")
+            .iter()
+            .map(|c| c.data())
+            .collect();
+        assert_eq!(messages, [b"a", b"b"]);
+        assert_eq!(png.chunks().len(), 4);
+    }
+
+    #[test]
+    fn test_encode_chunks_rejects_bad_specs() {
+        let test_png = TestPng::new("encode_chunks_rejects_bad_specs");
+        let mut args = EncodeArgs::parse_from([
+            "encode",
+            test_png.arg(),
+            "--chunk",
+            "ruSt=ok",
+            "--chunk",
+            "missing",
+        ]);
+        assert!(matches!(
+            encode_chunks(&args).unwrap_err(),
+            crate::Error::Command(CommandError::MissingArgument(_))
+        ));
+        args.chunks = vec![String::from("ru5t=typo")];
+        assert!(matches!(
+            encode_chunks(&args).unwrap_err(),
+            crate::Error::Command(CommandError::InvalidChunkType(_))
+        ));
+    }
+
+    #[test]
+    fn test_encode_message_file() {
+        let test_png = TestPng::new("encode_message_file");
+        let message_file = std::env::temp_dir().join("pngme_test_message.txt");
+        let output = std::env::temp_dir().join("pngme_test_message_file.png");
+        fs::write(&message_file, "Line one\nLine two\n").unwrap();
+
+        let args = EncodeArgs {
+            message: None,
+            message_file: Some(message_file.clone()),
+            output_file: Some(output.clone()),
+            ..encode_args(test_png.path(), "ruSt", "")
+        };
+        assert!(encode(args, &ParseOptions::default()).is_ok());
+
+        let png = Png::try_from(fs::read(&output).unwrap().as_slice()).unwrap();
+        assert_eq!(
+            png.chunk_by_type("ruSt").unwrap().data(),
+            b"Line one\nLine two\n"
+        );
+
+        let args = EncodeArgs {
+            message_file: Some(message_file),
+            output_file: Some(output),
+            ..encode_args(test_png.path(), "ruSt", "Test message")
+        };
+        assert!(encode(args, &ParseOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_encode_decode_text() {
+        let test_png = TestPng::new("encode_decode_text");
+        let output = std::env::temp_dir().join("pngme_test_text.png");
+        let args = EncodeArgs::parse_from([
+            "encode",
+            test_png.arg(),
+            "--text",
+            "Author=Ferris",
+            "--output",
+            output.to_str().unwrap(),
+        ]);
+        assert!(encode(args, &ParseOptions::default()).is_ok());
+
+        let args = decode_args(output, &["tEXt"]);
+        assert_eq!(decoded(&args), ["Author: Ferris"]);
+        assert_eq!(
+            decode(args, &ParseOptions::default()).unwrap(),
+            ExitCode::SUCCESS
+        );
+    }
+
+    #[test]
+    fn test_encode_decode_itxt() {
+        let test_png = TestPng::new("encode_decode_itxt");
+        let output = std::env::temp_dir().join("pngme_test_itxt.png");
+        let args = EncodeArgs::parse_from([
+            "encode",
+            test_png.arg(),
+            "--itxt",
+            "Title=Gr\u{fc}\u{df}e",
+            "--language",
+            "de",
+            "--translated-keyword",
+            "Titel",
+            "--compress-text",
+            "--output",
+            output.to_str().unwrap(),
+        ]);
+        assert!(encode(args, &ParseOptions::default()).is_ok());
+
+        let args = decode_args(output, &["iTXt"]);
+        assert_eq!(decoded(&args), ["Title [de] (Titel): Gr\u{fc}\u{df}e"]);
+        assert_eq!(
+            decode(args, &ParseOptions::default()).unwrap(),
+            ExitCode::SUCCESS
+        );
+    }
+
+    #[test]
+    fn test_decode() {
+        let test_png = TestPng::new("decode");
+        // The fixture carries no tEXt chunk.
+        let args = decode_args(test_png.path(), &["tEXt"]);
+        assert!(decoded(&args).is_empty());
+        assert_eq!(
+            decode(args, &ParseOptions::default()).unwrap(),
+            ExitCode::FAILURE
+        );
+    }
+
+    #[test]
+    fn test_decode_no_verify() {
+        let path = std::env::temp_dir().join("pngme_test_no_verify.png");
+        let png = Png::from_chunks(vec![
+            Chunk::new(ChunkType::from_str("IHDR").unwrap(), vec![]),
+            Chunk::new(ChunkType::from_str("ruSt").unwrap(), b"edited".to_vec()),
+            Chunk::new(ChunkType::from_str("IEND").unwrap(), vec![]),
+        ]);
+        let mut bytes = png.as_bytes();
+        // Flip a data byte of ruSt without fixing its CRC, as a hex editor would.
+        let offset = bytes.windows(6).position(|w| w == b"edited").unwrap();
+        bytes[offset] = b'E';
+        fs::write(&path, bytes).unwrap();
+
+        let args = |no_verify| DecodeArgs {
+            no_verify,
+            quiet: true,
+            ..decode_args(path.clone(), &["ruSt"])
+        };
+        assert!(matches!(
+            decode(args(false), &ParseOptions::default()).unwrap_err(),
+            crate::Error::Png(pngme::Error::Chunk(ChunkError::InvalidCrc(..)))
+        ));
+        assert_eq!(
+            decode(args(true), &ParseOptions::default()).unwrap(),
+            ExitCode::SUCCESS
+        );
+
+        // Skipping CRCs doesn't lift the chunk size limit.
+        let options = ParseOptions::default().with_max_chunk_size(4);
+        assert!(matches!(
+            decode(args(true), &options).unwrap_err(),
+            crate::Error::Png(pngme::Error::Chunk(ChunkError::ExceedsSizeLimit(6, 4)))
+        ));
+    }
+
+    #[test]
+    fn test_palette_table() {
+        assert_eq!(
+            palette_table(&[[255, 0, 0], [0, 128, 255]]),
+            "  0  #ff0000  (255, 0, 0)\n  1  #0080ff  (0, 128, 255)\n"
+        );
+    }
+
+    #[test]
+    fn test_palette_missing() {
+        let test_png = TestPng::new("palette_missing");
+        let args = PaletteArgs {
+            file_path: test_png.path(),
+        };
+        assert!(matches!(
+            palette(args, &ParseOptions::default()).unwrap_err(),
+            crate::Error::Png(pngme::Error::Decode(PngDecodeError::ChunkTypeNotFound(_)))
+        ));
+    }
+
+    #[test]
+    fn test_split_at_null() {
+        assert_eq!(
+            split_at_null(b"Author\0Ferris the Crab"),
+            "Keyword: Author\nValue:   Ferris the Crab"
+        );
+        // iCCP: profile name, NUL, then a compression byte and zlib data.
+        assert_eq!(
+            split_at_null(b"sRGB\0\0\x78\x9c"),
+            "Keyword: sRGB\nValue:   [hex] 00 78 9c"
+        );
+        assert_eq!(
+            split_at_null(b"plain"),
+            "Keyword: (no NUL separator)\nValue:   plain"
+        );
+        assert_eq!(split_at_null(b"key\0"), "Keyword: key\nValue:   ");
+    }
+
+    #[test]
+    fn test_decode_split_null() {
+        let args = DecodeArgs::parse_from(["decode", "ruSt", "tEXt", "unused.png", "--split-null"]);
+        let chunk = Chunk::new(ChunkType::from_str("ruSt").unwrap(), b"key\0value".to_vec());
+        assert_eq!(
+            decode_message(&chunk, &args, true).unwrap(),
+            "ruSt\tKeyword: key\nValue:   value"
+        );
+
+        assert!(DecodeArgs::try_parse_from([
+            "decode",
+            "ruSt",
+            "x.png",
+            "--split-null",
+            "--base64"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_decode_ascii_only() {
+        let args = DecodeArgs::parse_from(["decode", "ruSt", "unused.png", "--ascii-only"]);
+        let chunk = Chunk::new(
+            ChunkType::from_str("ruSt").unwrap(),
+            b"\x1b[2Jhi\x07\xff".to_vec(),
+        );
+        assert_eq!(
+            decode_message(&chunk, &args, false).unwrap(),
+            "ruSt\t\\x1b[2Jhi\\x07\\xff"
+        );
+
+        let text = TextChunk::new("Title", "caf\u{e9}")
+            .unwrap()
+            .to_chunk()
+            .unwrap();
+        assert_eq!(
+            decode_message(&text, &args, true).unwrap(),
+            "tEXt\tTitle: caf\\xc3\\xa9"
+        );
+    }
+
+    #[test]
+    fn test_encode_compress() {
+        let test_png = TestPng::new("encode_compress");
+        let path = std::env::temp_dir().join("pngme_test_compress.png");
+        let message = "squeeze me ".repeat(40);
+        let args = EncodeArgs::parse_from([
+            "encode",
+            test_png.arg(),
+            "ruSt",
+            message.as_str(),
+            "--compress",
+            "-o",
+            path.to_str().unwrap(),
+        ]);
+        encode(args, &ParseOptions::default()).unwrap();
+
+        let png = Png::try_from(fs::read(&path).unwrap().as_slice()).unwrap();
+        let chunk = png.chunk_by_type("ruSt").unwrap();
+        assert!(chunk.data().len() < message.len());
+
+        let args = |extra: &[&str]| {
+            let mut argv = vec!["decode", "ruSt", "unused.png"];
+            argv.extend(extra);
+            DecodeArgs::parse_from(argv)
+        };
+        assert_eq!(
+            decode_message(chunk, &args(&[]), false).unwrap(),
+            format!("ruSt\t{message}")
+        );
+        assert_eq!(
+            decode_message(chunk, &args(&["--base64"]), false).unwrap(),
+            format!("ruSt\t{}", base64::encode(message.as_bytes()))
+        );
+
+        // Uncompressed messages that happen to mention the magic still decode.
+        let plain = Chunk::new(
+            ChunkType::from_str("ruSt").unwrap(),
+            b"about pngmeZ".to_vec(),
+        );
+        assert_eq!(
+            decode_message(&plain, &args(&[]), false).unwrap(),
+            "ruSt\tabout pngmeZ"
+        );
+
+        let mut data = chunk.data().to_vec();
+        data.truncate(data.len() - 4);
+        let truncated = Chunk::new(ChunkType::from_str("ruSt").unwrap(), data);
+        let err = decode_message(&truncated, &args(&[]), false).unwrap_err();
+        assert!(err.to_string().contains("corrupt or truncated"), "{err}");
+    }
+
+    #[test]
+    fn test_decode_compressed_text() {
+        let args = DecodeArgs {
+            quiet: true,
+            ..decode_args(PathBuf::from("unused.png"), &["zTXt"])
+        };
+        let chunk = CompressedTextChunk::new("Comment", "squeezed")
+            .unwrap()
+            .to_chunk()
+            .unwrap();
+        assert_eq!(
+            decode_message(&chunk, &args, false).unwrap(),
+            "Comment: squeezed"
+        );
+
+        let corrupt = Chunk::new(
+            ChunkType::from_str("zTXt").unwrap(),
+            b"Comment\0\0garbage".to_vec(),
+        );
+        let err = decode_message(&corrupt, &args, false).unwrap_err();
+        assert!(err.to_string().contains("Could not decompress"), "{err}");
+    }
+
+    #[test]
+    fn test_decode_not_found() {
+        let test_png = TestPng::new("decode_not_found");
+        let args = DecodeArgs {
+            quiet: true,
+            ..decode_args(test_png.path(), &["NoNe"])
+        };
+        assert_eq!(
+            decode(args, &ParseOptions::default()).unwrap(),
+            ExitCode::FAILURE
+        );
+    }
+
+    #[test]
+    fn test_replace() {
+        let output = std::env::temp_dir().join("pngme_test_replace.png");
+        let png = Png::from_chunks(vec![
+            Chunk::new(ChunkType::from_str("IHDR").unwrap(), vec![]),
+            Chunk::new(ChunkType::from_str("ruSt").unwrap(), b"Old".to_vec()),
+            Chunk::new(ChunkType::from_str("IEND").unwrap(), vec![]),
+        ]);
+        fs::write(&output, png.as_bytes()).unwrap();
+
+        let backup = std::env::temp_dir().join("pngme_test_replace.png.bak");
+        let _ = fs::remove_file(&backup);
+        let args = ReplaceArgs {
+            file_path: output.clone(),
+            chunk_type: String::from("ruSt"),
+            message: String::from("New message"),
+            output_file: None,
+            backup: BackupArgs {
+                backup: true,
+                ..no_backup()
+            },
+        };
+        assert!(replace(args, &ParseOptions::default()).is_ok());
+        assert_eq!(fs::read(&backup).unwrap(), png.as_bytes());
+
+        let png = Png::try_from(fs::read(&output).unwrap().as_slice()).unwrap();
+        assert_eq!(png.chunks()[1].data(), b"New message");
+
+        let args = ReplaceArgs {
+            file_path: output,
+            chunk_type: String::from("NoNe"),
+            message: String::from("New message"),
+            output_file: None,
+            backup: no_backup(),
+        };
+        assert!(replace(args, &ParseOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_dedup() {
+        let path = std::env::temp_dir().join("pngme_test_dedup.png");
+        let png = Png::from_chunks(
+            [
+                "IHDR", "ruSt", "IDAT", "IDAT", "ruSt", "tEXt", "tEXt", "IEND",
+            ]
+            .iter()
+            .map(|chunk_type| Chunk::new(ChunkType::from_str(chunk_type).unwrap(), vec![]))
+            .collect(),
+        );
+        fs::write(&path, png.as_bytes()).unwrap();
+        let args = |chunk_type: Option<&str>| DedupArgs {
+            file_path: path.clone(),
+            chunk_type: chunk_type.map(String::from),
+            output_file: None,
+            backup: no_backup(),
+        };
+
+        dedup(args(Some("ruSt")), &ParseOptions::default()).unwrap();
+        let deduped = Png::try_from(fs::read(&path).unwrap().as_slice()).unwrap();
+        assert_eq!(deduped.chunks().len(), 7);
+        assert_eq!(deduped.chunks_by_type("tEXt").len(), 2);
+
+        dedup(args(None), &ParseOptions::default()).unwrap();
+        let deduped = Png::try_from(fs::read(&path).unwrap().as_slice()).unwrap();
+        assert_eq!(deduped.chunks().len(), 6);
+        assert_eq!(deduped.chunks_by_type("IDAT").len(), 2);
+
+        assert!(dedup(args(Some("ru$t")), &ParseOptions::default()).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_canonicalize() {
+        let path = std::env::temp_dir().join("pngme_test_canonicalize.png");
+        let png = Png::from_chunks(
+            ["IHDR", "IDAT", "IEND", "ruSt"]
+                .iter()
+                .map(|chunk_type| Chunk::new(ChunkType::from_str(chunk_type).unwrap(), vec![]))
+                .collect(),
+        );
+        fs::write(&path, png.as_bytes()).unwrap();
+        let args = || CanonicalizeArgs {
+            file_path: path.clone(),
+            output_file: None,
+            backup: no_backup(),
+        };
+
+        canonicalize(args(), &ParseOptions::default()).unwrap();
+        let canonical = Png::try_from(fs::read(&path).unwrap().as_slice()).unwrap();
+        assert!(canonical.validate_structure().is_ok());
+        assert_eq!(canonical.chunks()[2].chunk_type().as_str(), "ruSt");
+
+        fs::write(&path, Png::from_chunks(vec![]).as_bytes()).unwrap();
+        assert!(canonicalize(args(), &ParseOptions::default()).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_remove() {
+        let output = std::env::temp_dir().join("pngme_test_remove.png");
+        let png = Png::from_chunks(
+            ["IHDR", "tEXt", "IEND"]
+                .iter()
+                .map(|chunk_type| Chunk::new(ChunkType::from_str(chunk_type).unwrap(), vec![]))
+                .collect(),
+        );
+        fs::write(&output, png.as_bytes()).unwrap();
+
+        let args = RemoveArgs {
+            file_path: output.clone(),
+
+            output_file: None,
+            chunk_type: Some(String::from("tEXt")),
+            all: false,
+            index: None,
+            ignore_case: false,
+            summary: false,
+            backup: no_backup(),
+        };
+        assert!(remove(args, &ParseOptions::default()).is_ok());
+
+        let args = RemoveArgs {
+            file_path: output,
+
+            output_file: None,
+            chunk_type: Some(String::from("tEXt")),
+            all: false,
+            index: None,
+            ignore_case: false,
+            summary: false,
+            backup: no_backup(),
+        };
+        assert!(remove(args, &ParseOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_remove_ignore_case() {
+        let output = std::env::temp_dir().join("pngme_test_remove_ignore_case.png");
+        let png = Png::from_chunks(
+            ["IHDR", "tEXt", "TEXt", "tExT", "zTXt", "IEND"]
+                .iter()
+                .map(|chunk_type| Chunk::new(ChunkType::from_str(chunk_type).unwrap(), vec![]))
+                .collect(),
+        );
+        fs::write(&output, png.as_bytes()).unwrap();
+        let args = |chunk_type: &str, all, ignore_case| RemoveArgs {
+            file_path: output.clone(),
+            output_file: None,
+            chunk_type: Some(String::from(chunk_type)),
+            all,
+            index: None,
+            ignore_case,
+            summary: false,
+            backup: no_backup(),
+        };
+
+        assert!(remove(args("text", false, false), &ParseOptions::default()).is_err());
+        remove(args("text", false, true), &ParseOptions::default()).unwrap();
+        let png = Png::try_from(fs::read(&output).unwrap().as_slice()).unwrap();
+        assert_eq!(png.chunks()[1].chunk_type().as_str(), "TEXt");
+
+        remove(args("TEXT", true, true), &ParseOptions::default()).unwrap();
+        let png = Png::try_from(fs::read(&output).unwrap().as_slice()).unwrap();
+        let types: Vec<&str> = png
+            .iter()
+            .map(|chunk| chunk.chunk_type().as_str())
+            .collect();
+        assert_eq!(types, ["IHDR", "zTXt", "IEND"]);
+        fs::remove_file(&output).unwrap();
+    }
+
+    #[test]
+    fn test_decode_ignore_case() {
+        let test_png = TestPng::new("decode_ignore_case");
+        let args = DecodeArgs::parse_from(["decode", "ihdr", test_png.arg(), "--quiet"]);
+        assert_eq!(
+            decode(args, &ParseOptions::default()).unwrap(),
+            ExitCode::FAILURE
+        );
+        let args = DecodeArgs::parse_from(["decode", "ihdr", test_png.arg(), "--ignore-case"]);
+        assert_eq!(
+            decode(args, &ParseOptions::default()).unwrap(),
+            ExitCode::SUCCESS
+        );
+    }
+
+    #[test]
+    fn test_remove_index() {
+        let output = std::env::temp_dir().join("pngme_test_remove_index.png");
+        let png = Png::from_chunks(
+            ["IHDR", "first", "second", "third", "IEND"]
+                .iter()
+                .map(|data| match *data {
+                    "IHDR" | "IEND" => Chunk::new(ChunkType::from_str(data).unwrap(), vec![]),
+                    _ => Chunk::new(
+                        ChunkType::from_str("tEXt").unwrap(),
+                        data.as_bytes().to_vec(),
+                    ),
+                })
+                .collect(),
+        );
+        fs::write(&output, png.as_bytes()).unwrap();
+
+        let args = RemoveArgs {
+            file_path: output.clone(),
+
+            output_file: None,
+            chunk_type: Some(String::from("tEXt")),
+            all: false,
+            index: Some(2),
+            ignore_case: false,
+            summary: false,
+            backup: no_backup(),
+        };
+        assert!(remove(args, &ParseOptions::default()).is_ok());
+
+        let png = Png::try_from(fs::read(&output).unwrap().as_slice()).unwrap();
+        let remaining: Vec<&[u8]> = png
+            .chunks_by_type("tEXt")
+            .iter()
+            .map(|c| c.data())
+            .collect();
+        assert_eq!(remaining, [&b"first"[..], &b"second"[..]]);
+
+        let args = RemoveArgs {
+            file_path: output,
+
+            output_file: None,
+            chunk_type: Some(String::from("tEXt")),
+            all: false,
+            index: Some(2),
+            ignore_case: false,
+            summary: false,
+            backup: no_backup(),
+        };
+        assert!(remove(args, &ParseOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_remove_backup() {
+        let output = std::env::temp_dir().join("pngme_test_backup.png");
+        let backup = std::env::temp_dir().join("pngme_test_backup.png.bak");
+        let _ = fs::remove_file(&backup);
+
+        let png = Png::from_chunks(
+            ["IHDR", "tEXt", "tEXt", "IEND"]
+                .iter()
+                .map(|chunk_type| Chunk::new(ChunkType::from_str(chunk_type).unwrap(), vec![]))
+                .collect(),
+        );
+        fs::write(&output, png.as_bytes()).unwrap();
+
+        let backup_args = BackupArgs {
+            backup: true,
+            backup_suffix: String::from(".bak"),
+            force: false,
+        };
+        let args = RemoveArgs {
+            file_path: output.clone(),
+            output_file: None,
+            chunk_type: Some(String::from("tEXt")),
+            all: false,
+            index: None,
+            ignore_case: false,
+            summary: false,
+            backup: backup_args.clone(),
+        };
+        assert!(remove(args, &ParseOptions::default()).is_ok());
+        assert_eq!(fs::read(&backup).unwrap(), png.as_bytes());
+
+        let args = RemoveArgs {
+            file_path: output.clone(),
+
+            output_file: None,
+            chunk_type: Some(String::from("tEXt")),
+            all: false,
+            index: None,
+            ignore_case: false,
+            summary: false,
+            backup: backup_args.clone(),
+        };
+        assert!(remove(args, &ParseOptions::default()).is_err());
+
+        let args = RemoveArgs {
+            file_path: output,
+
+            output_file: None,
+            chunk_type: Some(String::from("tEXt")),
+            all: false,
+            index: Some(0),
+            ignore_case: false,
+            summary: false,
+            backup: BackupArgs {
+                force: true,
+                ..backup_args
+            },
+        };
+        assert!(remove(args, &ParseOptions::default()).is_ok());
+        assert_ne!(fs::read(&backup).unwrap(), png.as_bytes());
+    }
+
+    #[test]
+    fn test_remove_to_output() {
+        let input = std::env::temp_dir().join("pngme_test_remove_output_in.png");
+        let output = std::env::temp_dir().join("pngme_test_remove_output_out.png");
+        let png = Png::from_chunks(
+            ["IHDR", "tEXt", "IEND"]
+                .iter()
+                .map(|chunk_type| Chunk::new(ChunkType::from_str(chunk_type).unwrap(), vec![]))
+                .collect(),
+        );
+        fs::write(&input, png.as_bytes()).unwrap();
+
+        let args = RemoveArgs {
+            file_path: input.clone(),
+            output_file: Some(output.clone()),
+            chunk_type: Some(String::from("tEXt")),
+            all: false,
+            index: None,
+            ignore_case: false,
+            summary: false,
+            backup: no_backup(),
+        };
+        assert!(remove(args, &ParseOptions::default()).is_ok());
+
+        assert_eq!(fs::read(&input).unwrap(), png.as_bytes());
+        let cleaned = Png::try_from(fs::read(&output).unwrap().as_slice()).unwrap();
+        assert!(cleaned.chunk_by_type("tEXt").is_none());
+        assert_eq!(cleaned.chunks().len(), 2);
+    }
+
+    #[test]
+    fn test_remove_all() {
+        let output = std::env::temp_dir().join("pngme_test_remove_all.png");
+        let png = Png::from_chunks(
+            ["IHDR", "tEXt", "tEXt", "IEND"]
+                .iter()
+                .map(|chunk_type| Chunk::new(ChunkType::from_str(chunk_type).unwrap(), vec![]))
+                .collect(),
+        );
+        fs::write(&output, png.as_bytes()).unwrap();
+        let args = |chunk_type: &str| RemoveArgs {
+            file_path: output.clone(),
+            output_file: None,
+            chunk_type: Some(String::from(chunk_type)),
+            all: true,
+            index: None,
+            ignore_case: false,
+            summary: false,
+            backup: no_backup(),
+        };
+
+        remove(args("tEXt"), &ParseOptions::default()).unwrap();
+        let cleaned = Png::try_from(fs::read(&output).unwrap().as_slice()).unwrap();
+        assert_eq!(cleaned.chunks().len(), 2);
+
+        // Matching nothing fails the same way as removing a single chunk.
+        assert!(matches!(
+            remove(args("tEXt"), &ParseOptions::default()).unwrap_err(),
+            crate::Error::Png(pngme::Error::Decode(PngDecodeError::InvalidChunkTypeGiven(chunk_type))) if chunk_type == "tEXt"
+        ));
+        assert_eq!(fs::read(&output).unwrap(), cleaned.as_bytes());
+    }
+
+    #[test]
+    fn test_print() {
+        let test_png = TestPng::new("print");
+        let args = PrintArgs {
+            file_path: test_png.path(),
+            no_verify: false,
+            hex: false,
+            max_bytes: None,
+            format: OutputFormat::Text,
+            ancillary_only: false,
+            critical_only: false,
+            detailed: false,
+            ascii_only: false,
+            watch: false,
+            output_file: None,
+        };
+        assert!(print(args, &ParseOptions::default()).is_ok());
+
+        let args = PrintArgs {
+            file_path: test_png.path(),
+
+            no_verify: false,
+            hex: false,
+            max_bytes: None,
+            format: OutputFormat::Text,
+            ancillary_only: true,
+            critical_only: false,
+            detailed: true,
+            ascii_only: false,
+            watch: false,
+            output_file: None,
+        };
+        assert!(print(args, &ParseOptions::default()).is_ok());
+    }
+
+    #[test]
+    fn test_print_raw() {
+        let test_png = TestPng::new("print_raw");
+        let output = std::env::temp_dir().join("pngme_test_print_raw.png");
+        let args = PrintArgs {
+            file_path: test_png.path(),
+            no_verify: false,
+            hex: false,
+            max_bytes: None,
+            format: OutputFormat::Raw,
+            ancillary_only: false,
+            critical_only: false,
+            detailed: false,
+            ascii_only: false,
+            watch: false,
+            output_file: Some(output.clone()),
+        };
+        assert!(print(args, &ParseOptions::default()).is_ok());
+
+        let original = Png::try_from(fs::read(test_png.path()).unwrap().as_slice()).unwrap();
+        let clean = Png::try_from(fs::read(&output).unwrap().as_slice()).unwrap();
+        assert!(clean.validate_structure().is_ok());
+        assert!(clean.iter().all(Chunk::is_critical));
+        assert_eq!(clean, original.baseline());
+        fs::remove_file(&output).unwrap();
+
+        let args = ListArgs {
+            file_path: test_png.path(),
+            recursive: false,
+            format: OutputFormat::Raw,
+            sizes: false,
+            filter: None,
+            histogram: false,
+        };
+        assert!(matches!(
+            list(args, &ParseOptions::default()).unwrap_err(),
+            crate::Error::Command(CommandError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_list() {
+        let test_png = TestPng::new("list");
+        let args = ListArgs {
+            file_path: test_png.path(),
+            recursive: false,
+            format: OutputFormat::Text,
+            sizes: true,
+            filter: None,
+            histogram: false,
+        };
+        assert!(list(args, &ParseOptions::default()).is_ok());
+    }
+
+    #[test]
+    fn test_chunks_as_json() {
+        let test_png = TestPng::new("chunks_as_json");
+        let png = Png::from_chunks(vec![
+            Chunk::new(ChunkType::from_str("ruSt").unwrap(), b"foo".to_vec()),
+            Chunk::new(ChunkType::from_str("IEND").unwrap(), vec![]),
+        ]);
+        let expected = serde_json::json!([
+            {"index": 0, "type": "ruSt", "length": 3, "crc": png.chunks()[0].crc(), "data_base64": "Zm9v"},
+            {"index": 1, "type": "IEND", "length": 0, "crc": 2923585666u32, "data_base64": ""},
+        ]);
+        let json = chunks_as_json(png.iter().enumerate());
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&json).unwrap(),
+            expected
+        );
+        assert_eq!(chunks_as_json(std::iter::empty()), "[]");
+
+        let args = ListArgs {
+            file_path: test_png.path(),
+
+            recursive: false,
+            format: OutputFormat::Json,
+            sizes: false,
+            filter: Some(String::from("^I")),
+            histogram: false,
+        };
+        assert!(list(args, &ParseOptions::default()).is_ok());
+    }
+
+    #[test]
+    fn test_hide_and_reveal() {
+        let test_png = TestPng::new("hide_and_reveal");
+        let output = std::env::temp_dir().join("pngme_test_hidden.png");
+        let args = HideArgs {
+            file_path: test_png.path(),
+            message: String::from("Test message"),
+            output_file: Some(output.clone()),
+            check_capacity: false,
+        };
+        assert!(hide(args, &ParseOptions::default()).is_ok());
+
+        let args = RevealArgs { file_path: output };
+        assert!(reveal(args, &ParseOptions::default()).is_ok());
+    }
+
+    #[test]
+    fn test_hide_check_capacity_too_large() {
+        let test_png = TestPng::new("hide_check_capacity_too_large");
+        let output = std::env::temp_dir().join("pngme_test_hide_capacity.png");
+        let _ = fs::remove_file(&output);
+        let png = Png::try_from(fs::read(test_png.path()).unwrap().as_slice()).unwrap();
+        let pixels = png.embedding_capacity().unwrap().pixels().unwrap();
+
+        let args = HideArgs {
+            file_path: test_png.path(),
+            message: "x".repeat(pixels + 1),
+            output_file: Some(output.clone()),
+            check_capacity: true,
+        };
+        assert!(matches!(
+            hide(args, &ParseOptions::default()),
+            Err(crate::Error::Command(CommandError::MessageTooLarge(size, capacity)))
+                if size == pixels + 1 && capacity == pixels
+        ));
+        assert!(!output.exists());
+    }
+
+    #[test]
+    fn test_validate() {
+        let output = std::env::temp_dir().join("pngme_test_validate.png");
+        let png = Png::from_chunks(
+            ["IHDR", "IDAT", "IEND"]
+                .iter()
+                .map(|chunk_type| Chunk::new(ChunkType::from_str(chunk_type).unwrap(), vec![]))
+                .collect(),
+        );
+        fs::write(&output, png.as_bytes()).unwrap();
+
+        let args = ValidateArgs {
+            file_path: output.clone(),
+        };
+        assert!(validate(args, &ParseOptions::default()).is_ok());
+
+        let png = Png::from_chunks(
+            ["IHDR", "IDAT", "IEND", "ruSt"]
+                .iter()
+                .map(|chunk_type| Chunk::new(ChunkType::from_str(chunk_type).unwrap(), vec![]))
+                .collect(),
+        );
+        fs::write(&output, png.as_bytes()).unwrap();
+
+        let args = ValidateArgs { file_path: output };
+        assert!(validate(args, &ParseOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_info() {
+        let test_png = TestPng::new("info");
+        let args = InfoArgs {
+            file_path: test_png.path(),
+        };
+        assert!(info(args, &ParseOptions::default()).is_ok());
+    }
+
+    #[test]
+    fn test_check() {
+        let output = std::env::temp_dir().join("pngme_test_check.png");
+        let png = Png::from_chunks(
+            ["IHDR", "IDAT", "IEND"]
+                .iter()
+                .map(|chunk_type| Chunk::new(ChunkType::from_str(chunk_type).unwrap(), vec![]))
+                .collect(),
+        );
+        let mut bytes = png.as_bytes();
+        fs::write(&output, &bytes).unwrap();
+
+        let args = CheckArgs {
+            file_path: output.clone(),
+        };
+        assert_eq!(
+            check(args, &ParseOptions::default()).unwrap(),
+            ExitCode::SUCCESS
+        );
+
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+        fs::write(&output, bytes).unwrap();
+
+        let args = CheckArgs { file_path: output };
+        assert_eq!(
+            check(args, &ParseOptions::default()).unwrap(),
+            ExitCode::FAILURE
+        );
+    }
+
+    #[test]
+    fn test_resolve_chunk_type() {
+        let default = || Some(String::from("ruSt"));
+        assert_eq!(resolve_chunk_type(Some("tEXt"), default()).unwrap(), "tEXt");
+        assert_eq!(resolve_chunk_type(None, default()).unwrap(), "ruSt");
+
+        let err = resolve_chunk_type(None, None).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "A chunk type is required, pass one or set PNGME_CHUNK_TYPE"
+        );
+
+        let err = resolve_chunk_type(None, Some(String::from("ru$t"))).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("PNGME_CHUNK_TYPE: Invalid chunk type \"ru$t\": '$' at position 3"));
+        // An explicit argument wins even when the variable is malformed.
+        assert!(resolve_chunk_type(Some("ruSt"), Some(String::from("ru$t"))).is_ok());
+    }
+
+    #[test]
+    fn test_parse_chunk_type_explains_errors() {
+        assert_eq!(parse_chunk_type("ruSt").unwrap().to_string(), "ruSt");
+
+        let err = parse_chunk_type("Ru1t").unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::Command(CommandError::InvalidChunkType(_))
+        ));
+        let message = err.to_string();
+        assert!(
+            message.starts_with(
+                "Invalid chunk type \"Ru1t\": '1' at position 3 is not an ASCII letter"
+            ),
+            "{message}"
+        );
+        assert!(message.contains("four ASCII letters"), "{message}");
+        assert!(message.contains("critical"), "{message}");
+
+        let message = parse_chunk_type("rust1").unwrap_err().to_string();
+        assert!(message.contains("'1' at position 5"), "{message}");
+        let message = parse_chunk_type("rustc").unwrap_err().to_string();
+        assert!(message.contains("it has 5 letters"), "{message}");
+        let message = parse_chunk_type("r\u{fc}St").unwrap_err().to_string();
+        assert!(message.contains("'\u{fc}' at position 2"), "{message}");
+    }
+
+    fn interactive_png() -> Png {
+        Png::from_chunks(vec![
+            Chunk::new(ChunkType::from_str("IHDR").unwrap(), vec![]),
+            Chunk::new(ChunkType::from_str("ruSt").unwrap(), b"secret".to_vec()),
+            Chunk::new(ChunkType::from_str("IEND").unwrap(), vec![]),
+        ])
+    }
+
+    fn run_session(png: &mut Png, input: &str) -> (bool, String) {
+        let mut out = Vec::new();
+        let save = interactive_session(png, "test.png", &mut input.as_bytes(), &mut out).unwrap();
+        (save, String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn test_interactive_session() {
+        let extracted = std::env::temp_dir().join("pngme_test_interactive_extract.bin");
+        let mut png = interactive_png();
+        let input = format!("v 1\nx 1 {}\nr 1\nlist\nquit\ny\n", extracted.display());
+        let (save, out) = run_session(&mut png, &input);
+
+        assert!(save);
+        assert!(out.contains("Data:   secret"), "{out}");
+        assert!(out.contains("Removed chunk 1 (ruSt)"), "{out}");
+        assert!(out.contains("Save changes to test.png? [y/N]"), "{out}");
+        assert_eq!(fs::read(&extracted).unwrap(), b"secret");
+        assert_eq!(png.chunks().len(), 2);
+    }
+
+    #[test]
+    fn test_interactive_session_reports_mistakes() {
+        let mut png = interactive_png();
+        let (save, out) = run_session(&mut png, "view 7\nremove one\nfrobnicate\n\nq\n");
+
+        assert!(!save);
+        assert!(out.contains("Error: "), "{out}");
+        assert!(out.contains("\"one\" is not a chunk number"), "{out}");
+        assert!(out.contains("Unknown command \"frobnicate\""), "{out}");
+        assert!(!out.contains("Save changes"), "{out}");
+        assert_eq!(png, interactive_png());
+    }
+
+    #[test]
+    fn test_interactive_session_eof_discards_changes() {
+        let mut png = interactive_png();
+        assert!(!run_session(&mut png, "r 1\n").0);
+        // Declining, or hitting Ctrl-D at the confirmation, doesn't save either.
+        assert!(!run_session(&mut interactive_png(), "r 1\nq\nn\n").0);
+        assert!(!run_session(&mut interactive_png(), "r 1\nq\n").0);
+    }
+
+    #[test]
+    fn test_merge() {
+        let source = std::env::temp_dir().join("pngme_test_merge_source.png");
+        let target = std::env::temp_dir().join("pngme_test_merge_target.png");
+        let output = std::env::temp_dir().join("pngme_test_merge_output.png");
+        let png = |chunks: &[(&str, &str)]| {
+            Png::from_chunks(
+                chunks
+                    .iter()
+                    .map(|(chunk_type, data)| {
+                        Chunk::new(
+                            ChunkType::from_str(chunk_type).unwrap(),
+                            data.as_bytes().to_vec(),
+                        )
+                    })
+                    .collect(),
+            )
+        };
+        fs::write(
+            &source,
+            png(&[
+                ("IHDR", ""),
+                ("gAMA", "src"),
+                ("ruSt", "one"),
+                ("ruSt", "two"),
+                ("IEND", ""),
+            ])
+            .as_bytes(),
+        )
+        .unwrap();
+        fs::write(
+            &target,
+            png(&[("IHDR", ""), ("gAMA", "own"), ("IEND", "")]).as_bytes(),
+        )
+        .unwrap();
+
+        let merge_args = |chunk_types: &[&str], replace: bool| MergeArgs {
+            source: source.clone(),
+            target: target.clone(),
+            chunk_types: chunk_types.iter().map(|t| t.to_string()).collect(),
+            replace,
+            output_file: Some(output.clone()),
+            backup: no_backup(),
+        };
+        let merged = || Png::try_from(fs::read(&output).unwrap().as_slice()).unwrap();
+        let types =
+            |png: &Png| -> Vec<String> { png.iter().map(|c| c.chunk_type().to_string()).collect() };
+
+        assert!(merge(
+            merge_args(&["ruSt", "gAMA"], false),
+            &ParseOptions::default()
+        )
+        .is_ok());
+        assert_eq!(
+            types(&merged()),
+            ["IHDR", "gAMA", "ruSt", "ruSt", "gAMA", "IEND"]
+        );
+
+        assert!(merge(merge_args(&["gAMA"], true), &ParseOptions::default()).is_ok());
+        let png = merged();
+        assert_eq!(types(&png), ["IHDR", "gAMA", "IEND"]);
+        assert_eq!(png.chunk_by_type("gAMA").unwrap().data(), b"src");
+
+        assert!(merge(merge_args(&["NoNe"], false), &ParseOptions::default()).is_err());
+        assert_eq!(
+            types(&Png::try_from(fs::read(&target).unwrap().as_slice()).unwrap()),
+            ["IHDR", "gAMA", "IEND"]
+        );
+    }
+
+    #[test]
+    fn test_diff_chunks() {
+        let chunk = |chunk_type: &str, data: &str| {
+            Chunk::new(
+                ChunkType::from_str(chunk_type).unwrap(),
+                data.as_bytes().to_vec(),
+            )
+        };
+        let old = [
+            chunk("IHDR", ""),
+            chunk("tEXt", "old"),
+            chunk("IDAT", "pixels"),
+            chunk("zTXt", "gone"),
+            chunk("IEND", ""),
+        ];
+        let new = [
+            chunk("IHDR", ""),
+            chunk("tEXt", "new!"),
+            chunk("IDAT", "pixels"),
+            chunk("ruSt", "added"),
+            chunk("IEND", ""),
+        ];
+
+        assert!(diff_chunks(&old, &old).is_empty());
+        assert_eq!(
+            diff_chunks(&old, &new),
+            vec![
+                ChunkChange::Modified(1, &old[1], 1, &new[1]),
+                ChunkChange::Removed(3, &old[3]),
+                ChunkChange::Added(3, &new[3]),
+            ]
+        );
+        assert_eq!(
+            diff_chunks(&old[..1], &old[..3]),
+            vec![
+                ChunkChange::Added(1, &old[1]),
+                ChunkChange::Added(2, &old[2]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_chunks_reordered() {
+        let chunk = |chunk_type: &str| Chunk::new(ChunkType::from_str(chunk_type).unwrap(), vec![]);
+        let old = [
+            chunk("IHDR"),
+            chunk("tEXt"),
+            chunk("zTXt"),
+            chunk("iTXt"),
+            chunk("IEND"),
+        ];
+        let new = [
+            chunk("IHDR"),
+            chunk("iTXt"),
+            chunk("tEXt"),
+            chunk("ruSt"),
+            chunk("IEND"),
+        ];
+
+        // Of the two equally long alignments, the one removing first is kept.
+        assert_eq!(
+            diff_chunks(&old, &new),
+            vec![
+                ChunkChange::Removed(1, &old[1]),
+                ChunkChange::Removed(2, &old[2]),
+                ChunkChange::Added(2, &new[2]),
+                ChunkChange::Added(3, &new[3]),
+            ]
+        );
+        assert_eq!(diff_chunks(&[], &new).len(), new.len());
+        assert_eq!(diff_chunks(&old, &[]).len(), old.len());
+    }
+
+    #[test]
+    fn test_diff() {
+        let original = std::env::temp_dir().join("pngme_test_diff_original.png");
+        let modified = std::env::temp_dir().join("pngme_test_diff_modified.png");
+        let mut png = Png::from_chunks(
+            ["IHDR", "IDAT", "IEND"]
+                .iter()
+                .map(|chunk_type| Chunk::new(ChunkType::from_str(chunk_type).unwrap(), vec![]))
+                .collect(),
+        );
+        fs::write(&original, png.as_bytes()).unwrap();
+        fs::write(&modified, png.as_bytes()).unwrap();
+
+        let args = DiffArgs {
+            original: original.clone(),
+            modified: modified.clone(),
+        };
+        assert_eq!(
+            diff(args, &ParseOptions::default()).unwrap(),
+            ExitCode::SUCCESS
+        );
+
+        png.insert_chunk_before(
+            "IEND",
+            Chunk::new(ChunkType::from_str("ruSt").unwrap(), b"hi".to_vec()),
+        )
+        .unwrap();
+        fs::write(&modified, png.as_bytes()).unwrap();
+
+        let args = DiffArgs { original, modified };
+        assert_eq!(
+            diff(args, &ParseOptions::default()).unwrap(),
+            ExitCode::FAILURE
+        );
+    }
+
+    #[test]
+    fn test_count() {
+        let test_png = TestPng::new("count");
+        let png = Png::from_chunks(
+            ["IHDR", "tEXt", "IDAT", "tEXt", "IDAT", "IDAT", "IEND"]
+                .iter()
+                .map(|chunk_type| Chunk::new(ChunkType::from_str(chunk_type).unwrap(), vec![]))
+                .collect(),
+        );
+        let counts = chunk_type_counts(&png, None);
+        let expected = [("IDAT", 3), ("tEXt", 2), ("IEND", 1), ("IHDR", 1)];
+        assert_eq!(counts.len(), expected.len());
+        for ((chunk_type, count), (expected_type, expected_count)) in counts.iter().zip(expected) {
+            assert_eq!(chunk_type, expected_type);
+            assert_eq!(*count, expected_count);
+        }
+
+        let args = CountArgs {
+            file_path: test_png.path(),
+            total: true,
+            filter: None,
+            histogram: true,
+        };
+        assert!(count(args, &ParseOptions::default()).is_ok());
+    }
+
+    #[test]
+    fn test_is_pattern() {
+        assert!(is_pattern(Path::new("images/*.png")));
+        assert!(is_pattern(Path::new("image?.png")));
+        assert!(is_pattern(Path::new("image[12].png")));
+        assert!(!is_pattern(Path::new("images/test.png")));
+    }
+
+    #[test]
+    fn test_expand_pattern() {
+        let dir = std::env::temp_dir().join("pngme_test_expand_pattern");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("sub").join("deeper")).unwrap();
+        for file in [
+            "b.png",
+            "a.png",
+            "notes.txt",
+            "sub/c.png",
+            "sub/deeper/d.png",
+        ] {
+            fs::write(dir.join(file), b"").unwrap();
+        }
+
+        let found = expand_pattern(&dir.join("*.png")).unwrap();
+        assert_eq!(found, [dir.join("a.png"), dir.join("b.png")]);
+
+        let found = expand_pattern(&dir.join("**").join("*.png")).unwrap();
+        assert_eq!(
+            found,
+            [
+                dir.join("a.png"),
+                dir.join("b.png"),
+                dir.join("sub").join("c.png"),
+                dir.join("sub").join("deeper").join("d.png"),
+            ]
+        );
+
+        let err = expand_pattern(&dir.join("*.jpg")).unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::Command(CommandError::NoFilesMatch(_))
+        ));
+        assert!(matches!(
+            expand_pattern(&dir.join("***.png")).unwrap_err(),
+            crate::Error::Pattern(_)
+        ));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_count_filter() {
+        let png = Png::from_chunks(
+            ["IHDR", "tEXt", "IDAT", "ruSt", "iTXt", "IEND"]
+                .iter()
+                .map(|chunk_type| Chunk::new(ChunkType::from_str(chunk_type).unwrap(), vec![]))
+                .collect(),
+        );
+        let private = Regex::new("^.[a-z]").unwrap();
+        let counts = chunk_type_counts(&png, Some(&private));
+        assert_eq!(counts, vec![(String::from("ruSt"), 1)]);
+
+        let lowercase_start = Regex::new("^[a-z]").unwrap();
+        assert_eq!(chunk_type_counts(&png, Some(&lowercase_start)).len(), 3);
+    }
+
+    #[test]
+    fn test_length_histogram() {
+        let chunks: Vec<Chunk> = [0, 5, 6, 7, 100, 100]
+            .iter()
+            .map(|&len| Chunk::new(ChunkType::from_str("IDAT").unwrap(), vec![0; len]))
+            .collect();
+        let chart = length_histogram(chunks.iter());
+        let lines: Vec<String> = chart
+            .lines()
+            .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                "Data length Chunks",
+                "0 1 ##############",
+                "1 0",
+                "2-3 0",
+                "4-7 3 ########################################",
+                "8-15 0",
+                "16-31 0",
+                "32-63 0",
+                "64-127 2 ###########################",
+            ]
+        );
+
+        assert_eq!(length_histogram(std::iter::empty()), "No chunks\n");
+    }
+
+    #[test]
+    fn test_invalid_filter_is_reported_before_reading() {
+        let args = CountArgs {
+            file_path: PathBuf::from("does-not-exist.png"),
+            total: false,
+            filter: Some(String::from("[a-")),
+            histogram: false,
+        };
+        let err = count(args, &ParseOptions::default()).unwrap_err();
+        assert!(matches!(err, crate::Error::Regex(_)));
+    }
+
+    #[test]
+    fn test_print_hex() {
+        let test_png = TestPng::new("print_hex");
+        let args = PrintArgs {
+            file_path: test_png.path(),
+            no_verify: false,
+            hex: true,
+            max_bytes: Some(32),
+            format: OutputFormat::Text,
+            ancillary_only: true,
+            critical_only: false,
+            detailed: false,
+            ascii_only: false,
+            watch: false,
+            output_file: None,
+        };
+        assert!(print(args, &ParseOptions::default()).is_ok());
+    }
+}
@@ -0,0 +1,84 @@
+mod args;
+mod color;
+mod commands;
+mod progress;
+mod stats;
+mod watch;
+
+use args::Commands;
+use clap::ColorChoice;
+use clap::Parser;
+use commands::CommandError;
+use pngme::chunk::ParseOptions;
+use pngme::png::PngDecodeError;
+use std::env;
+use std::io::{self, IsTerminal};
+use std::process::ExitCode;
+use std::time::Instant;
+
+/// Everything a command can fail with: the library's errors, plus the ones
+/// only the command line runs into.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Png(#[from] pngme::Error),
+    #[error(transparent)]
+    Command(#[from] CommandError),
+    #[error(transparent)]
+    Regex(#[from] regex::Error),
+    #[error(transparent)]
+    Pattern(#[from] glob::PatternError),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+// Commands raise these directly, so they convert without a detour through
+// `pngme::Error` at every call site.
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Png(e.into())
+    }
+}
+
+impl From<PngDecodeError> for Error {
+    fn from(e: PngDecodeError) -> Self {
+        Error::Png(e.into())
+    }
+}
+
+fn main() -> ExitCode {
+    let args = Commands::parse();
+    let level = match args.verbose {
+        0 => "warn",
+        1 => "info",
+        2 => "debug",
+        _ => "trace",
+    };
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(level)).init();
+    progress::set_enabled(io::stderr().is_terminal());
+    color::set_enabled(match args.color {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+        }
+    });
+    stats::set_enabled(args.stats);
+    commands::set_self_check(args.self_check);
+    let start = Instant::now();
+    let mut options = ParseOptions::default().with_crc_algorithm(args.crc_algo.into());
+    if let Some(limit) = args.max_chunk_size {
+        options = options.with_max_chunk_size(limit);
+    }
+    let code = match commands::run(args.command, &options) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            ExitCode::FAILURE
+        }
+    };
+    if args.stats {
+        eprint!("{}", stats::report(start.elapsed()));
+    }
+    code
+}
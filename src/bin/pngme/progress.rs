@@ -0,0 +1,158 @@
+//! A byte-driven progress bar on stderr for reading and writing large files.
+//! Off unless `main` turns it on, which it does only when stderr is a terminal,
+//! so piped output and tests never see it.
+
+use std::io::{self, Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Files smaller than this finish too quickly for a bar to be worth drawing.
+const MINIMUM_TOTAL: u64 = 16 * 1024 * 1024;
+
+const BAR_WIDTH: u64 = 30;
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub struct Progress {
+    label: &'static str,
+    total: u64,
+    done: u64,
+    shown_percent: Option<u64>,
+    enabled: bool,
+}
+
+impl Progress {
+    pub fn new(label: &'static str, total: u64) -> Progress {
+        Progress {
+            label,
+            total,
+            done: 0,
+            shown_percent: None,
+            enabled: ENABLED.load(Ordering::Relaxed) && total >= MINIMUM_TOTAL,
+        }
+    }
+
+    /// Records `bytes` more bytes as processed, redrawing only when the
+    /// whole percentage changes.
+    pub fn advance(&mut self, bytes: u64) {
+        self.done = self.done.saturating_add(bytes).min(self.total);
+        if !self.enabled {
+            return;
+        }
+
+        let percent = self.done * 100 / self.total;
+        if self.shown_percent != Some(percent) {
+            self.shown_percent = Some(percent);
+            let mut stderr = io::stderr().lock();
+            let _ = write!(stderr, "\r{}", render(self.label, self.done, self.total));
+            let _ = stderr.flush();
+        }
+    }
+}
+
+impl Drop for Progress {
+    /// Clears the bar so later output starts on a clean line.
+    fn drop(&mut self) {
+        if self.enabled && self.shown_percent.is_some() {
+            eprint!("\r\x1b[2K");
+        }
+    }
+}
+
+/// Wraps a reader or writer, advancing a progress bar by every byte that
+/// passes through it.
+pub struct Tracked<T> {
+    inner: T,
+    progress: Progress,
+}
+
+impl<T> Tracked<T> {
+    pub fn new(inner: T, label: &'static str, total: u64) -> Tracked<T> {
+        Tracked {
+            inner,
+            progress: Progress::new(label, total),
+        }
+    }
+}
+
+impl<R: Read> Read for Tracked<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.progress.advance(read as u64);
+        Ok(read)
+    }
+}
+
+impl<W: Write> Write for Tracked<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.progress.advance(written as u64);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+fn render(label: &str, done: u64, total: u64) -> String {
+    let filled = (done * BAR_WIDTH / total.max(1)) as usize;
+    format!(
+        "{label} [{}{}] {:>3}% ({} / {} MiB)",
+        "#".repeat(filled),
+        "-".repeat(BAR_WIDTH as usize - filled),
+        done * 100 / total.max(1),
+        done / (1024 * 1024),
+        total / (1024 * 1024)
+    )
+}
+
+// ----------TESTS-------------//
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let mib = 1024 * 1024;
+        assert_eq!(
+            render("Parsing", 0, 100 * mib),
+            "Parsing [------------------------------]   0% (0 / 100 MiB)"
+        );
+        assert_eq!(
+            render("Parsing", 50 * mib, 100 * mib),
+            "Parsing [###############---------------]  50% (50 / 100 MiB)"
+        );
+        assert_eq!(
+            render("Writing", 100 * mib, 100 * mib),
+            "Writing [##############################] 100% (100 / 100 MiB)"
+        );
+    }
+
+    #[test]
+    fn test_disabled_by_default() {
+        let mut progress = Progress::new("Parsing", u64::MAX);
+        assert!(!progress.enabled);
+        progress.advance(u64::MAX);
+        assert_eq!(progress.done, u64::MAX);
+        assert_eq!(progress.shown_percent, None);
+    }
+
+    #[test]
+    fn test_tracked_counts_bytes() {
+        let mut reader = Tracked::new(&b"some bytes"[..], "Reading", 10);
+        let mut read = Vec::new();
+        reader.read_to_end(&mut read).unwrap();
+        assert_eq!(read, b"some bytes");
+        assert_eq!(reader.progress.done, 10);
+
+        let mut writer = Tracked::new(Vec::new(), "Writing", 10);
+        writer.write_all(b"more").unwrap();
+        assert_eq!(writer.inner, b"more");
+        assert_eq!(writer.progress.done, 4);
+    }
+}
@@ -0,0 +1,124 @@
+//! Timings for `--stats`: how long a command spent reading, parsing and
+//! writing files, with the rest counted as the operation itself. Off unless
+//! `main` turns it on, so nothing is recorded during normal runs or tests.
+
+use std::fmt::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static TIMINGS: Mutex<Timings> = Mutex::new(Timings::new());
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Read,
+    Parse,
+    Write,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Timings {
+    read: (Duration, u64),
+    parse: (Duration, u64),
+    write: (Duration, u64),
+}
+
+impl Timings {
+    const fn new() -> Timings {
+        Timings {
+            read: (Duration::ZERO, 0),
+            parse: (Duration::ZERO, 0),
+            write: (Duration::ZERO, 0),
+        }
+    }
+
+    fn add(&mut self, phase: Phase, elapsed: Duration, bytes: u64) {
+        let entry = match phase {
+            Phase::Read => &mut self.read,
+            Phase::Parse => &mut self.parse,
+            Phase::Write => &mut self.write,
+        };
+        entry.0 += elapsed;
+        entry.1 += bytes;
+    }
+}
+
+/// Adds `elapsed` and `bytes` to the totals for `phase`, if stats are on.
+pub fn record(phase: Phase, elapsed: Duration, bytes: u64) {
+    if ENABLED.load(Ordering::Relaxed) {
+        TIMINGS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .add(phase, elapsed, bytes);
+    }
+}
+
+/// Renders everything recorded so far against the `total` run time.
+pub fn report(total: Duration) -> String {
+    render(&TIMINGS.lock().unwrap_or_else(|e| e.into_inner()), total)
+}
+
+fn render(timings: &Timings, total: Duration) -> String {
+    let operation = total.saturating_sub(timings.read.0 + timings.parse.0 + timings.write.0);
+    let ms = |duration: Duration| duration.as_secs_f64() * 1000.0;
+
+    let mut report = String::new();
+    for (label, (elapsed, bytes)) in [
+        ("Read", timings.read),
+        ("Parse", timings.parse),
+        ("Operation", (operation, 0)),
+        ("Write", timings.write),
+    ] {
+        let _ = write!(report, "{label:<10} {:>10.3} ms", ms(elapsed));
+        if bytes > 0 {
+            let _ = write!(report, "  {bytes} bytes");
+        }
+        report.push('\n');
+    }
+    let _ = writeln!(
+        report,
+        "{:<10} {:>10.3} ms  {} bytes processed",
+        "Total",
+        ms(total),
+        timings.read.1 + timings.write.1
+    );
+    report
+}
+
+// ----------TESTS-------------//
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let mut timings = Timings::default();
+        timings.add(Phase::Read, Duration::from_millis(2), 1000);
+        timings.add(Phase::Parse, Duration::from_millis(1), 1000);
+        timings.add(Phase::Write, Duration::from_micros(1500), 1200);
+        timings.add(Phase::Write, Duration::from_micros(500), 100);
+
+        assert_eq!(
+            render(&timings, Duration::from_millis(10)),
+            "\
+Read            2.000 ms  1000 bytes
+Parse           1.000 ms  1000 bytes
+Operation       5.000 ms
+Write           2.000 ms  1300 bytes
+Total          10.000 ms  2300 bytes processed
+"
+        );
+    }
+
+    #[test]
+    fn test_disabled_by_default() {
+        record(Phase::Read, Duration::from_secs(1), 1);
+        assert_eq!(*TIMINGS.lock().unwrap(), Timings::new());
+    }
+}
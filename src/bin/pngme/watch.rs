@@ -0,0 +1,106 @@
+//! Watches a file for changes, for `print --watch`. Editors and image tools
+//! often save in several quick writes, so a change only counts once the file
+//! has stopped changing for a moment.
+
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use std::ffi::OsString;
+use std::io;
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::Duration;
+
+/// How long the file must go without changes before a change is reported.
+pub const QUIET_PERIOD: Duration = Duration::from_millis(200);
+
+pub struct FileWatcher {
+    // Dropping the watcher stops the events.
+    _watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<Event>>,
+    file_name: OsString,
+}
+
+impl FileWatcher {
+    /// Starts watching `path`. Its directory is watched rather than the file,
+    /// so a file that is replaced instead of rewritten is still followed.
+    pub fn new(path: &Path) -> crate::Result<FileWatcher> {
+        let file_name = path
+            .file_name()
+            .ok_or_else(|| io::Error::other(format!("{} is not a file", path.display())))?
+            .to_os_string();
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+
+        let (sender, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender).map_err(io::Error::other)?;
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .map_err(io::Error::other)?;
+        Ok(FileWatcher {
+            _watcher: watcher,
+            events,
+            file_name,
+        })
+    }
+
+    /// Blocks until the file changes and then holds still for `quiet`.
+    pub fn wait_for_change(&self, quiet: Duration) -> crate::Result<()> {
+        while !self.concerns_file(self.events.recv().map_err(io::Error::other)?)? {}
+        loop {
+            match self.events.recv_timeout(quiet) {
+                Ok(event) => {
+                    self.concerns_file(event)?;
+                }
+                Err(RecvTimeoutError::Timeout) => return Ok(()),
+                Err(e @ RecvTimeoutError::Disconnected) => return Err(io::Error::other(e).into()),
+            }
+        }
+    }
+
+    /// Whether `event` changed the watched file, rather than another file in
+    /// its directory or just reading it.
+    fn concerns_file(&self, event: notify::Result<Event>) -> crate::Result<bool> {
+        let event = event.map_err(io::Error::other)?;
+        Ok(!event.kind.is_access()
+            && event
+                .paths
+                .iter()
+                .any(|path| path.file_name() == Some(self.file_name.as_os_str())))
+    }
+}
+
+// ----------TESTS-------------//
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::thread;
+
+    #[test]
+    fn test_wait_for_change() {
+        let dir =
+            std::env::temp_dir().join(format!("pngme_test_wait_for_change_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("watched.png");
+        fs::write(&path, b"one").unwrap();
+        let watcher = FileWatcher::new(&path).unwrap();
+
+        let writer = {
+            let path = path.clone();
+            thread::spawn(move || {
+                // Writes to other files in the directory are ignored.
+                fs::write(path.with_file_name("other.png"), b"two").unwrap();
+                thread::sleep(Duration::from_millis(30));
+                fs::write(&path, b"three").unwrap();
+            })
+        };
+        watcher.wait_for_change(Duration::from_millis(50)).unwrap();
+        // Only returns once the watched file itself has been written.
+        assert_eq!(fs::read(&path).unwrap(), b"three");
+        writer.join().unwrap();
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,89 +1,248 @@
 #![allow(dead_code)]
 
+use crate::base64;
 use crate::chunk_type::ChunkType;
+use crate::zlib;
+use bytes::Bytes;
 use core::fmt;
-use crc::{Crc, CRC_32_ISO_HDLC};
+use crc::{
+    Crc, CRC_32_AUTOSAR, CRC_32_BZIP2, CRC_32_CKSUM, CRC_32_ISCSI, CRC_32_ISO_HDLC, CRC_32_JAMCRC,
+    CRC_32_MPEG_2, CRC_32_XFER,
+};
 use std::{
     fmt::Display,
-    io::{BufReader, Read},
+    io::{self, Read, Write},
 };
 
-const MAXIMUM_LENGTH: u32 = 2_147_483_647;
+pub const MAXIMUM_LENGTH: u32 = 2_147_483_647;
 
-#[derive(Debug)]
+/// The most chunk data `from_reader` allocates before any of it has been read.
+const READ_CHUNK_SIZE: usize = 64 * 1024;
+
+/// The 32-bit CRC variants chunks can be checksummed with. PNG mandates
+/// `IsoHdlc`; the others exist for reading and writing non-conformant files.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CrcAlgorithm {
+    #[default]
+    IsoHdlc,
+    Autosar,
+    Bzip2,
+    Cksum,
+    Iscsi,
+    Jamcrc,
+    Mpeg2,
+    Xfer,
+}
+
+static ISO_HDLC: Crc<u32> = Crc::<u32>::new(&CRC_32_ISO_HDLC);
+static AUTOSAR: Crc<u32> = Crc::<u32>::new(&CRC_32_AUTOSAR);
+static BZIP2: Crc<u32> = Crc::<u32>::new(&CRC_32_BZIP2);
+static CKSUM: Crc<u32> = Crc::<u32>::new(&CRC_32_CKSUM);
+static ISCSI: Crc<u32> = Crc::<u32>::new(&CRC_32_ISCSI);
+static JAMCRC: Crc<u32> = Crc::<u32>::new(&CRC_32_JAMCRC);
+static MPEG_2: Crc<u32> = Crc::<u32>::new(&CRC_32_MPEG_2);
+static XFER: Crc<u32> = Crc::<u32>::new(&CRC_32_XFER);
+
+impl CrcAlgorithm {
+    pub fn checksum(self, bytes: &[u8]) -> u32 {
+        self.crc().checksum(bytes)
+    }
+
+    /// Checksums `parts` as if they were one buffer, without joining them.
+    pub fn checksum_parts(self, parts: &[&[u8]]) -> u32 {
+        let mut digest = self.crc().digest();
+        for part in parts {
+            digest.update(part);
+        }
+        digest.finalize()
+    }
+
+    fn crc(self) -> &'static Crc<u32> {
+        match self {
+            CrcAlgorithm::IsoHdlc => &ISO_HDLC,
+            CrcAlgorithm::Autosar => &AUTOSAR,
+            CrcAlgorithm::Bzip2 => &BZIP2,
+            CrcAlgorithm::Cksum => &CKSUM,
+            CrcAlgorithm::Iscsi => &ISCSI,
+            CrcAlgorithm::Jamcrc => &JAMCRC,
+            CrcAlgorithm::Mpeg2 => &MPEG_2,
+            CrcAlgorithm::Xfer => &XFER,
+        }
+    }
+}
+
+/// How chunks are read: the CRC variant they are checked with and the most
+/// data one may hold. The default is what the PNG spec requires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseOptions {
+    crc_algorithm: CrcAlgorithm,
+    max_chunk_size: u32,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            crc_algorithm: CrcAlgorithm::default(),
+            max_chunk_size: MAXIMUM_LENGTH,
+        }
+    }
+}
+
+impl ParseOptions {
+    /// Checks CRCs with `algorithm` instead of ISO-HDLC.
+    pub fn with_crc_algorithm(mut self, algorithm: CrcAlgorithm) -> Self {
+        self.crc_algorithm = algorithm;
+        self
+    }
+
+    /// Rejects chunks with more than `limit` bytes of data, as a policy for
+    /// untrusted input. Limits above `MAXIMUM_LENGTH` are capped to it.
+    pub fn with_max_chunk_size(mut self, limit: u32) -> Self {
+        self.max_chunk_size = limit.min(MAXIMUM_LENGTH);
+        self
+    }
+
+    pub fn crc_algorithm(&self) -> CrcAlgorithm {
+        self.crc_algorithm
+    }
+
+    pub fn max_chunk_size(&self) -> u32 {
+        self.max_chunk_size
+    }
+}
+
+/// A single PNG chunk. Its data is reference-counted, so chunks parsed out of
+/// a file share that file's buffer and cloning one doesn't copy its data.
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Chunk {
     length: u32,
     chunk_type: ChunkType,
-    chunk_data: Vec<u8>,
+    chunk_data: Bytes,
     crc: u32,
 }
 
-// TODO: Refactor this mess
 impl TryFrom<&[u8]> for Chunk {
     type Error = crate::Error;
     fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
-        //Creates a new BuffReader that reads into the buffer
-        let mut reader = BufReader::new(bytes);
-        let mut buffer: [u8; 4] = [0; 4];
-
-        // length will always be u32 (u8 * 4 == u32)
-        reader.read_exact(&mut buffer)?;
-        let length = u32::from_be_bytes(buffer);
-
-        if length > MAXIMUM_LENGTH {
-            return Err(ChunkError::InvalidLengthGT(length).into());
+        let (chunk, consumed) = Chunk::try_from_prefix(bytes)?;
+        if consumed < bytes.len() {
+            return Err(ChunkError::TrailingBytes(bytes.len() - consumed).into());
         }
+        Ok(chunk)
+    }
+}
 
-        // read in length from buffer
-        reader.read_exact(&mut buffer)?;
-        let chunk_type = ChunkType::try_from(buffer)?;
-
-        //establish a vector the size of length, then read the chunk data into it
-        let mut chunk_data = vec![0; usize::try_from(length)?];
-        reader.read_exact(&mut chunk_data)?;
+impl TryFrom<Vec<u8>> for Chunk {
+    type Error = crate::Error;
+    fn try_from(bytes: Vec<u8>) -> Result<Self, Self::Error> {
+        Chunk::try_from(bytes.as_slice())
+    }
+}
 
-        //chunk_data's length should be the same as length
-        if chunk_data.len() != length.try_into()? {
-            return Err(ChunkError::InvalidLengthCmp(chunk_data.len() as u32, length).into());
+/// Replaces each byte outside printable ASCII with a `\xNN` escape, like
+/// `cat -v`. Newlines and tabs are kept so text keeps its layout.
+pub fn escape_non_printable(bytes: &[u8]) -> String {
+    let mut escaped = String::with_capacity(bytes.len());
+    for &byte in bytes {
+        match byte {
+            0x20..=0x7e | b'\n' | b'\t' => escaped.push(char::from(byte)),
+            _ => escaped.push_str(&format!("\\x{byte:02x}")),
         }
-
-        // read in crc and test it agains our correct crc
-        reader.read_exact(&mut buffer)?;
-        let tried_crc = u32::from_be_bytes(buffer);
-        let real_crc: u32 =
-            Self::gen_u32_crc(&[&chunk_type.bytes(), chunk_data.as_slice()].concat());
-        if tried_crc != real_crc {
-            return Err(ChunkError::InvalidCrc(real_crc, tried_crc).into());
-        }
-
-        Ok(Chunk::new_with_all_fields(
-            length, chunk_type, chunk_data, real_crc,
-        ))
     }
+    escaped
 }
 
 impl Display for Chunk {
+    /// `type\tdata` on one line. The alternate form (`{:#}`) instead gives one
+    /// labeled line per field, summarizing binary data rather than printing it.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "{}\t{}",
-            self.chunk_type(),
-            self.data_as_string()
-                .unwrap_or_else(|_| "[data]".to_string())
-        )
+        if !f.alternate() {
+            return write!(f, "{}\t{}", self.chunk_type(), self.data_as_string_lossy());
+        }
+
+        writeln!(f, "Type:   {}", self.chunk_type())?;
+        writeln!(f, "Length: {}", self.length())?;
+        writeln!(f, "CRC:    {:#010x}", self.crc())?;
+        match std::str::from_utf8(&self.chunk_data) {
+            Ok(text)
+                if !text
+                    .chars()
+                    .any(|c| c.is_control() && !matches!(c, '\n' | '\r' | '\t')) =>
+            {
+                write!(f, "Data:   {text}")
+            }
+            _ => write!(f, "Data:   [{} bytes binary]", self.chunk_data.len()),
+        }
     }
 }
 
 impl Chunk {
+    /// Builds a chunk from its type and data. The length and CRC are derived
+    /// from the data, so the resulting chunk is always consistent.
     pub fn new(chunk_type: ChunkType, chunk_data: Vec<u8>) -> Chunk {
         Chunk {
             length: chunk_data.len() as u32,
             chunk_type,
-            chunk_data: chunk_data.clone(),
-            crc: Self::gen_u32_crc(&[&chunk_type.bytes(), chunk_data.as_slice()].concat()),
+            crc: CrcAlgorithm::default().checksum_parts(&[&chunk_type.bytes(), &chunk_data]),
+            chunk_data: chunk_data.into(),
+        }
+    }
+
+    /// Spreads `data` over as many chunks of `chunk_type` as it takes to keep
+    /// each one at most `max_size` bytes (and at most `MAXIMUM_LENGTH`), in
+    /// order. Only use it for types that may appear more than once, such as
+    /// private ancillary chunks. Empty data still gives one empty chunk.
+    pub fn split(chunk_type: ChunkType, data: &[u8], max_size: usize) -> Vec<Chunk> {
+        if data.is_empty() {
+            return vec![Chunk::new(chunk_type, Vec::new())];
+        }
+        let max_size = max_size.clamp(1, MAXIMUM_LENGTH as usize);
+        data.chunks(max_size)
+            .map(|part| Chunk::new(chunk_type, part.to_vec()))
+            .collect()
+    }
+
+    /// Reassembles data fragmented by `split` by concatenating the chunks'
+    /// data in the order given, which should be their order in the file.
+    pub fn join<'a>(chunks: impl IntoIterator<Item = &'a Chunk>) -> Vec<u8> {
+        chunks
+            .into_iter()
+            .flat_map(|chunk| chunk.data().iter().copied())
+            .collect()
+    }
+
+    /// Parses a whole chunk (length, type, data and CRC) written as hex, like
+    /// `0000000049454e44ae426082`. Whitespace between digits is ignored, so
+    /// spaced-out hex dumps can be pasted as they are.
+    pub fn from_hex(hex: &str) -> crate::Result<Chunk> {
+        let digits: Vec<char> = hex.chars().filter(|c| !c.is_ascii_whitespace()).collect();
+        if !digits.len().is_multiple_of(2) {
+            return Err(ChunkError::OddHexLength(digits.len()).into());
         }
+        let bytes = digits
+            .chunks(2)
+            .map(|pair| {
+                let digit = |c: char| c.to_digit(16).ok_or(ChunkError::InvalidHexDigit(c));
+                Ok((digit(pair[0])? << 4 | digit(pair[1])?) as u8)
+            })
+            .collect::<Result<Vec<u8>, ChunkError>>()?;
+        Chunk::try_from(bytes)
     }
 
+    /// Builds a chunk whose data is `data` zlib-compressed. The length and CRC
+    /// cover the compressed bytes, as they are stored on disk.
+    pub fn new_compressed(chunk_type: ChunkType, data: &[u8]) -> crate::Result<Chunk> {
+        let compressed = zlib::compress(data);
+        if compressed.len() > MAXIMUM_LENGTH as usize {
+            return Err(ChunkError::InvalidLengthGT(compressed.len() as u32).into());
+        }
+
+        Ok(Chunk::new(chunk_type, compressed))
+    }
+
+    /// Builds a chunk from raw fields exactly as given. Neither `length` nor `crc`
+    /// are checked against `chunk_data`; use `verify_crc` to audit the result or
+    /// `recompute_crc` to fix it.
     pub fn new_with_all_fields(
         length: u32,
         chunk_type: ChunkType,
@@ -93,26 +252,269 @@ impl Chunk {
         Chunk {
             length,
             chunk_type,
-            chunk_data,
+            chunk_data: chunk_data.into(),
             crc,
         }
     }
 
+    /// Parses the chunk at the start of `bytes`, returning it along with the
+    /// number of bytes it took up. Anything after the CRC is left untouched.
+    pub fn try_from_prefix(bytes: &[u8]) -> crate::Result<(Chunk, usize)> {
+        Chunk::try_from_prefix_with(bytes, &ParseOptions::default())
+    }
+
+    /// Like `try_from_prefix`, but reads the chunk as `options` say.
+    pub fn try_from_prefix_with(
+        bytes: &[u8],
+        options: &ParseOptions,
+    ) -> crate::Result<(Chunk, usize)> {
+        // length, type and CRC take up 12 bytes even when there is no data
+        if bytes.len() < 12 {
+            return Err(ChunkError::ChunkTooSmall(bytes.len() as u32).into());
+        }
+
+        // Catch lengths the input can't possibly hold before reading anything.
+        let length = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        if length <= MAXIMUM_LENGTH && length as usize > bytes.len() - 12 {
+            return Err(ChunkError::LengthExceedsInput(length, bytes.len() - 12).into());
+        }
+
+        let mut reader = bytes;
+        let chunk = Chunk::from_reader_with(&mut reader, options)?;
+        Ok((chunk, bytes.len() - reader.len()))
+    }
+
+    /// Reads a single chunk directly off `reader` without buffering anything
+    /// beyond the chunk itself. The CRC is validated as the chunk is read.
+    ///
+    /// If the reader is exhausted before any byte of the length field is read,
+    /// `ChunkError::EndOfStream` is returned so callers can loop until done.
+    pub fn from_reader<R: Read>(reader: &mut R) -> crate::Result<Chunk> {
+        Chunk::from_reader_with(reader, &ParseOptions::default())
+    }
+
+    /// Like `from_reader`, but reads the chunk as `options` say.
+    pub fn from_reader_with<R: Read>(
+        reader: &mut R,
+        options: &ParseOptions,
+    ) -> crate::Result<Chunk> {
+        Chunk::read_unchecked(reader, options.max_chunk_size())?.checked(options.crc_algorithm())
+    }
+
+    /// Parses the chunk at the start of `buffer` the way `try_from_prefix_with`
+    /// does, but shares its data with `buffer` instead of copying it.
+    pub(crate) fn from_shared_prefix(
+        buffer: &Bytes,
+        options: &ParseOptions,
+    ) -> crate::Result<(Chunk, usize)> {
+        if buffer.len() < 12 {
+            return Err(ChunkError::ChunkTooSmall(buffer.len() as u32).into());
+        }
+        let length = u32::from_be_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]);
+        if length <= MAXIMUM_LENGTH && length as usize > buffer.len() - 12 {
+            return Err(ChunkError::LengthExceedsInput(length, buffer.len() - 12).into());
+        }
+
+        let (chunk, consumed) = Chunk::read_shared(buffer, options.max_chunk_size())?;
+        Ok((chunk.checked(options.crc_algorithm())?, consumed))
+    }
+
+    /// Like `from_reader_unchecked_with`, but for the chunk at the start of
+    /// `buffer`, sharing its data instead of copying it. Returns the chunk and
+    /// the number of bytes it took up.
+    pub(crate) fn from_shared_prefix_unchecked(
+        buffer: &Bytes,
+        options: &ParseOptions,
+    ) -> crate::Result<(Chunk, usize)> {
+        Chunk::read_shared(buffer, options.max_chunk_size())
+    }
+
+    /// Fails with `InvalidCrc` unless the stored CRC matches under `algorithm`.
+    fn checked(self, algorithm: CrcAlgorithm) -> crate::Result<Chunk> {
+        let real_crc = self.compute_crc(algorithm);
+        if self.crc != real_crc {
+            return Err(ChunkError::InvalidCrc(real_crc, self.crc).into());
+        }
+        Ok(self)
+    }
+
+    /// Like `from_reader`, but keeps the stored CRC without checking it, so
+    /// corrupted chunks can still be inspected. See `verify_crc`.
+    pub fn from_reader_unchecked<R: Read>(reader: &mut R) -> crate::Result<Chunk> {
+        Chunk::from_reader_unchecked_with(reader, &ParseOptions::default())
+    }
+
+    /// Like `from_reader_unchecked`, but still holds the chunk to the size
+    /// limit in `options`.
+    pub fn from_reader_unchecked_with<R: Read>(
+        reader: &mut R,
+        options: &ParseOptions,
+    ) -> crate::Result<Chunk> {
+        Chunk::read_unchecked(reader, options.max_chunk_size())
+    }
+
+    /// Reads a chunk without checking its CRC, rejecting data longer than
+    /// `max_chunk_size`.
+    fn read_unchecked<R: Read>(reader: &mut R, max_chunk_size: u32) -> crate::Result<Chunk> {
+        let mut buffer: [u8; 4] = [0; 4];
+
+        // length will always be u32 (u8 * 4 == u32)
+        let mut filled = 0;
+        while filled < buffer.len() {
+            match reader.read(&mut buffer[filled..]) {
+                Ok(0) if filled == 0 => return Err(ChunkError::EndOfStream.into()),
+                Ok(0) => return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        let length = u32::from_be_bytes(buffer);
+
+        if length > MAXIMUM_LENGTH {
+            return Err(ChunkError::InvalidLengthGT(length).into());
+        }
+        Chunk::check_size_limit(length, max_chunk_size)?;
+
+        reader.read_exact(&mut buffer)?;
+        let chunk_type = ChunkType::try_from(buffer)?;
+
+        // Grow the buffer as data actually arrives, so a forged length can't
+        // make us allocate gigabytes up front.
+        let mut chunk_data = Vec::with_capacity((length as usize).min(READ_CHUNK_SIZE));
+        reader
+            .by_ref()
+            .take(u64::from(length))
+            .read_to_end(&mut chunk_data)?;
+        if chunk_data.len() < length as usize {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+
+        reader.read_exact(&mut buffer)?;
+        let crc = u32::from_be_bytes(buffer);
+
+        Ok(Chunk::new_with_all_fields(
+            length, chunk_type, chunk_data, crc,
+        ))
+    }
+
+    /// Reads a chunk off the start of `buffer` like `read_unchecked`, failing
+    /// the same ways, but slicing its data out of `buffer`.
+    fn read_shared(buffer: &Bytes, max_chunk_size: u32) -> crate::Result<(Chunk, usize)> {
+        if buffer.is_empty() {
+            return Err(ChunkError::EndOfStream.into());
+        }
+        let field = |offset: usize| -> crate::Result<[u8; 4]> {
+            match buffer.get(offset..offset + 4) {
+                Some(field) => Ok([field[0], field[1], field[2], field[3]]),
+                None => Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+            }
+        };
+
+        let length = u32::from_be_bytes(field(0)?);
+        if length > MAXIMUM_LENGTH {
+            return Err(ChunkError::InvalidLengthGT(length).into());
+        }
+        Chunk::check_size_limit(length, max_chunk_size)?;
+        let chunk_type = ChunkType::try_from(field(4)?)?;
+
+        let end = 8 + length as usize;
+        if buffer.len() < end {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        let crc = u32::from_be_bytes(field(end)?);
+
+        let chunk = Chunk {
+            length,
+            chunk_type,
+            chunk_data: buffer.slice(8..end),
+            crc,
+        };
+        Ok((chunk, end + 4))
+    }
+
+    /// Fails with `ExceedsSizeLimit` when `length` is over the configured `limit`.
+    fn check_size_limit(length: u32, limit: u32) -> crate::Result<()> {
+        if length > limit {
+            return Err(ChunkError::ExceedsSizeLimit(length, limit).into());
+        }
+        Ok(())
+    }
+
+    /// Checksums `bytes` with ISO-HDLC, the algorithm PNG requires.
     pub fn gen_u32_crc(bytes: &[u8]) -> u32 {
-        const ALGO: Crc<u32> = Crc::<u32>::new(&CRC_32_ISO_HDLC);
-        Crc::<u32>::checksum(&ALGO, bytes)
+        Self::gen_u32_crc_with(bytes, CrcAlgorithm::default())
+    }
+
+    pub fn gen_u32_crc_with(bytes: &[u8], algorithm: CrcAlgorithm) -> u32 {
+        algorithm.checksum(bytes)
+    }
+
+    fn compute_crc(&self, algorithm: CrcAlgorithm) -> u32 {
+        algorithm.checksum_parts(&[&self.chunk_type.bytes(), self.data()])
+    }
+
+    /// Returns whether the stored CRC matches the one computed over the chunk
+    /// type and data.
+    pub fn verify_crc(&self) -> bool {
+        self.verify_crc_with(CrcAlgorithm::default())
+    }
+
+    pub fn verify_crc_with(&self, algorithm: CrcAlgorithm) -> bool {
+        self.crc == self.compute_crc(algorithm)
+    }
+
+    /// Replaces the stored CRC with the one computed over the chunk type and data.
+    pub fn recompute_crc(&mut self) {
+        self.recompute_crc_with(CrcAlgorithm::default());
+    }
+
+    pub fn recompute_crc_with(&mut self, algorithm: CrcAlgorithm) {
+        self.crc = self.compute_crc(algorithm);
+    }
+
+    /// Replaces the chunk data, updating the length and CRC to match.
+    pub fn set_data(&mut self, data: Vec<u8>) -> crate::Result<()> {
+        if data.len() > MAXIMUM_LENGTH as usize {
+            return Err(ChunkError::InvalidLengthGT(data.len() as u32).into());
+        }
+
+        self.length = data.len() as u32;
+        self.chunk_data = data.into();
+        self.recompute_crc();
+        Ok(())
     }
 
+    /// The stored length field, which `new_with_all_fields` does not check
+    /// against the data. See `data_len` for the actual size.
     pub fn length(&self) -> u32 {
         self.length
     }
 
+    /// The number of data bytes the chunk actually holds.
+    pub fn data_len(&self) -> usize {
+        self.chunk_data.len()
+    }
+
+    /// Returns whether the stored length field agrees with the data.
+    pub fn length_matches_data(&self) -> bool {
+        self.length as usize == self.data_len()
+    }
+
     pub fn chunk_type(&self) -> &ChunkType {
         &self.chunk_type
     }
 
+    pub fn is_critical(&self) -> bool {
+        self.chunk_type.is_critical()
+    }
+
+    pub fn is_ancillary(&self) -> bool {
+        !self.chunk_type.is_critical()
+    }
+
     pub fn data(&self) -> &[u8] {
-        self.chunk_data.as_slice()
+        &self.chunk_data
     }
 
     pub fn crc(&self) -> u32 {
@@ -120,18 +522,82 @@ impl Chunk {
     }
 
     pub fn data_as_string(&self) -> crate::Result<String> {
-        Ok(String::from_utf8(self.chunk_data.clone()).map_err(Box::new)?)
+        Ok(String::from_utf8(self.chunk_data.to_vec())?)
+    }
+
+    /// The chunk data as standard-alphabet base64.
+    pub fn data_as_base64(&self) -> String {
+        base64::encode(&self.chunk_data)
+    }
+
+    /// Inflates chunk data that was zlib-compressed, e.g. by `new_compressed`.
+    pub fn decompressed_data(&self) -> crate::Result<Vec<u8>> {
+        zlib::decompress(&self.chunk_data)
+    }
+
+    /// Like `data_as_string`, but replaces invalid UTF-8 sequences with `U+FFFD`
+    /// instead of failing.
+    pub fn data_as_string_lossy(&self) -> String {
+        String::from_utf8_lossy(&self.chunk_data).into_owned()
+    }
+
+    /// Like `data_as_string_lossy`, but with every byte outside printable ASCII
+    /// escaped, so binary data can't garble a terminal.
+    pub fn data_as_ascii(&self) -> String {
+        escape_non_printable(&self.chunk_data)
+    }
+
+    /// Renders the chunk data as an offset/hex/ASCII dump, 16 bytes per line.
+    pub fn hex_dump(&self) -> String {
+        self.hex_dump_limited(self.chunk_data.len())
+    }
+
+    /// Like `hex_dump`, but stops after `max_bytes` bytes of data and notes how
+    /// many were left out.
+    pub fn hex_dump_limited(&self, max_bytes: usize) -> String {
+        let shown = &self.chunk_data[..max_bytes.min(self.chunk_data.len())];
+        let mut dump = String::new();
+
+        for (line, bytes) in shown.chunks(16).enumerate() {
+            let hex: Vec<String> = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+            let ascii: String = bytes
+                .iter()
+                .map(|&byte| match byte {
+                    0x20..=0x7e => char::from(byte),
+                    _ => '.',
+                })
+                .collect();
+            dump.push_str(&format!(
+                "{:08x}  {:<47}  |{}|\n",
+                line * 16,
+                hex.join(" "),
+                ascii
+            ));
+        }
+
+        let hidden = self.chunk_data.len() - shown.len();
+        if hidden > 0 {
+            dump.push_str(&format!("... {hidden} more bytes\n"));
+        }
+
+        dump
+    }
+
+    /// Writes the length, type, data and CRC straight to `writer`, without
+    /// building the byte vector `as_bytes` returns.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> crate::Result<()> {
+        writer.write_all(&self.length.to_be_bytes())?;
+        writer.write_all(&self.chunk_type.bytes())?;
+        writer.write_all(&self.chunk_data)?;
+        writer.write_all(&self.crc.to_be_bytes())?;
+        Ok(())
     }
 
     pub fn as_bytes(&self) -> Vec<u8> {
-        self.length()
-            .to_be_bytes()
-            .iter()
-            .chain(self.chunk_type().bytes().iter())
-            .chain(self.data().iter())
-            .chain(self.crc.to_be_bytes().iter())
-            .copied()
-            .collect()
+        let mut bytes = Vec::with_capacity(12 + self.chunk_data.len());
+        self.write_to(&mut bytes)
+            .expect("writing to a Vec cannot fail");
+        bytes
     }
 }
 // TODO: IMPROVE ERROR HANDLING
@@ -160,6 +626,12 @@ pub enum ChunkError {
     ChunkTooSmall(u32),
     InvalidChunkType,
     InvalidCrc(u32, u32),
+    EndOfStream,
+    TrailingBytes(usize),
+    LengthExceedsInput(u32, usize),
+    ExceedsSizeLimit(u32, u32),
+    OddHexLength(usize),
+    InvalidHexDigit(char),
 }
 
 impl fmt::Display for ChunkError {
@@ -177,6 +649,25 @@ impl fmt::Display for ChunkError {
                 f,
                 "The provided CRC of {expected} does not match the expected CRC of {actual}"
             ),
+            ChunkError::EndOfStream => write!(f, "Reached the end of the stream"),
+            ChunkError::TrailingBytes(count) => {
+                write!(
+                    f,
+                    "Found {count} unexpected bytes after the end of the chunk"
+                )
+            }
+            ChunkError::LengthExceedsInput(declared, available) => write!(
+                f,
+                "Chunk declares {declared} bytes of data but only {available} bytes remain"
+            ),
+            ChunkError::ExceedsSizeLimit(length, limit) => write!(
+                f,
+                "Chunk length {length} is over the configured limit of {limit} bytes"
+            ),
+            ChunkError::OddHexLength(digits) => {
+                write!(f, "Hex string has an odd number of digits: {digits}")
+            }
+            ChunkError::InvalidHexDigit(c) => write!(f, "Invalid hex digit {c:?}"),
             ChunkError::ChunkTooSmall(bytes) => {
                 write!(f, "Chunk is smaller than 12 bytes. Actual: {bytes}")
             } // ChunkError::InvalidChunkType => write!(f, "Invalid Chunk Type"),
@@ -223,12 +714,64 @@ mod tests {
         assert_eq!(chunk.crc(), 2882656334);
     }
 
+    #[test]
+    fn test_split_and_join() {
+        let chunk_type = ChunkType::from_str("ruSt").unwrap();
+        let data: Vec<u8> = (0..10).collect();
+
+        let chunks = Chunk::split(chunk_type, &data, 4);
+        let lengths: Vec<u32> = chunks.iter().map(Chunk::length).collect();
+        assert_eq!(lengths, [4, 4, 2]);
+        assert!(chunks.iter().all(|chunk| chunk.verify_crc()));
+        assert_eq!(Chunk::join(&chunks), data);
+
+        // Fragments read back out of order reassemble out of order.
+        assert_ne!(Chunk::join(chunks.iter().rev()), data);
+
+        assert_eq!(Chunk::split(chunk_type, &data, 10).len(), 1);
+        assert_eq!(Chunk::split(chunk_type, &data, 0).len(), 10);
+        let empty = Chunk::split(chunk_type, &[], 4);
+        assert_eq!(empty.len(), 1);
+        assert_eq!(Chunk::join(&empty), Vec::<u8>::new());
+    }
+
     #[test]
     fn test_chunk_length() {
         let chunk = testing_chunk();
         assert_eq!(chunk.length(), 42);
     }
 
+    #[test]
+    fn test_chunk_data_len() {
+        let chunk = testing_chunk();
+        assert_eq!(chunk.data_len(), 42);
+        assert!(chunk.length_matches_data());
+
+        let chunk = Chunk::new_with_all_fields(
+            7,
+            ChunkType::from_str("RuSt").unwrap(),
+            b"abc".to_vec(),
+            chunk.crc(),
+        );
+        assert_eq!(chunk.length(), 7);
+        assert_eq!(chunk.data_len(), 3);
+        assert!(!chunk.length_matches_data());
+    }
+
+    #[test]
+    fn test_data_as_ascii() {
+        let chunk = Chunk::new(
+            ChunkType::from_str("RuSt").unwrap(),
+            b"line\tone\n\x1b[31mred\x00\xff".to_vec(),
+        );
+        assert_eq!(chunk.data_as_ascii(), "line\tone\n\\x1b[31mred\\x00\\xff");
+        assert_eq!(escape_non_printable("é".as_bytes()), "\\xc3\\xa9");
+        assert_eq!(
+            testing_chunk().data_as_ascii(),
+            testing_chunk().data_as_string_lossy()
+        );
+    }
+
     #[test]
     fn test_chunk_type() {
         let chunk = testing_chunk();
@@ -276,6 +819,33 @@ mod tests {
         assert_eq!(chunk.crc(), 2882656334);
     }
 
+    #[test]
+    fn test_chunk_from_vec() {
+        let bytes = Chunk::new(ChunkType::from_str("RuSt").unwrap(), b"vec".to_vec()).as_bytes();
+        let chunk = Chunk::try_from(bytes.clone()).unwrap();
+        assert_eq!(chunk, Chunk::try_from(bytes.as_slice()).unwrap());
+        assert!(Chunk::try_from(bytes[..bytes.len() - 1].to_vec()).is_err());
+    }
+
+    #[test]
+    fn test_chunk_from_hex() {
+        let chunk = Chunk::from_hex("0000000049454e44ae426082").unwrap();
+        assert_eq!(chunk.chunk_type().to_string(), "IEND");
+        assert_eq!(chunk.length(), 0);
+        assert_eq!(
+            chunk,
+            Chunk::from_hex("00000000 49454E44\nAE426082").unwrap()
+        );
+
+        let err = Chunk::from_hex("0000000049454e44ae42608").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Hex string has an odd number of digits: 23"
+        );
+        assert!(Chunk::from_hex("0000000049454e44ae4260zz").is_err());
+        assert!(Chunk::from_hex("0000000049454e44ae426083").is_err());
+    }
+
     #[test]
     fn test_invalid_chunk_from_bytes() {
         let data_length: u32 = 42;
@@ -297,6 +867,271 @@ mod tests {
         assert!(chunk.is_err());
     }
 
+    #[test]
+    fn test_verify_and_recompute_crc() {
+        let chunk_type = ChunkType::from_str("RuSt").unwrap();
+        let data = "This is where your secret message will be!"
+            .as_bytes()
+            .to_vec();
+
+        assert!(testing_chunk().verify_crc());
+
+        let mut chunk = Chunk::new_with_all_fields(42, chunk_type, data, 1234);
+        assert!(!chunk.verify_crc());
+
+        chunk.recompute_crc();
+        assert!(chunk.verify_crc());
+        assert_eq!(chunk.crc(), 2882656334);
+    }
+
+    #[test]
+    fn test_crc_algorithms() {
+        // The standard "123456789" check values from the CRC catalogue.
+        let check = [
+            (CrcAlgorithm::IsoHdlc, 0xcbf43926),
+            (CrcAlgorithm::Bzip2, 0xfc891918),
+            (CrcAlgorithm::Iscsi, 0xe3069283),
+            (CrcAlgorithm::Mpeg2, 0x0376e6e7),
+        ];
+        for (algorithm, expected) in check {
+            assert_eq!(Chunk::gen_u32_crc_with(b"123456789", algorithm), expected);
+            assert_eq!(
+                algorithm.checksum_parts(&[b"1234", b"", b"56789"]),
+                expected
+            );
+        }
+        assert_eq!(CrcAlgorithm::default(), CrcAlgorithm::IsoHdlc);
+        assert_eq!(
+            Chunk::gen_u32_crc(b"123456789"),
+            Chunk::gen_u32_crc_with(b"123456789", CrcAlgorithm::default())
+        );
+    }
+
+    #[test]
+    fn test_verify_and_read_with_other_algorithm() {
+        let mut chunk = testing_chunk();
+        chunk.recompute_crc_with(CrcAlgorithm::Bzip2);
+        assert!(!chunk.verify_crc());
+        assert!(chunk.verify_crc_with(CrcAlgorithm::Bzip2));
+
+        let bytes = chunk.as_bytes();
+        assert!(Chunk::try_from(bytes.as_slice()).is_err());
+        let options = ParseOptions::default().with_crc_algorithm(CrcAlgorithm::Bzip2);
+        let read = Chunk::from_reader_with(&mut bytes.as_slice(), &options).unwrap();
+        assert_eq!(read, chunk);
+        let (read, _) = Chunk::try_from_prefix_with(&bytes, &options).unwrap();
+        assert_eq!(read, chunk);
+    }
+
+    #[test]
+    fn test_chunk_set_data() {
+        let mut chunk = Chunk::new(ChunkType::from_str("RuSt").unwrap(), b"old".to_vec());
+        chunk
+            .set_data(
+                "This is where your secret message will be!"
+                    .as_bytes()
+                    .to_vec(),
+            )
+            .unwrap();
+
+        assert_eq!(chunk.length(), 42);
+        assert_eq!(chunk.crc(), 2882656334);
+        assert!(chunk.verify_crc());
+    }
+
+    #[test]
+    fn test_chunk_string_lossy() {
+        let chunk = Chunk::new(ChunkType::from_str("RuSt").unwrap(), vec![72, 105, 0xff]);
+        assert!(chunk.data_as_string().is_err());
+        assert_eq!(chunk.data_as_string_lossy(), "Hi\u{fffd}");
+        assert_eq!(chunk.to_string(), "RuSt\tHi\u{fffd}");
+    }
+
+    #[test]
+    fn test_chunk_alternate_display() {
+        let chunk = Chunk::new(ChunkType::from_str("tEXt").unwrap(), b"Hello".to_vec());
+        assert_eq!(
+            format!("{chunk:#}"),
+            format!(
+                "Type:   tEXt\nLength: 5\nCRC:    {:#010x}\nData:   Hello",
+                chunk.crc()
+            )
+        );
+
+        let chunk = Chunk::new(ChunkType::from_str("IDAT").unwrap(), vec![0x78, 0x9c, 0, 1]);
+        assert!(format!("{chunk:#}").ends_with("Data:   [4 bytes binary]"));
+        assert_eq!(chunk.to_string().lines().count(), 1);
+    }
+
+    #[test]
+    fn test_chunk_hex_dump() {
+        let chunk = testing_chunk();
+        let dump = chunk.hex_dump();
+        let lines: Vec<&str> = dump.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[0],
+            "00000000  54 68 69 73 20 69 73 20 77 68 65 72 65 20 79 6f  |This is where yo|"
+        );
+        assert_eq!(
+            lines[2],
+            "00000020  65 20 77 69 6c 6c 20 62 65 21                    |e will be!|"
+        );
+
+        let limited = chunk.hex_dump_limited(16);
+        assert_eq!(limited.lines().count(), 2);
+        assert!(limited.ends_with("... 26 more bytes\n"));
+    }
+
+    #[test]
+    fn test_chunk_eq() {
+        let chunk_type = ChunkType::from_str("RuSt").unwrap();
+        let data = "This is where your secret message will be!"
+            .as_bytes()
+            .to_vec();
+
+        assert_eq!(testing_chunk(), Chunk::new(chunk_type, data.clone()));
+        assert_ne!(
+            testing_chunk(),
+            Chunk::new_with_all_fields(42, chunk_type, data, 1234)
+        );
+    }
+
+    #[test]
+    fn test_chunk_write_to() {
+        let chunk = testing_chunk();
+        let mut written = Vec::new();
+        chunk.write_to(&mut written).unwrap();
+        assert_eq!(written, chunk.as_bytes());
+    }
+
+    #[test]
+    fn test_chunk_is_critical() {
+        let chunk = testing_chunk();
+        assert!(chunk.is_critical());
+        assert!(!chunk.is_ancillary());
+
+        let chunk = Chunk::new(ChunkType::from_str("ruSt").unwrap(), vec![]);
+        assert!(!chunk.is_critical());
+        assert!(chunk.is_ancillary());
+    }
+
+    #[test]
+    fn test_chunk_data_as_base64() {
+        let chunk = testing_chunk();
+        assert_eq!(
+            chunk.data_as_base64(),
+            "VGhpcyBpcyB3aGVyZSB5b3VyIHNlY3JldCBtZXNzYWdlIHdpbGwgYmUh"
+        );
+    }
+
+    #[test]
+    fn test_chunk_compressed() {
+        let message = "This is where your secret message will be! ".repeat(10);
+        let chunk = Chunk::new_compressed(ChunkType::from_str("RuSt").unwrap(), message.as_bytes())
+            .unwrap();
+
+        assert!(chunk.length() < message.len() as u32);
+        assert!(chunk.verify_crc());
+        assert_eq!(chunk.decompressed_data().unwrap(), message.as_bytes());
+    }
+
+    #[test]
+    fn test_chunk_from_reader() {
+        let bytes: Vec<u8> = [testing_chunk().as_bytes(), testing_chunk().as_bytes()].concat();
+        let mut reader = bytes.as_slice();
+
+        let first = Chunk::from_reader(&mut reader).unwrap();
+        let second = Chunk::from_reader(&mut reader).unwrap();
+        assert_eq!(first.crc(), 2882656334);
+        assert_eq!(second.crc(), 2882656334);
+
+        let end = Chunk::from_reader(&mut reader).unwrap_err();
+        assert!(matches!(end, crate::Error::Chunk(ChunkError::EndOfStream)));
+    }
+
+    #[test]
+    fn test_chunk_from_reader_truncated() {
+        let bytes = testing_chunk().as_bytes();
+        let mut reader = &bytes[..bytes.len() - 2];
+
+        let err = Chunk::from_reader(&mut reader).unwrap_err();
+        assert!(matches!(err, crate::Error::Io(_)));
+    }
+
+    #[test]
+    fn test_chunk_from_reader_unchecked() {
+        let mut bytes = testing_chunk().as_bytes();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+
+        assert!(Chunk::from_reader(&mut bytes.as_slice()).is_err());
+
+        let chunk = Chunk::from_reader_unchecked(&mut bytes.as_slice()).unwrap();
+        assert_eq!(chunk.crc(), 2882656334 ^ 0xff);
+        assert!(!chunk.verify_crc());
+    }
+
+    #[test]
+    fn test_length_larger_than_input() {
+        // Twelve bytes that claim almost 2 GiB of data.
+        let mut bytes = MAXIMUM_LENGTH.to_be_bytes().to_vec();
+        bytes.extend_from_slice(b"RuSt");
+        bytes.extend_from_slice(&[0; 4]);
+
+        let err = Chunk::try_from(bytes.as_slice()).unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::Chunk(ChunkError::LengthExceedsInput(MAXIMUM_LENGTH, 0))
+        ));
+
+        let err = Chunk::from_reader_unchecked(&mut bytes.as_slice()).unwrap_err();
+        assert!(matches!(err, crate::Error::Io(e) if e.kind() == io::ErrorKind::UnexpectedEof));
+    }
+
+    #[test]
+    fn test_size_limit() {
+        assert_eq!(ParseOptions::default().max_chunk_size(), MAXIMUM_LENGTH);
+        assert_eq!(
+            ParseOptions::default()
+                .with_max_chunk_size(u32::MAX)
+                .max_chunk_size(),
+            MAXIMUM_LENGTH
+        );
+        assert!(Chunk::check_size_limit(1024, 1024).is_ok());
+        assert!(matches!(
+            Chunk::check_size_limit(1025, 1024).unwrap_err(),
+            crate::Error::Chunk(ChunkError::ExceedsSizeLimit(1025, 1024))
+        ));
+    }
+
+    #[test]
+    fn test_chunk_too_small() {
+        let err = Chunk::try_from(&[0, 0, 0, 0, 82][..]).unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::Chunk(ChunkError::ChunkTooSmall(5))
+        ));
+    }
+
+    #[test]
+    fn test_chunk_try_from_prefix() {
+        let mut bytes = testing_chunk().as_bytes();
+        let length = bytes.len();
+        bytes.extend_from_slice(b"trailing");
+
+        let (chunk, consumed) = Chunk::try_from_prefix(&bytes).unwrap();
+        assert_eq!(chunk, testing_chunk());
+        assert_eq!(consumed, length);
+
+        let err = Chunk::try_from(bytes.as_slice()).unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::Chunk(ChunkError::TrailingBytes(8))
+        ));
+    }
+
     #[test]
     pub fn test_chunk_trait_impls() {
         let data_length: u32 = 42;
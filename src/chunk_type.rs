@@ -6,7 +6,37 @@ use std::fmt;
 use std::fmt::Display;
 use std::str::FromStr;
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+/// The chunk types defined by the PNG specification and its registered
+/// extensions, with a short description of each.
+const KNOWN_CHUNK_TYPES: [(&[u8; 4], &str); 23] = [
+    (b"IHDR", "Image header"),
+    (b"PLTE", "Palette"),
+    (b"IDAT", "Image data"),
+    (b"IEND", "Image trailer"),
+    (b"tRNS", "Transparency"),
+    (b"cHRM", "Primary chromaticities and white point"),
+    (b"gAMA", "Image gamma"),
+    (b"iCCP", "Embedded ICC profile"),
+    (b"sBIT", "Significant bits"),
+    (b"sRGB", "Standard RGB color space"),
+    (b"cICP", "Coding-independent code points"),
+    (b"tEXt", "Textual data"),
+    (b"zTXt", "Compressed textual data"),
+    (b"iTXt", "International textual data"),
+    (b"bKGD", "Background color"),
+    (b"hIST", "Palette histogram"),
+    (b"pHYs", "Physical pixel dimensions"),
+    (b"sPLT", "Suggested palette"),
+    (b"eXIf", "Exif metadata"),
+    (b"tIME", "Last modification time"),
+    (b"acTL", "Animation control"),
+    (b"fcTL", "Frame control"),
+    (b"fdAT", "Frame data"),
+];
+
+/// Ordered lexicographically by the four bytes, so uppercase (critical,
+/// public, ...) letters sort before lowercase ones: `IDAT < IEND < tEXt`.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
 pub struct ChunkType {
     bytes: [u8; 4],
 }
@@ -17,7 +47,7 @@ impl TryFrom<[u8; 4]> for ChunkType {
     fn try_from(bytes: [u8; 4]) -> Result<Self, Self::Error> {
         for byte in bytes.iter() {
             if !ChunkType::is_valid_byte(*byte) {
-                return Err(Box::new(ChunkTypeDecodeError::InvalidByte(*byte)));
+                return Err(ChunkTypeDecodeError::InvalidByte(*byte).into());
             }
         }
         Ok(ChunkType { bytes })
@@ -28,7 +58,7 @@ impl FromStr for ChunkType {
     type Err = crate::Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         if s.len() != 4 {
-            return Err(Box::new(ChunkTypeDecodeError::InvalidLen(s.len())));
+            return Err(ChunkTypeDecodeError::InvalidLen(s.len()).into());
         }
 
         let mut temp: [u8; 4] = [0; 4];
@@ -37,7 +67,7 @@ impl FromStr for ChunkType {
             if ChunkType::is_valid_byte(*byte) {
                 temp[i] = *byte
             } else {
-                return Err(Box::new(ChunkTypeDecodeError::InvalidByte(*byte)));
+                return Err(ChunkTypeDecodeError::InvalidByte(*byte).into());
             }
         }
 
@@ -59,6 +89,13 @@ impl ChunkType {
         self.bytes
     }
 
+    /// Borrows the chunk type as a `&str` without allocating. The string lives
+    /// as long as this `ChunkType`.
+    pub fn as_str(&self) -> &str {
+        // construction only accepts ASCII letters, so the bytes are always UTF-8
+        std::str::from_utf8(&self.bytes).expect("chunk type bytes are ASCII letters")
+    }
+
     pub fn is_valid(&self) -> bool {
         (self.bytes.len() == 4)
             && ChunkType::is_reserved_bit_valid(self)
@@ -94,6 +131,65 @@ impl ChunkType {
     pub fn is_safe_to_copy(&self) -> bool {
         !Self::semantic_bit_is_zero(self.bytes[3])
     }
+
+    /// A short description of a standard chunk type, or `None` for unknown and
+    /// custom types.
+    pub fn description(&self) -> Option<&'static str> {
+        KNOWN_CHUNK_TYPES
+            .iter()
+            .find(|(bytes, _)| **bytes == self.bytes)
+            .map(|(_, description)| *description)
+    }
+
+    /// Compares with `other` ignoring ASCII case, so `text` matches `tEXt` and
+    /// `TEXT`. Case carries the critical, public, reserved and safe-to-copy bits,
+    /// so types that match this way can mean different things to a decoder.
+    pub fn eq_ignore_case(&self, other: &str) -> bool {
+        self.bytes.eq_ignore_ascii_case(other.as_bytes())
+    }
+
+    /// Whether this is one of the public chunk types registered in the PNG
+    /// specification, like `tEXt`. Other types with the public bit set may
+    /// clash with future registrations.
+    pub fn is_registered_public(&self) -> bool {
+        self.is_public() && self.description().is_some()
+    }
+
+    /// Returns a copy with the semantic bit of byte `index` set (lowercase) or
+    /// cleared (uppercase).
+    fn with_semantic_bit(self, index: usize, set: bool) -> ChunkType {
+        let mut bytes = self.bytes;
+        bytes[index] = if set {
+            bytes[index].to_ascii_lowercase()
+        } else {
+            bytes[index].to_ascii_uppercase()
+        };
+        ChunkType { bytes }
+    }
+
+    pub fn to_critical(self) -> ChunkType {
+        self.with_semantic_bit(0, false)
+    }
+
+    pub fn to_ancillary(self) -> ChunkType {
+        self.with_semantic_bit(0, true)
+    }
+
+    pub fn to_public(self) -> ChunkType {
+        self.with_semantic_bit(1, false)
+    }
+
+    pub fn to_private(self) -> ChunkType {
+        self.with_semantic_bit(1, true)
+    }
+
+    pub fn to_safe_to_copy(self) -> ChunkType {
+        self.with_semantic_bit(3, true)
+    }
+
+    pub fn to_unsafe_to_copy(self) -> ChunkType {
+        self.with_semantic_bit(3, false)
+    }
 }
 
 #[derive(Debug)]
@@ -207,6 +303,68 @@ mod tests {
         assert_eq!(&chunk.to_string(), "RuSt");
     }
 
+    #[test]
+    pub fn test_chunk_type_flag_helpers() {
+        let chunk = ChunkType::from_str("RuSt").unwrap();
+
+        assert_eq!(&chunk.to_ancillary().to_string(), "ruSt");
+        assert_eq!(&chunk.to_critical().to_string(), "RuSt");
+        assert_eq!(&chunk.to_public().to_string(), "RUSt");
+        assert_eq!(&chunk.to_private().to_string(), "RuSt");
+        assert_eq!(&chunk.to_unsafe_to_copy().to_string(), "RuST");
+        assert_eq!(&chunk.to_safe_to_copy().to_string(), "RuSt");
+
+        let flipped = chunk.to_ancillary().to_public().to_unsafe_to_copy();
+        assert!(!flipped.is_critical());
+        assert!(flipped.is_public());
+        assert!(!flipped.is_safe_to_copy());
+        assert_eq!(&chunk.to_string(), "RuSt");
+    }
+
+    #[test]
+    pub fn test_chunk_type_description() {
+        let chunk = ChunkType::from_str("pHYs").unwrap();
+        assert_eq!(chunk.description(), Some("Physical pixel dimensions"));
+
+        let chunk = ChunkType::from_str("RuSt").unwrap();
+        assert_eq!(chunk.description(), None);
+    }
+
+    #[test]
+    pub fn test_chunk_type_eq_ignore_case() {
+        let chunk = ChunkType::from_str("tEXt").unwrap();
+        assert!(chunk.eq_ignore_case("tEXt"));
+        assert!(chunk.eq_ignore_case("TEXT"));
+        assert!(chunk.eq_ignore_case("text"));
+        assert!(!chunk.eq_ignore_case("zTXt"));
+        assert!(!chunk.eq_ignore_case("tEXtt"));
+    }
+
+    #[test]
+    pub fn test_chunk_type_is_registered_public() {
+        assert!(ChunkType::from_str("tEXt").unwrap().is_registered_public());
+        assert!(ChunkType::from_str("IDAT").unwrap().is_registered_public());
+        assert!(!ChunkType::from_str("RUSt").unwrap().is_registered_public());
+        assert!(!ChunkType::from_str("ruSt").unwrap().is_registered_public());
+    }
+
+    #[test]
+    pub fn test_chunk_type_as_str() {
+        let chunk = ChunkType::from_str("RuSt").unwrap();
+        assert_eq!(chunk.as_str(), "RuSt");
+        assert_eq!(chunk.as_str(), chunk.to_string());
+    }
+
+    #[test]
+    pub fn test_chunk_type_str_round_trip() {
+        for s in ["IHDR", "tEXt", "RuSt", "ruST", "abcd"] {
+            let chunk = ChunkType::from_str(s).unwrap();
+            assert_eq!(chunk.to_string(), s);
+            assert_eq!(ChunkType::from_str(&chunk.to_string()).unwrap(), chunk);
+            assert_eq!(ChunkType::try_from(chunk.bytes()).unwrap(), chunk);
+        }
+    }
+
     #[test]
     pub fn test_chunk_type_trait_impls() {
         let chunk_type_1: ChunkType = TryFrom::try_from([82, 117, 83, 116]).unwrap();
@@ -214,4 +372,22 @@ mod tests {
         let _chunk_string = format!("{}", chunk_type_1);
         let _are_chunks_equal = chunk_type_1 == chunk_type_2;
     }
+
+    #[test]
+    pub fn test_chunk_type_ord_and_hash() {
+        let mut types: Vec<ChunkType> = ["tEXt", "IEND", "IDAT", "RuSt", "IDAT"]
+            .iter()
+            .map(|s| ChunkType::from_str(s).unwrap())
+            .collect();
+        types.sort();
+        let sorted: Vec<String> = types.iter().map(ToString::to_string).collect();
+        assert_eq!(sorted, ["IDAT", "IDAT", "IEND", "RuSt", "tEXt"]);
+
+        let mut counts = std::collections::HashMap::new();
+        for chunk_type in types {
+            *counts.entry(chunk_type).or_insert(0) += 1;
+        }
+        assert_eq!(counts[&ChunkType::from_str("IDAT").unwrap()], 2);
+        assert_eq!(counts.len(), 4);
+    }
 }
@@ -0,0 +1,191 @@
+#![allow(dead_code)]
+
+//! Password-based encryption for embedded messages: PBKDF2-HMAC-SHA256 derives
+//! a key that seals the message with AES-256-GCM.
+//!
+//! Encrypted payloads are laid out as `MAGIC || salt || nonce || ciphertext || tag`.
+//!
+//! Signed payloads carry a fixed-size trailer, `message || SIGNATURE_MAGIC || hmac`,
+//! where the HMAC-SHA256 covers the message.
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+pub const MAGIC: &[u8; 8] = b"pngmeAES";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+pub const SIGNATURE_MAGIC: &[u8; 8] = b"pngmeSIG";
+/// The length of the trailer `sign` appends: the marker and a 32-byte HMAC.
+pub const SIGNATURE_LEN: usize = SIGNATURE_MAGIC.len() + 32;
+const KDF_ITERATIONS: u32 = 100_000;
+
+/// Encrypts `message` with a key derived from `password`.
+pub fn encrypt(message: &[u8], password: &str) -> crate::Result<Vec<u8>> {
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    fill_random(&mut salt)?;
+    fill_random(&mut nonce)?;
+
+    let sealed = cipher(password, &salt)
+        .encrypt(&Nonce::from(nonce), message)
+        .map_err(|_| CryptoError::MessageTooLong)?;
+
+    Ok([MAGIC.as_slice(), &salt, &nonce, &sealed].concat())
+}
+
+/// Decrypts a payload produced by `encrypt`, failing if the password is wrong
+/// or the data was tampered with.
+pub fn decrypt(data: &[u8], password: &str) -> crate::Result<Vec<u8>> {
+    if !is_encrypted(data) {
+        return Err(CryptoError::NotEncrypted.into());
+    }
+    let data = &data[MAGIC.len()..];
+    if data.len() < SALT_LEN + NONCE_LEN + TAG_LEN {
+        return Err(CryptoError::Truncated.into());
+    }
+
+    let (salt, rest) = data.split_at(SALT_LEN);
+    let (nonce, sealed) = rest.split_at(NONCE_LEN);
+    let nonce: [u8; NONCE_LEN] = nonce.try_into().map_err(|_| CryptoError::Truncated)?;
+
+    cipher(password, salt)
+        .decrypt(&Nonce::from(nonce), sealed)
+        .map_err(|_| CryptoError::AuthenticationFailed.into())
+}
+
+/// The AES-256-GCM cipher keyed by PBKDF2-HMAC-SHA256 of `password` and `salt`.
+fn cipher(password: &str, salt: &[u8]) -> Aes256Gcm {
+    let key = pbkdf2::pbkdf2_hmac_array::<Sha256, 32>(password.as_bytes(), salt, KDF_ITERATIONS);
+    Aes256Gcm::new(&key.into())
+}
+
+/// Returns whether `data` starts with the encrypted payload marker.
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// Appends a trailer holding the HMAC-SHA256 of `message` under `key`.
+pub fn sign(message: &[u8], key: &str) -> Vec<u8> {
+    let mac = hmac(key, message).finalize().into_bytes();
+    [message, SIGNATURE_MAGIC, &mac].concat()
+}
+
+/// Splits data produced by `sign` into the message and whether its HMAC
+/// matches `key`. The comparison is constant-time.
+pub fn verify_signature<'a>(data: &'a [u8], key: &str) -> crate::Result<(&'a [u8], bool)> {
+    if !is_signed(data) {
+        return Err(CryptoError::NotSigned.into());
+    }
+    let (message, trailer) = data.split_at(data.len() - SIGNATURE_LEN);
+    let valid = hmac(key, message)
+        .verify_slice(&trailer[SIGNATURE_MAGIC.len()..])
+        .is_ok();
+    Ok((message, valid))
+}
+
+fn hmac(key: &str, message: &[u8]) -> Hmac<Sha256> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(key.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(message);
+    mac
+}
+
+/// Returns the message in `data` without its signature trailer, if it has one.
+pub fn strip_signature(data: &[u8]) -> &[u8] {
+    if is_signed(data) {
+        &data[..data.len() - SIGNATURE_LEN]
+    } else {
+        data
+    }
+}
+
+/// Returns whether `data` ends with a signature trailer.
+pub fn is_signed(data: &[u8]) -> bool {
+    data.len() >= SIGNATURE_LEN && data[data.len() - SIGNATURE_LEN..].starts_with(SIGNATURE_MAGIC)
+}
+
+fn fill_random(buffer: &mut [u8]) -> crate::Result<()> {
+    getrandom::fill(buffer).map_err(|e| io::Error::other(e.to_string()))?;
+    Ok(())
+}
+
+#[derive(Debug)]
+pub enum CryptoError {
+    NotEncrypted,
+    Truncated,
+    AuthenticationFailed,
+    MessageTooLong,
+    NotSigned,
+}
+
+impl fmt::Display for CryptoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CryptoError::NotEncrypted => write!(f, "The chunk data is not encrypted"),
+            CryptoError::Truncated => write!(f, "The encrypted chunk data is truncated"),
+            CryptoError::AuthenticationFailed => write!(
+                f,
+                "Decryption failed: wrong password or the data has been tampered with"
+            ),
+            CryptoError::MessageTooLong => write!(f, "The message is too long to encrypt"),
+            CryptoError::NotSigned => write!(f, "The chunk data is not signed"),
+        }
+    }
+}
+
+impl Error for CryptoError {}
+
+// ----------TESTS-------------//
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_round_trip() {
+        let sealed = encrypt(b"This is where your secret message will be!", "hunter2").unwrap();
+
+        assert!(is_encrypted(&sealed));
+        assert_eq!(
+            decrypt(&sealed, "hunter2").unwrap(),
+            b"This is where your secret message will be!"
+        );
+        assert!(decrypt(&sealed, "hunter3").is_err());
+    }
+
+    #[test]
+    fn test_sign_round_trip() {
+        let signed = sign(b"This is where your secret message will be!", "hunter2");
+        assert_eq!(signed.len(), 42 + SIGNATURE_LEN);
+        assert!(is_signed(&signed));
+
+        let (message, valid) = verify_signature(&signed, "hunter2").unwrap();
+        assert_eq!(message, b"This is where your secret message will be!");
+        assert!(valid);
+        assert!(!verify_signature(&signed, "hunter3").unwrap().1);
+
+        let mut tampered = signed.clone();
+        tampered[0] ^= 1;
+        assert!(!verify_signature(&tampered, "hunter2").unwrap().1);
+    }
+
+    #[test]
+    fn test_strip_signature() {
+        let signed = sign(b"message", "hunter2");
+        assert_eq!(strip_signature(&signed), b"message");
+        assert_eq!(strip_signature(b"message"), b"message");
+    }
+
+    #[test]
+    fn test_verify_unsigned() {
+        assert!(!is_signed(b"short"));
+        assert!(verify_signature(b"short", "hunter2").is_err());
+        assert!(verify_signature(&[0u8; SIGNATURE_LEN], "hunter2").is_err());
+    }
+}
@@ -0,0 +1,86 @@
+//! The crate-wide error type. Every module keeps its own error enum, and
+//! `PngMeError` wraps them so callers can match on what went wrong.
+
+use crate::apng::ApngError;
+use crate::base64::Base64Error;
+use crate::chunk::ChunkError;
+use crate::chunk_type::ChunkTypeDecodeError;
+use crate::crypto::CryptoError;
+use crate::header::HeaderError;
+use crate::lsb::LsbError;
+use crate::metadata::MetadataError;
+use crate::png::{PngDecodeError, PngStructureError};
+use crate::text::TextError;
+use crate::zlib::ZlibError;
+use std::io;
+use std::string::FromUtf8Error;
+
+/// Each variant is transparent: it displays as the error it wraps and passes
+/// on that error's source, so error reporters print each message once.
+#[derive(Debug, thiserror::Error)]
+pub enum PngMeError {
+    #[error(transparent)]
+    Chunk(#[from] ChunkError),
+    #[error(transparent)]
+    ChunkType(#[from] ChunkTypeDecodeError),
+    #[error(transparent)]
+    Decode(#[from] PngDecodeError),
+    #[error(transparent)]
+    Structure(#[from] PngStructureError),
+    #[error(transparent)]
+    Header(#[from] HeaderError),
+    #[error(transparent)]
+    Metadata(#[from] MetadataError),
+    #[error(transparent)]
+    Apng(#[from] ApngError),
+    #[error(transparent)]
+    Text(#[from] TextError),
+    #[error(transparent)]
+    Zlib(#[from] ZlibError),
+    #[error(transparent)]
+    Crypto(#[from] CryptoError),
+    #[error(transparent)]
+    Lsb(#[from] LsbError),
+    #[error(transparent)]
+    Base64(#[from] Base64Error),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Utf8(#[from] FromUtf8Error),
+}
+
+// ----------TESTS-------------//
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk::Chunk;
+    use crate::chunk_type::ChunkType;
+    use std::error::Error;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_errors_can_be_matched() {
+        let err = ChunkType::from_str("RuS").unwrap_err();
+        assert!(matches!(
+            err,
+            PngMeError::ChunkType(ChunkTypeDecodeError::InvalidLen(3))
+        ));
+
+        let mut bytes = Chunk::new(ChunkType::from_str("RuSt").unwrap(), vec![1, 2, 3]).as_bytes();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+        let err = Chunk::try_from(bytes.as_slice()).unwrap_err();
+        assert!(matches!(err, PngMeError::Chunk(ChunkError::InvalidCrc(..))));
+    }
+
+    #[test]
+    fn test_error_display_and_source() {
+        let err = PngMeError::from(PngDecodeError::InvalidSignature);
+        assert_eq!(
+            err.to_string(),
+            PngDecodeError::InvalidSignature.to_string()
+        );
+        assert!(err.source().is_none());
+    }
+}
@@ -0,0 +1,82 @@
+//! Recognizes common image formats by their magic bytes, so inputs that
+//! aren't PNGs at all can be named in the error instead of failing on the
+//! signature alone.
+
+use std::fmt;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DetectedFormat {
+    Png,
+    Jpeg,
+    Gif,
+    WebP,
+    Bmp,
+    Tiff,
+    Unknown,
+}
+
+const SIGNATURES: [(&[u8], DetectedFormat); 7] = [
+    (&[137, 80, 78, 71, 13, 10, 26, 10], DetectedFormat::Png),
+    (&[0xff, 0xd8, 0xff], DetectedFormat::Jpeg),
+    (b"GIF87a", DetectedFormat::Gif),
+    (b"GIF89a", DetectedFormat::Gif),
+    (b"BM", DetectedFormat::Bmp),
+    (b"II*\0", DetectedFormat::Tiff),
+    (b"MM\0*", DetectedFormat::Tiff),
+];
+
+/// Guesses the format of a file from its first bytes.
+pub fn detect_format(bytes: &[u8]) -> DetectedFormat {
+    // WebP is a RIFF container: "RIFF", a 4-byte size, then "WEBP".
+    if bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(b"WEBP") {
+        return DetectedFormat::WebP;
+    }
+    SIGNATURES
+        .iter()
+        .find(|(signature, _)| bytes.starts_with(signature))
+        .map_or(DetectedFormat::Unknown, |&(_, format)| format)
+}
+
+impl fmt::Display for DetectedFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DetectedFormat::Png => "PNG",
+            DetectedFormat::Jpeg => "JPEG",
+            DetectedFormat::Gif => "GIF",
+            DetectedFormat::WebP => "WebP",
+            DetectedFormat::Bmp => "BMP",
+            DetectedFormat::Tiff => "TIFF",
+            DetectedFormat::Unknown => "unknown",
+        };
+        write!(f, "{name}")
+    }
+}
+
+// ----------TESTS-------------//
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_format() {
+        let cases: [(&[u8], DetectedFormat); 9] = [
+            (&[137, 80, 78, 71, 13, 10, 26, 10, 0], DetectedFormat::Png),
+            (
+                &[0xff, 0xd8, 0xff, 0xe0, 0, 16, 74, 70],
+                DetectedFormat::Jpeg,
+            ),
+            (b"GIF89a\x01\x00", DetectedFormat::Gif),
+            (b"RIFF\x24\x00\x00\x00WEBPVP8 ", DetectedFormat::WebP),
+            (b"RIFF\x24\x00\x00\x00WAVEfmt ", DetectedFormat::Unknown),
+            (b"BM\x36\x00", DetectedFormat::Bmp),
+            (b"MM\x00\x2a\x00", DetectedFormat::Tiff),
+            // A PNG signature mangled by a text-mode transfer is not a PNG.
+            (&[137, 80, 78, 71, 10, 26, 10], DetectedFormat::Unknown),
+            (&[], DetectedFormat::Unknown),
+        ];
+        for (bytes, expected) in cases {
+            assert_eq!(detect_format(bytes), expected, "{bytes:?}");
+        }
+    }
+}
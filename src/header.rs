@@ -0,0 +1,261 @@
+#![allow(dead_code)]
+
+use crate::chunk::Chunk;
+use std::error::Error;
+use std::fmt;
+
+/// The image properties stored in the 13-byte `IHDR` chunk.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct PngHeader {
+    width: u32,
+    height: u32,
+    bit_depth: u8,
+    color_type: ColorType,
+    compression_method: u8,
+    filter_method: u8,
+    interlace_method: u8,
+}
+
+impl TryFrom<&Chunk> for PngHeader {
+    type Error = crate::Error;
+
+    fn try_from(chunk: &Chunk) -> Result<Self, Self::Error> {
+        let data = chunk.data();
+        if data.len() != 13 {
+            return Err(HeaderError::WrongLength(data.len()).into());
+        }
+
+        let color_type = ColorType::try_from(data[9])?;
+        let bit_depth = data[8];
+        if !color_type.allowed_bit_depths().contains(&bit_depth) {
+            return Err(HeaderError::BitDepthNotAllowed(bit_depth, color_type).into());
+        }
+
+        let width = dimension("width", [data[0], data[1], data[2], data[3]])?;
+        let height = dimension("height", [data[4], data[5], data[6], data[7]])?;
+
+        Ok(PngHeader {
+            width,
+            height,
+            bit_depth,
+            color_type,
+            compression_method: data[10],
+            filter_method: data[11],
+            interlace_method: data[12],
+        })
+    }
+}
+
+/// The largest width or height the PNG spec allows, 2^31-1.
+pub const MAX_DIMENSION: u32 = i32::MAX as u32;
+
+/// Reads a width or height, which must be between 1 and `MAX_DIMENSION`.
+fn dimension(name: &'static str, bytes: [u8; 4]) -> crate::Result<u32> {
+    match u32::from_be_bytes(bytes) {
+        value @ 1..=MAX_DIMENSION => Ok(value),
+        value => Err(HeaderError::DimensionOutOfRange(name, value).into()),
+    }
+}
+
+impl fmt::Display for PngHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}x{}, {}-bit {}, {}",
+            self.width,
+            self.height,
+            self.bit_depth,
+            self.color_type,
+            if self.is_interlaced() {
+                "interlaced"
+            } else {
+                "non-interlaced"
+            }
+        )
+    }
+}
+
+impl PngHeader {
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn bit_depth(&self) -> u8 {
+        self.bit_depth
+    }
+
+    pub fn color_type(&self) -> ColorType {
+        self.color_type
+    }
+
+    pub fn compression_method(&self) -> u8 {
+        self.compression_method
+    }
+
+    pub fn filter_method(&self) -> u8 {
+        self.filter_method
+    }
+
+    pub fn interlace_method(&self) -> u8 {
+        self.interlace_method
+    }
+
+    pub fn is_interlaced(&self) -> bool {
+        self.interlace_method == 1
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ColorType {
+    Grayscale,
+    Rgb,
+    Indexed,
+    GrayscaleAlpha,
+    Rgba,
+}
+
+impl TryFrom<u8> for ColorType {
+    type Error = crate::Error;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(ColorType::Grayscale),
+            2 => Ok(ColorType::Rgb),
+            3 => Ok(ColorType::Indexed),
+            4 => Ok(ColorType::GrayscaleAlpha),
+            6 => Ok(ColorType::Rgba),
+            _ => Err(HeaderError::UnknownColorType(value).into()),
+        }
+    }
+}
+
+impl fmt::Display for ColorType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColorType::Grayscale => write!(f, "grayscale"),
+            ColorType::Rgb => write!(f, "RGB"),
+            ColorType::Indexed => write!(f, "indexed"),
+            ColorType::GrayscaleAlpha => write!(f, "grayscale+alpha"),
+            ColorType::Rgba => write!(f, "RGBA"),
+        }
+    }
+}
+
+impl ColorType {
+    /// The number of samples that make up each pixel.
+    pub fn channels(&self) -> usize {
+        match self {
+            ColorType::Grayscale | ColorType::Indexed => 1,
+            ColorType::GrayscaleAlpha => 2,
+            ColorType::Rgb => 3,
+            ColorType::Rgba => 4,
+        }
+    }
+
+    pub fn has_alpha(&self) -> bool {
+        matches!(self, ColorType::GrayscaleAlpha | ColorType::Rgba)
+    }
+
+    fn allowed_bit_depths(&self) -> &'static [u8] {
+        match self {
+            ColorType::Grayscale => &[1, 2, 4, 8, 16],
+            ColorType::Indexed => &[1, 2, 4, 8],
+            ColorType::Rgb | ColorType::GrayscaleAlpha | ColorType::Rgba => &[8, 16],
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum HeaderError {
+    WrongLength(usize),
+    UnknownColorType(u8),
+    BitDepthNotAllowed(u8, ColorType),
+    DimensionOutOfRange(&'static str, u32),
+}
+
+impl fmt::Display for HeaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HeaderError::WrongLength(length) => {
+                write!(f, "IHDR data must be 13 bytes long, found {length}")
+            }
+            HeaderError::DimensionOutOfRange(name, value) => write!(
+                f,
+                "The image {name} must be between 1 and {MAX_DIMENSION}, found {value}"
+            ),
+            HeaderError::UnknownColorType(value) => write!(f, "Unknown color type: {value}"),
+            HeaderError::BitDepthNotAllowed(depth, color_type) => {
+                write!(
+                    f,
+                    "A bit depth of {depth} is not allowed for {color_type} images"
+                )
+            }
+        }
+    }
+}
+
+impl Error for HeaderError {}
+
+// ----------TESTS-------------//
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk_type::ChunkType;
+    use std::str::FromStr;
+
+    fn ihdr(data: Vec<u8>) -> Chunk {
+        Chunk::new(ChunkType::from_str("IHDR").unwrap(), data)
+    }
+
+    #[test]
+    fn test_header_from_chunk() {
+        let chunk = ihdr(vec![0, 0, 7, 128, 0, 0, 4, 56, 8, 6, 0, 0, 0]);
+        let header = PngHeader::try_from(&chunk).unwrap();
+
+        assert_eq!(header.width(), 1920);
+        assert_eq!(header.height(), 1080);
+        assert_eq!(header.bit_depth(), 8);
+        assert_eq!(header.color_type(), ColorType::Rgba);
+        assert!(!header.is_interlaced());
+        assert_eq!(header.to_string(), "1920x1080, 8-bit RGBA, non-interlaced");
+    }
+
+    #[test]
+    fn test_header_invalid_length() {
+        let chunk = ihdr(vec![0, 0, 7, 128, 0, 0, 4, 56, 8, 6, 0, 0]);
+        assert!(PngHeader::try_from(&chunk).is_err());
+    }
+
+    #[test]
+    fn test_header_invalid_color_type_and_depth() {
+        let chunk = ihdr(vec![0, 0, 0, 1, 0, 0, 0, 1, 8, 5, 0, 0, 0]);
+        assert!(PngHeader::try_from(&chunk).is_err());
+
+        let chunk = ihdr(vec![0, 0, 0, 1, 0, 0, 0, 1, 4, 2, 0, 0, 0]);
+        assert!(PngHeader::try_from(&chunk).is_err());
+    }
+
+    #[test]
+    fn test_header_dimension_out_of_range() {
+        let chunk = ihdr(vec![0, 0, 0, 0, 0, 0, 0, 1, 8, 2, 0, 0, 0]);
+        let err = PngHeader::try_from(&chunk).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The image width must be between 1 and 2147483647, found 0"
+        );
+
+        let chunk = ihdr(vec![0, 0, 0, 1, 0xff, 0xff, 0xff, 0xff, 8, 2, 0, 0, 0]);
+        assert!(matches!(
+            PngHeader::try_from(&chunk).unwrap_err(),
+            crate::Error::Header(HeaderError::DimensionOutOfRange("height", u32::MAX))
+        ));
+
+        let chunk = ihdr(vec![0x7f, 0xff, 0xff, 0xff, 0, 0, 0, 1, 8, 2, 0, 0, 0]);
+        assert_eq!(PngHeader::try_from(&chunk).unwrap().width(), MAX_DIMENSION);
+    }
+}
@@ -0,0 +1,50 @@
+//! Read, edit and write the chunks of PNG files, and hide messages in them.
+//!
+//! ```
+//! use pngme::{Chunk, ChunkType, Png};
+//! use std::str::FromStr;
+//!
+//! # fn main() -> pngme::Result<()> {
+//! # let mut image = Png::new();
+//! # for (chunk_type, data) in [("IHDR", &[0, 0, 0, 1, 0, 0, 0, 1, 8, 0, 0, 0, 0][..]), ("IDAT", &[]), ("IEND", &[])] {
+//! #     image.append_chunk(Chunk::new(ChunkType::from_str(chunk_type)?, data.to_vec()))?;
+//! # }
+//! # let bytes = image.as_bytes();
+//! // Parse an existing file, e.g. from `std::fs::read("image.png")?`.
+//! let mut png = Png::try_from(bytes.as_slice())?;
+//!
+//! // Add a private chunk carrying the message, ahead of IEND.
+//! let chunk = Chunk::new(ChunkType::from_str("ruSt")?, b"Hello!".to_vec());
+//! png.insert_chunk_before("IEND", chunk)?;
+//!
+//! // Write it back out; any `std::io::Write` will do.
+//! let mut output = Vec::new();
+//! png.write_to(&mut output)?;
+//!
+//! let reread = Png::try_from(output.as_slice())?;
+//! assert_eq!(reread.chunk_by_type("ruSt").unwrap().data(), b"Hello!");
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod apng;
+pub mod base64;
+pub mod chunk;
+pub mod chunk_type;
+pub mod crypto;
+pub mod error;
+pub mod format;
+pub mod header;
+pub mod lsb;
+pub mod metadata;
+pub mod png;
+pub mod text;
+pub mod zlib;
+
+pub use chunk::Chunk;
+pub use chunk_type::ChunkType;
+pub use error::PngMeError;
+pub use png::Png;
+
+pub type Error = error::PngMeError;
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,337 @@
+#![allow(dead_code)]
+
+//! Hides messages in the least significant bits of a PNG's pixel samples rather
+//! than in a chunk of their own.
+//!
+//! The message is prefixed with its length as a big-endian `u32` and written one
+//! bit per color sample, most significant bit first. Alpha samples are left
+//! untouched so transparency is preserved.
+
+use crate::chunk::Chunk;
+use crate::chunk_type::ChunkType;
+use crate::header::ColorType;
+use crate::png::Png;
+use crate::zlib;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+const LENGTH_BITS: usize = 32;
+
+/// How many message bytes `hide` can fit in the pixels of `png`.
+pub fn capacity(png: &Png) -> crate::Result<usize> {
+    Ok(ImageLayout::from_png(png)?.capacity())
+}
+
+/// Returns a copy of `png` with `message` hidden in its pixel data. All `IDAT`
+/// chunks are replaced by a single one where the first used to be.
+pub fn hide(png: &Png, message: &[u8]) -> crate::Result<Png> {
+    let layout = ImageLayout::from_png(png)?;
+    let mut pixels = layout.unfilter(&layout.inflate(png)?)?;
+
+    let capacity = layout.capacity();
+    if message.len() > capacity {
+        return Err(LsbError::MessageTooLarge(message.len(), capacity).into());
+    }
+
+    let length = (message.len() as u32).to_be_bytes();
+    let bits = length
+        .iter()
+        .chain(message)
+        .flat_map(|byte| (0..8).rev().map(move |i| (byte >> i) & 1));
+    for (position, bit) in layout.sample_positions().zip(bits) {
+        pixels[position] = (pixels[position] & !1) | bit;
+    }
+
+    let idat = Chunk::new(
+        ChunkType::from_str("IDAT")?,
+        zlib::compress(&layout.filter(&pixels)),
+    );
+
+    let mut chunks = Vec::with_capacity(png.chunks().len());
+    let mut idat = Some(idat);
+    for chunk in png.chunks() {
+        if chunk.chunk_type().as_str() == "IDAT" {
+            chunks.extend(idat.take());
+        } else {
+            chunks.push(Chunk::new(*chunk.chunk_type(), chunk.data().to_vec()));
+        }
+    }
+
+    Ok(Png::from_chunks(chunks))
+}
+
+/// Extracts a message previously hidden with `hide`.
+pub fn reveal(png: &Png) -> crate::Result<Vec<u8>> {
+    let layout = ImageLayout::from_png(png)?;
+    let pixels = layout.unfilter(&layout.inflate(png)?)?;
+
+    let mut bits = layout.sample_positions().map(|i| pixels[i] & 1);
+    let mut bytes =
+        std::iter::from_fn(|| (0..8).try_fold(0u8, |acc, _| Some((acc << 1) | bits.next()?)));
+
+    let length: Vec<u8> = bytes.by_ref().take(LENGTH_BITS / 8).collect();
+    let length = u32::from_be_bytes(length.try_into().map_err(|_| LsbError::NoMessage)?);
+
+    if length as usize > layout.capacity() {
+        return Err(LsbError::NoMessage.into());
+    }
+
+    Ok(bytes.take(length as usize).collect())
+}
+
+fn image_data(png: &Png) -> Vec<u8> {
+    png.chunks_by_type("IDAT")
+        .iter()
+        .flat_map(|chunk| chunk.data())
+        .copied()
+        .collect()
+}
+
+/// The shape of the pixel data. Every size is computed with checked
+/// arithmetic up front, so dimensions too large to address are reported as
+/// corrupt rather than overflowing later.
+struct ImageLayout {
+    pixels: usize,
+    color_channels: usize,
+    bytes_per_sample: usize,
+    bytes_per_pixel: usize,
+    stride: usize,
+    height: usize,
+}
+
+impl ImageLayout {
+    fn from_png(png: &Png) -> crate::Result<ImageLayout> {
+        let header = png.header()?;
+
+        if header.color_type() == ColorType::Indexed {
+            return Err(LsbError::Unsupported("palette images").into());
+        }
+        if header.bit_depth() < 8 {
+            return Err(LsbError::Unsupported("bit depths below 8").into());
+        }
+        if header.is_interlaced() {
+            return Err(LsbError::Unsupported("interlaced images").into());
+        }
+
+        let channels = header.color_type().channels();
+        let (width, height) = (header.width() as usize, header.height() as usize);
+        let bytes_per_sample = usize::from(header.bit_depth() / 8);
+        let bytes_per_pixel = channels * bytes_per_sample;
+        let color_channels = channels - usize::from(header.color_type().has_alpha());
+
+        let pixels = width.checked_mul(height);
+        let stride = width.checked_mul(bytes_per_pixel);
+        // The filtered scanlines, each with a leading filter type byte.
+        let raw_len = stride
+            .and_then(|stride| stride.checked_add(1))
+            .and_then(|line| line.checked_mul(height));
+        let samples = pixels.and_then(|pixels| pixels.checked_mul(color_channels));
+        match (pixels, stride, raw_len, samples) {
+            (Some(pixels), Some(stride), Some(_), Some(_)) => Ok(ImageLayout {
+                pixels,
+                color_channels,
+                bytes_per_sample,
+                bytes_per_pixel,
+                stride,
+                height,
+            }),
+            _ => Err(LsbError::CorruptImageData.into()),
+        }
+    }
+
+    /// One bit per color sample, less the 32 bits of the length prefix.
+    fn capacity(&self) -> usize {
+        (self.pixels * self.color_channels).saturating_sub(LENGTH_BITS) / 8
+    }
+
+    /// The length of the filtered scanlines the `IDAT` data inflates to.
+    fn raw_len(&self) -> usize {
+        (self.stride + 1) * self.height
+    }
+
+    /// Inflates the image data, refusing to produce more than the scanlines
+    /// the header describes.
+    fn inflate(&self, png: &Png) -> crate::Result<Vec<u8>> {
+        zlib::decompress_with_limit(&image_data(png), self.raw_len())
+    }
+
+    /// Indices of the least significant byte of every color sample.
+    fn sample_positions(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.pixels).flat_map(move |pixel| {
+            (1..=self.color_channels).map(move |channel| {
+                pixel * self.bytes_per_pixel + channel * self.bytes_per_sample - 1
+            })
+        })
+    }
+
+    /// Reverses the per-scanline filters, returning the bare pixel bytes.
+    fn unfilter(&self, raw: &[u8]) -> crate::Result<Vec<u8>> {
+        let (stride, bpp) = (self.stride, self.bytes_per_pixel);
+        if raw.len() < self.raw_len() {
+            return Err(LsbError::CorruptImageData.into());
+        }
+
+        let mut pixels = vec![0u8; self.height * stride];
+        for (y, line) in raw.chunks_exact(stride + 1).take(self.height).enumerate() {
+            let (filter, line) = (line[0], &line[1..]);
+            let (previous, current) = pixels.split_at_mut(y * stride);
+            let above = match y {
+                0 => None,
+                _ => Some(&previous[(y - 1) * stride..]),
+            };
+            let current = &mut current[..stride];
+
+            for x in 0..stride {
+                let a = if x >= bpp { current[x - bpp] } else { 0 };
+                let b = above.map_or(0, |above| above[x]);
+                let c = match above {
+                    Some(above) if x >= bpp => above[x - bpp],
+                    _ => 0,
+                };
+                let predictor = match filter {
+                    0 => 0,
+                    1 => a,
+                    2 => b,
+                    3 => ((u16::from(a) + u16::from(b)) / 2) as u8,
+                    4 => paeth(a, b, c),
+                    _ => return Err(LsbError::CorruptImageData.into()),
+                };
+                current[x] = line[x].wrapping_add(predictor);
+            }
+        }
+
+        Ok(pixels)
+    }
+
+    /// Lays the pixel bytes out as scanlines, each using filter type 0 (None).
+    fn filter(&self, pixels: &[u8]) -> Vec<u8> {
+        pixels
+            .chunks(self.stride)
+            .flat_map(|line| std::iter::once(0).chain(line.iter().copied()))
+            .collect()
+    }
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = i16::from(a) + i16::from(b) - i16::from(c);
+    let (pa, pb, pc) = (
+        (p - i16::from(a)).abs(),
+        (p - i16::from(b)).abs(),
+        (p - i16::from(c)).abs(),
+    );
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+#[derive(Debug)]
+pub enum LsbError {
+    Unsupported(&'static str),
+    CorruptImageData,
+    MessageTooLarge(usize, usize),
+    NoMessage,
+}
+
+impl fmt::Display for LsbError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LsbError::Unsupported(what) => {
+                write!(f, "Hiding messages in {what} is not supported")
+            }
+            LsbError::CorruptImageData => write!(f, "The PNG image data is corrupt"),
+            LsbError::MessageTooLarge(size, capacity) => write!(
+                f,
+                "The message is {size} bytes but this image can only hide {capacity} bytes"
+            ),
+            LsbError::NoMessage => write!(f, "No hidden message was found in the image"),
+        }
+    }
+}
+
+impl Error for LsbError {}
+
+// ----------TESTS-------------//
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(chunk_type: &str, data: Vec<u8>) -> Chunk {
+        Chunk::new(ChunkType::from_str(chunk_type).unwrap(), data)
+    }
+
+    /// An 8x8 8-bit RGB image whose rows use every filter type.
+    fn testing_png() -> Png {
+        let ihdr = [0, 0, 0, 8, 0, 0, 0, 8, 8, 2, 0, 0, 0].to_vec();
+
+        let mut raw = Vec::new();
+        for y in 0..8u8 {
+            raw.push(y % 5);
+            raw.extend((0..24u8).map(|x| x.wrapping_mul(y).wrapping_add(y * 3)));
+        }
+
+        Png::from_chunks(vec![
+            chunk("IHDR", ihdr),
+            chunk("IDAT", zlib::compress(&raw)),
+            chunk("IEND", Vec::new()),
+        ])
+    }
+
+    #[test]
+    fn test_hide_and_reveal() {
+        let png = testing_png();
+        let hidden = hide(&png, b"Hidden!").unwrap();
+
+        assert_eq!(reveal(&hidden).unwrap(), b"Hidden!");
+        assert_eq!(hidden.chunks().len(), 3);
+        assert_eq!(&hidden.chunks()[1].chunk_type().to_string(), "IDAT");
+    }
+
+    #[test]
+    fn test_hide_only_changes_low_bits() {
+        let png = testing_png();
+        let hidden = hide(&png, b"Hidden!").unwrap();
+        let layout = ImageLayout::from_png(&png).unwrap();
+
+        let before = layout.unfilter(&layout.inflate(&png).unwrap()).unwrap();
+        let after = layout.unfilter(&layout.inflate(&hidden).unwrap()).unwrap();
+
+        assert!(before.iter().zip(&after).all(|(a, b)| a & !1 == b & !1));
+    }
+
+    #[test]
+    fn test_hide_message_too_large() {
+        // 8x8 RGB holds 192 bits, 32 of which are the length
+        let png = testing_png();
+        assert!(hide(&png, &[0; 20]).is_ok());
+        assert!(hide(&png, &[0; 21]).is_err());
+        assert_eq!(capacity(&png).unwrap(), 20);
+    }
+
+    #[test]
+    fn test_huge_dimensions() {
+        // 2^31-1 square at 16-bit RGBA has more bytes per image than a usize holds.
+        let ihdr = [
+            0x7f, 0xff, 0xff, 0xff, 0x7f, 0xff, 0xff, 0xff, 16, 6, 0, 0, 0,
+        ]
+        .to_vec();
+        let png = Png::from_chunks(vec![
+            chunk("IHDR", ihdr),
+            chunk("IDAT", zlib::compress(&[0; 64])),
+            chunk("IEND", Vec::new()),
+        ]);
+
+        for err in [
+            capacity(&png).unwrap_err(),
+            reveal(&png).unwrap_err(),
+            hide(&png, b"hi").unwrap_err(),
+        ] {
+            assert!(matches!(err, crate::Error::Lsb(LsbError::CorruptImageData)));
+        }
+    }
+}
@@ -1,16 +0,0 @@
-use args::Commands;
-use clap::Parser;
-
-mod args;
-mod chunk;
-mod chunk_type;
-mod commands;
-mod png;
-
-pub type Error = Box<dyn std::error::Error>;
-pub type Result<T> = std::result::Result<T, Error>;
-
-fn main() -> Result<()> {
-    let args = Commands::parse();
-    commands::run(args.command)
-}
@@ -0,0 +1,517 @@
+#![allow(dead_code)]
+
+//! Typed views of common ancillary chunks: `gAMA`, `pHYs`, `tIME`, `sRGB`,
+//! `cHRM` and `bKGD`, and of the `PLTE` palette.
+
+use crate::chunk::Chunk;
+use crate::header::ColorType;
+use std::error::Error;
+use std::fmt;
+
+const METERS_PER_INCH: f64 = 0.0254;
+
+/// The image gamma from a `gAMA` chunk, stored as gamma times 100000.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Gamma {
+    value: u32,
+}
+
+impl TryFrom<&Chunk> for Gamma {
+    type Error = crate::Error;
+
+    fn try_from(chunk: &Chunk) -> Result<Self, Self::Error> {
+        let data: [u8; 4] = fixed_length("gAMA", chunk)?;
+        Ok(Gamma {
+            value: u32::from_be_bytes(data),
+        })
+    }
+}
+
+impl fmt::Display for Gamma {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.5}", self.gamma())
+    }
+}
+
+impl Gamma {
+    pub fn gamma(&self) -> f64 {
+        f64::from(self.value) / 100_000.0
+    }
+
+    pub fn raw_value(&self) -> u32 {
+        self.value
+    }
+}
+
+/// The pixel size from a `pHYs` chunk.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct PhysicalDimensions {
+    x_pixels_per_unit: u32,
+    y_pixels_per_unit: u32,
+    unit: PhysicalUnit,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PhysicalUnit {
+    /// Only the aspect ratio of the pixels is known.
+    Unknown,
+    Meter,
+}
+
+impl TryFrom<&Chunk> for PhysicalDimensions {
+    type Error = crate::Error;
+
+    fn try_from(chunk: &Chunk) -> Result<Self, Self::Error> {
+        let data: [u8; 9] = fixed_length("pHYs", chunk)?;
+        let unit = match data[8] {
+            0 => PhysicalUnit::Unknown,
+            1 => PhysicalUnit::Meter,
+            unit => return Err(MetadataError::UnknownUnit(unit).into()),
+        };
+
+        Ok(PhysicalDimensions {
+            x_pixels_per_unit: u32::from_be_bytes([data[0], data[1], data[2], data[3]]),
+            y_pixels_per_unit: u32::from_be_bytes([data[4], data[5], data[6], data[7]]),
+            unit,
+        })
+    }
+}
+
+impl fmt::Display for PhysicalDimensions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.dpi() {
+            Some((x, y)) if x.round() == y.round() => write!(f, "{x:.0} DPI"),
+            Some((x, y)) => write!(f, "{x:.0}x{y:.0} DPI"),
+            None => write!(
+                f,
+                "pixel aspect ratio {}:{}",
+                self.x_pixels_per_unit, self.y_pixels_per_unit
+            ),
+        }
+    }
+}
+
+impl PhysicalDimensions {
+    pub fn x_pixels_per_unit(&self) -> u32 {
+        self.x_pixels_per_unit
+    }
+
+    pub fn y_pixels_per_unit(&self) -> u32 {
+        self.y_pixels_per_unit
+    }
+
+    pub fn unit(&self) -> PhysicalUnit {
+        self.unit
+    }
+
+    /// The horizontal and vertical resolution in dots per inch, when the unit is
+    /// known.
+    pub fn dpi(&self) -> Option<(f64, f64)> {
+        match self.unit {
+            PhysicalUnit::Meter => Some((
+                f64::from(self.x_pixels_per_unit) * METERS_PER_INCH,
+                f64::from(self.y_pixels_per_unit) * METERS_PER_INCH,
+            )),
+            PhysicalUnit::Unknown => None,
+        }
+    }
+}
+
+/// The last modification time from a `tIME` chunk, in UTC.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct PngTime {
+    year: u16,
+    month: u8,
+    day: u8,
+    hour: u8,
+    minute: u8,
+    second: u8,
+}
+
+impl TryFrom<&Chunk> for PngTime {
+    type Error = crate::Error;
+
+    fn try_from(chunk: &Chunk) -> Result<Self, Self::Error> {
+        let data: [u8; 7] = fixed_length("tIME", chunk)?;
+        let time = PngTime {
+            year: u16::from_be_bytes([data[0], data[1]]),
+            month: data[2],
+            day: data[3],
+            hour: data[4],
+            minute: data[5],
+            second: data[6],
+        };
+
+        // the spec allows a second of 60 for leap seconds
+        let valid = (1..=12).contains(&time.month)
+            && (1..=31).contains(&time.day)
+            && time.hour <= 23
+            && time.minute <= 59
+            && time.second <= 60;
+        if !valid {
+            return Err(MetadataError::InvalidTime(time).into());
+        }
+        Ok(time)
+    }
+}
+
+impl fmt::Display for PngTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+impl PngTime {
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+
+    pub fn hour(&self) -> u8 {
+        self.hour
+    }
+
+    pub fn minute(&self) -> u8 {
+        self.minute
+    }
+
+    pub fn second(&self) -> u8 {
+        self.second
+    }
+}
+
+/// The rendering intent from an `sRGB` chunk, which marks the image as being
+/// in the sRGB color space.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RenderingIntent {
+    Perceptual,
+    RelativeColorimetric,
+    Saturation,
+    AbsoluteColorimetric,
+}
+
+impl TryFrom<&Chunk> for RenderingIntent {
+    type Error = crate::Error;
+
+    fn try_from(chunk: &Chunk) -> Result<Self, Self::Error> {
+        let [intent]: [u8; 1] = fixed_length("sRGB", chunk)?;
+        Ok(match intent {
+            0 => RenderingIntent::Perceptual,
+            1 => RenderingIntent::RelativeColorimetric,
+            2 => RenderingIntent::Saturation,
+            3 => RenderingIntent::AbsoluteColorimetric,
+            intent => return Err(MetadataError::UnknownRenderingIntent(intent).into()),
+        })
+    }
+}
+
+impl fmt::Display for RenderingIntent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            RenderingIntent::Perceptual => "Perceptual",
+            RenderingIntent::RelativeColorimetric => "Relative colorimetric",
+            RenderingIntent::Saturation => "Saturation",
+            RenderingIntent::AbsoluteColorimetric => "Absolute colorimetric",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// The CIE 1931 x,y chromaticities of the white point and the three primaries
+/// from a `cHRM` chunk, each stored times 100000 like the gamma.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Chromaticities {
+    white_point: (u32, u32),
+    red: (u32, u32),
+    green: (u32, u32),
+    blue: (u32, u32),
+}
+
+impl TryFrom<&Chunk> for Chromaticities {
+    type Error = crate::Error;
+
+    fn try_from(chunk: &Chunk) -> Result<Self, Self::Error> {
+        let data: [u8; 32] = fixed_length("cHRM", chunk)?;
+        let value = |i: usize| {
+            u32::from_be_bytes([
+                data[i * 4],
+                data[i * 4 + 1],
+                data[i * 4 + 2],
+                data[i * 4 + 3],
+            ])
+        };
+        Ok(Chromaticities {
+            white_point: (value(0), value(1)),
+            red: (value(2), value(3)),
+            green: (value(4), value(5)),
+            blue: (value(6), value(7)),
+        })
+    }
+}
+
+impl fmt::Display for Chromaticities {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (wx, wy) = self.white_point();
+        let (rx, ry) = self.red();
+        let (gx, gy) = self.green();
+        let (bx, by) = self.blue();
+        write!(
+            f,
+            "white ({wx:.4}, {wy:.4}), red ({rx:.4}, {ry:.4}), green ({gx:.4}, {gy:.4}), blue ({bx:.4}, {by:.4})"
+        )
+    }
+}
+
+impl Chromaticities {
+    pub fn white_point(&self) -> (f64, f64) {
+        scale(self.white_point)
+    }
+
+    pub fn red(&self) -> (f64, f64) {
+        scale(self.red)
+    }
+
+    pub fn green(&self) -> (f64, f64) {
+        scale(self.green)
+    }
+
+    pub fn blue(&self) -> (f64, f64) {
+        scale(self.blue)
+    }
+}
+
+fn scale((x, y): (u32, u32)) -> (f64, f64) {
+    (f64::from(x) / 100_000.0, f64::from(y) / 100_000.0)
+}
+
+/// The default background color from a `bKGD` chunk. Its layout depends on
+/// the image's color type, so it is parsed with `Background::from_chunk`
+/// rather than `TryFrom`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Background {
+    /// An index into the `PLTE` palette, for indexed images.
+    PaletteIndex(u8),
+    /// A gray level at the image's bit depth, for grayscale images.
+    Gray(u16),
+    /// Red, green and blue levels at the image's bit depth, for RGB images.
+    Rgb(u16, u16, u16),
+}
+
+impl Background {
+    pub fn from_chunk(chunk: &Chunk, color_type: ColorType) -> crate::Result<Background> {
+        Ok(match color_type {
+            ColorType::Indexed => {
+                let [index]: [u8; 1] = fixed_length("bKGD", chunk)?;
+                Background::PaletteIndex(index)
+            }
+            ColorType::Grayscale | ColorType::GrayscaleAlpha => {
+                let data: [u8; 2] = fixed_length("bKGD", chunk)?;
+                Background::Gray(u16::from_be_bytes(data))
+            }
+            ColorType::Rgb | ColorType::Rgba => {
+                let data: [u8; 6] = fixed_length("bKGD", chunk)?;
+                Background::Rgb(
+                    u16::from_be_bytes([data[0], data[1]]),
+                    u16::from_be_bytes([data[2], data[3]]),
+                    u16::from_be_bytes([data[4], data[5]]),
+                )
+            }
+        })
+    }
+}
+
+impl fmt::Display for Background {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Background::PaletteIndex(index) => write!(f, "palette index {index}"),
+            Background::Gray(level) => write!(f, "gray {level}"),
+            Background::Rgb(red, green, blue) => write!(f, "RGB ({red}, {green}, {blue})"),
+        }
+    }
+}
+
+/// Splits a `PLTE` chunk into its RGB entries. There must be between 1 and
+/// 256 of them, three bytes each.
+pub fn palette_entries(chunk: &Chunk) -> crate::Result<Vec<[u8; 3]>> {
+    let data = chunk.data();
+    if data.is_empty() || !data.len().is_multiple_of(3) || data.len() / 3 > 256 {
+        return Err(MetadataError::InvalidPaletteLength(data.len()).into());
+    }
+    Ok(data
+        .chunks_exact(3)
+        .map(|entry| [entry[0], entry[1], entry[2]])
+        .collect())
+}
+
+fn fixed_length<const N: usize>(chunk_type: &'static str, chunk: &Chunk) -> crate::Result<[u8; N]> {
+    chunk
+        .data()
+        .try_into()
+        .map_err(|_| MetadataError::WrongLength(chunk_type, N, chunk.data().len()).into())
+}
+
+#[derive(Debug)]
+pub enum MetadataError {
+    WrongLength(&'static str, usize, usize),
+    UnknownUnit(u8),
+    InvalidTime(PngTime),
+    UnknownRenderingIntent(u8),
+    InvalidPaletteLength(usize),
+}
+
+impl fmt::Display for MetadataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MetadataError::WrongLength(chunk_type, expected, actual) => write!(
+                f,
+                "{chunk_type} data must be {expected} bytes long, found {actual}"
+            ),
+            MetadataError::UnknownUnit(unit) => write!(f, "Unknown pHYs unit: {unit}"),
+            MetadataError::InvalidTime(time) => write!(f, "Invalid tIME timestamp: {time}"),
+            MetadataError::InvalidPaletteLength(len) => write!(
+                f,
+                "PLTE data must be a multiple of 3 bytes long with 1 to 256 entries, found {len} bytes"
+            ),
+            MetadataError::UnknownRenderingIntent(intent) => {
+                write!(f, "Unknown sRGB rendering intent: {intent}")
+            }
+        }
+    }
+}
+
+impl Error for MetadataError {}
+
+// ----------TESTS-------------//
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk_type::ChunkType;
+    use std::str::FromStr;
+
+    fn chunk(chunk_type: &str, data: Vec<u8>) -> Chunk {
+        Chunk::new(ChunkType::from_str(chunk_type).unwrap(), data)
+    }
+
+    #[test]
+    fn test_gamma() {
+        let gamma = Gamma::try_from(&chunk("gAMA", vec![0, 0, 177, 143])).unwrap();
+        assert_eq!(gamma.raw_value(), 45455);
+        assert_eq!(gamma.to_string(), "0.45455");
+
+        assert!(Gamma::try_from(&chunk("gAMA", vec![0, 0, 177])).is_err());
+    }
+
+    #[test]
+    fn test_physical_dimensions() {
+        let phys =
+            PhysicalDimensions::try_from(&chunk("pHYs", vec![0, 0, 11, 19, 0, 0, 11, 19, 1]))
+                .unwrap();
+        assert_eq!(phys.x_pixels_per_unit(), 2835);
+        assert_eq!(phys.unit(), PhysicalUnit::Meter);
+        assert_eq!(phys.to_string(), "72 DPI");
+
+        let phys =
+            PhysicalDimensions::try_from(&chunk("pHYs", vec![0, 0, 0, 2, 0, 0, 0, 1, 0])).unwrap();
+        assert_eq!(phys.dpi(), None);
+        assert_eq!(phys.to_string(), "pixel aspect ratio 2:1");
+
+        assert!(
+            PhysicalDimensions::try_from(&chunk("pHYs", vec![0, 0, 0, 2, 0, 0, 0, 1, 2])).is_err()
+        );
+    }
+
+    #[test]
+    fn test_time() {
+        let time = PngTime::try_from(&chunk("tIME", vec![7, 232, 1, 15, 10, 30, 0])).unwrap();
+        assert_eq!(time.year(), 2024);
+        assert_eq!(time.to_string(), "2024-01-15 10:30:00");
+
+        assert!(PngTime::try_from(&chunk("tIME", vec![7, 232, 13, 15, 10, 30, 0])).is_err());
+        assert!(PngTime::try_from(&chunk("tIME", vec![7, 232, 1, 15, 10, 30])).is_err());
+    }
+
+    #[test]
+    fn test_rendering_intent() {
+        let intent = RenderingIntent::try_from(&chunk("sRGB", vec![0])).unwrap();
+        assert_eq!(intent, RenderingIntent::Perceptual);
+        assert_eq!(intent.to_string(), "Perceptual");
+        assert_eq!(
+            RenderingIntent::try_from(&chunk("sRGB", vec![3])).unwrap(),
+            RenderingIntent::AbsoluteColorimetric
+        );
+
+        let err = RenderingIntent::try_from(&chunk("sRGB", vec![4])).unwrap_err();
+        assert_eq!(err.to_string(), "Unknown sRGB rendering intent: 4");
+        assert!(RenderingIntent::try_from(&chunk("sRGB", vec![0, 0])).is_err());
+    }
+
+    #[test]
+    fn test_background() {
+        let parse =
+            |data: Vec<u8>, color_type| Background::from_chunk(&chunk("bKGD", data), color_type);
+
+        assert_eq!(
+            parse(vec![7], ColorType::Indexed).unwrap(),
+            Background::PaletteIndex(7)
+        );
+        assert_eq!(
+            parse(vec![1, 0], ColorType::GrayscaleAlpha).unwrap(),
+            Background::Gray(256)
+        );
+        let rgb = parse(vec![0, 255, 0, 128, 0, 0], ColorType::Rgba).unwrap();
+        assert_eq!(rgb, Background::Rgb(255, 128, 0));
+        assert_eq!(rgb.to_string(), "RGB (255, 128, 0)");
+
+        // The same bytes are only valid for the color type they were written for.
+        let err = parse(vec![0, 255, 0, 128, 0, 0], ColorType::Grayscale).unwrap_err();
+        assert_eq!(err.to_string(), "bKGD data must be 2 bytes long, found 6");
+        assert!(parse(vec![7], ColorType::Rgb).is_err());
+        assert!(parse(vec![0, 7], ColorType::Indexed).is_err());
+    }
+
+    #[test]
+    fn test_palette_entries() {
+        let entries = palette_entries(&chunk("PLTE", vec![255, 0, 0, 0, 128, 255])).unwrap();
+        assert_eq!(entries, [[255, 0, 0], [0, 128, 255]]);
+        assert_eq!(
+            palette_entries(&chunk("PLTE", vec![7; 768])).unwrap().len(),
+            256
+        );
+
+        for len in [0, 4, 771] {
+            let err = palette_entries(&chunk("PLTE", vec![0; len])).unwrap_err();
+            assert!(
+                err.to_string().contains(&format!("found {len} bytes")),
+                "{err}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_chromaticities() {
+        // The sRGB values that encoders write alongside an sRGB chunk.
+        let values: [u32; 8] = [31270, 32900, 64000, 33000, 30000, 60000, 15000, 6000];
+        let data = values.iter().flat_map(|v| v.to_be_bytes()).collect();
+        let chrm = Chromaticities::try_from(&chunk("cHRM", data)).unwrap();
+        assert_eq!(chrm.white_point(), (0.3127, 0.329));
+        assert_eq!(chrm.blue(), (0.15, 0.06));
+        assert_eq!(
+            chrm.to_string(),
+            "white (0.3127, 0.3290), red (0.6400, 0.3300), green (0.3000, 0.6000), blue (0.1500, 0.0600)"
+        );
+
+        assert!(Chromaticities::try_from(&chunk("cHRM", vec![0; 31])).is_err());
+    }
+}
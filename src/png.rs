@@ -1,12 +1,24 @@
 #![allow(dead_code, unused_variables)]
 
-use crate::chunk::Chunk;
+use crate::apng::{AnimationControl, FrameControl};
+use crate::chunk::{Chunk, ChunkError, CrcAlgorithm, ParseOptions, MAXIMUM_LENGTH};
+use crate::format::{detect_format, DetectedFormat};
+use crate::header::PngHeader;
+use crate::lsb::{self, LsbError};
+use crate::metadata::{
+    self, Background, Chromaticities, Gamma, PhysicalDimensions, PngTime, RenderingIntent,
+};
+use bytes::Bytes;
+use log::{debug, info};
 use std::{
+    collections::BTreeMap,
     error::Error,
     fmt::{self, Display},
+    io::Write,
+    ops::Range,
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Png {
     chunks: Vec<Chunk>,
 }
@@ -15,96 +27,741 @@ impl TryFrom<&[u8]> for Png {
     type Error = crate::Error;
 
     fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
-        let header: [u8; 8] = value[..8].try_into()?;
+        Png::parse_with(value, &ParseOptions::default())
+    }
+}
+
+impl TryFrom<Vec<u8>> for Png {
+    type Error = crate::Error;
 
-        if !header.eq(&Png::STANDARD_HEADER) {
-            return Err(PngDecodeError::InvalidHeader.into());
+    fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
+        Png::parse_owned_with(value, &ParseOptions::default())
+    }
+}
+
+impl Display for Png {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for chunk in self.chunks.iter() {
+            write!(f, "{}", chunk)?;
         }
+        Ok(())
+    }
+}
+
+impl Default for Png {
+    fn default() -> Self {
+        Png::new()
+    }
+}
 
+impl Png {
+    const STANDARD_HEADER: [u8; 8] = [137, 80, 78, 71, 13, 10, 26, 10];
+
+    /// Returns whether `bytes` begins with the 8-byte PNG signature.
+    pub fn validate_signature(bytes: &[u8]) -> bool {
+        bytes.starts_with(&Png::STANDARD_HEADER)
+    }
+
+    /// Fails unless `bytes` begins with the PNG signature, naming the format
+    /// when it is recognizably some other kind of image.
+    fn check_signature(bytes: &[u8]) -> crate::Result<()> {
+        if Png::validate_signature(bytes) {
+            return Ok(());
+        }
+        Err(match detect_format(bytes) {
+            DetectedFormat::Png | DetectedFormat::Unknown => PngDecodeError::InvalidSignature,
+            format => PngDecodeError::WrongFormat(format),
+        }
+        .into())
+    }
+
+    /// Parses a PNG the way `try_from` does, but reading each chunk as
+    /// `options` say, e.g. checking CRCs with another algorithm.
+    pub fn parse_with(bytes: &[u8], options: &ParseOptions) -> crate::Result<Png> {
+        Png::check_signature(bytes)?;
+        Png::parse_shared(Bytes::copy_from_slice(bytes), options)
+    }
+
+    /// Like `parse_with`, but takes ownership of `bytes` so the chunks can
+    /// share it rather than each copying their data out.
+    pub fn parse_owned_with(bytes: Vec<u8>, options: &ParseOptions) -> crate::Result<Png> {
+        Png::check_signature(&bytes)?;
+        Png::parse_shared(Bytes::from(bytes), options)
+    }
+
+    /// Parses the chunks after the signature, which has already been checked.
+    /// Every chunk's data is a slice of `bytes`.
+    fn parse_shared(bytes: Bytes, options: &ParseOptions) -> crate::Result<Png> {
         let mut chunks: Vec<Chunk> = Vec::new();
         let mut cursor: usize = 8;
 
-        while cursor < value.len() {
-            let length = (u32::from_be_bytes(value[cursor..cursor + 4].try_into()?) + 12) as usize;
-
-            match Chunk::try_from(&value[cursor..cursor + length]) {
-                Ok(chunk) => {
+        while cursor < bytes.len() {
+            match Chunk::from_shared_prefix(&bytes.slice(cursor..), options) {
+                Ok((chunk, length)) => {
+                    debug!(
+                        "offset {cursor}: {} chunk, {} bytes, CRC {:#010x} ok",
+                        chunk.chunk_type(),
+                        chunk.length(),
+                        chunk.crc()
+                    );
                     chunks.push(chunk);
                     cursor += length;
                 }
-                Err(e) => return Err(e),
+                Err(e) => {
+                    debug!("offset {cursor}: failed to parse chunk: {e}");
+                    return Err(e);
+                }
             }
         }
 
+        info!("Parsed {} chunks from {} bytes", chunks.len(), bytes.len());
         Ok(Png { chunks })
     }
-}
 
-impl Display for Png {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for chunk in self.chunks.iter() {
-            write!(f, "{}", chunk)?;
+    /// Parses a PNG without checking chunk CRCs, keeping the stored values so
+    /// they can be audited with `Chunk::verify_crc`.
+    pub fn try_from_unchecked(bytes: &[u8]) -> crate::Result<Png> {
+        Png::try_from_unchecked_with(bytes, &ParseOptions::default())
+    }
+
+    /// Like `try_from_unchecked`, but reading each chunk as `options` say. The
+    /// CRC algorithm goes unused since no CRC is checked.
+    pub fn try_from_unchecked_with(bytes: &[u8], options: &ParseOptions) -> crate::Result<Png> {
+        Png::check_signature(bytes)?;
+
+        let buffer = Bytes::copy_from_slice(bytes);
+        let mut cursor = Png::STANDARD_HEADER.len();
+        let mut chunks = Vec::new();
+        loop {
+            match Chunk::from_shared_prefix_unchecked(&buffer.slice(cursor..), options) {
+                Ok((chunk, consumed)) => {
+                    chunks.push(chunk);
+                    cursor += consumed;
+                }
+                Err(crate::Error::Chunk(ChunkError::EndOfStream)) => break,
+                Err(e) => return Err(e),
+            }
         }
-        Ok(())
+        Ok(Png { chunks })
     }
-}
 
-impl Png {
-    const STANDARD_HEADER: [u8; 8] = [137, 80, 78, 71, 13, 10, 26, 10];
+    /// Parses as much of `bytes` as possible, collecting problems instead of
+    /// stopping at the first one. Chunks with a bad CRC are kept; after a chunk
+    /// that can't be read at all, parsing resumes at the next offset holding a
+    /// well-formed chunk.
+    pub fn try_from_lenient(bytes: &[u8]) -> (Png, Vec<PngError>) {
+        let mut errors = Vec::new();
+        if let Err(e) = Png::check_signature(bytes) {
+            errors.push(PngError::new(0, e));
+        }
+
+        let mut chunks = Vec::new();
+        let mut cursor = Png::STANDARD_HEADER.len().min(bytes.len());
+        while cursor < bytes.len() {
+            match Png::read_lenient(&bytes[cursor..]) {
+                Ok((chunk, consumed)) => {
+                    if !chunk.verify_crc() {
+                        let mut expected = chunk.clone();
+                        expected.recompute_crc();
+                        let error = ChunkError::InvalidCrc(expected.crc(), chunk.crc());
+                        errors.push(PngError::new(cursor, error.into()));
+                    }
+                    chunks.push(chunk);
+                    cursor += consumed;
+                }
+                Err(error) => {
+                    errors.push(PngError::new(cursor, error));
+                    match Png::resync(bytes, cursor + 1) {
+                        Some(next) => cursor = next,
+                        None => break,
+                    }
+                }
+            }
+        }
+
+        (Png { chunks }, errors)
+    }
+
+    /// Reads one chunk without checking its CRC, refusing lengths that run past
+    /// the end of `bytes`.
+    fn read_lenient(bytes: &[u8]) -> crate::Result<(Chunk, usize)> {
+        if bytes.len() < 12 {
+            return Err(ChunkError::ChunkTooSmall(bytes.len() as u32).into());
+        }
+        let length = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        if length as usize > bytes.len() - 12 {
+            return Err(ChunkError::InvalidLengthCmp(length, (bytes.len() - 12) as u32).into());
+        }
+
+        let mut reader = bytes;
+        let chunk = Chunk::from_reader_unchecked(&mut reader)?;
+        Ok((chunk, bytes.len() - reader.len()))
+    }
+
+    /// Finds the first offset from `start` where a complete chunk with a valid
+    /// CRC begins. Only offsets that pass the cheap `could_be_chunk` check are
+    /// parsed, so scanning garbage doesn't checksum the rest of the file at
+    /// every byte.
+    fn resync(bytes: &[u8], start: usize) -> Option<usize> {
+        (start..bytes.len()).find(|&offset| {
+            let rest = &bytes[offset..];
+            Png::could_be_chunk(rest)
+                && Png::read_lenient(rest).is_ok_and(|(chunk, _)| chunk.verify_crc())
+        })
+    }
+
+    /// Whether `bytes` starts with a length that fits in what's left and a type
+    /// made of four ASCII letters.
+    fn could_be_chunk(bytes: &[u8]) -> bool {
+        if bytes.len() < 12 {
+            return false;
+        }
+        let length = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        length <= MAXIMUM_LENGTH
+            && length as usize <= bytes.len() - 12
+            && bytes[4..8].iter().all(u8::is_ascii_alphabetic)
+    }
+
+    /// An empty PNG: just the signature, with no chunks. Nothing is validated;
+    /// call `validate_structure` once the chunks are in place.
+    pub fn new() -> Png {
+        Png { chunks: Vec::new() }
+    }
 
+    /// Builds a PNG from chunks in the order given, without validating them.
     pub fn from_chunks(chunks: Vec<Chunk>) -> Png {
         Png { chunks }
     }
 
-    pub fn append_chunk(&mut self, chunk: Chunk) {
+    /// Appends `chunk` to the end of the file. Chunks whose data is longer than
+    /// the spec allows, or that would land after an existing `IEND`, are
+    /// rejected; use `insert_chunk_before("IEND", ..)` for the latter.
+    pub fn append_chunk(&mut self, chunk: Chunk) -> crate::Result<()> {
+        Png::check_length(&chunk)?;
+        if self.chunk_by_type("IEND").is_some() {
+            return Err(PngDecodeError::ChunkAfterIend(chunk.chunk_type().to_string()).into());
+        }
+
         self.chunks.push(chunk);
+        Ok(())
     }
 
-    pub fn remove_chunk(&mut self, chunk_type: &str) -> crate::Result<Chunk> {
+    /// Inserts `chunk` directly before the first chunk of `target_type`, e.g. to
+    /// keep new chunks ahead of `IEND`.
+    pub fn insert_chunk_before(&mut self, target_type: &str, chunk: Chunk) -> crate::Result<()> {
         let idx = self
             .chunks
             .iter()
-            .position(|chunk| chunk.chunk_type().to_string() == chunk_type);
-        
-        match idx {
-            Some(index) => Ok(self.chunks.remove(index)),
-            None => Err(PngDecodeError::InvalidChunkTypeGiven(chunk_type.to_string()).into()),
+            .position(|chunk| chunk.chunk_type().as_str() == target_type)
+            .ok_or_else(|| PngDecodeError::ChunkTypeNotFound(target_type.to_string()))?;
+        Png::check_length(&chunk)?;
+
+        self.chunks.insert(idx, chunk);
+        Ok(())
+    }
+
+    fn check_length(chunk: &Chunk) -> crate::Result<()> {
+        if chunk.data().len() > MAXIMUM_LENGTH as usize {
+            return Err(PngDecodeError::ChunkTooLarge(chunk.data().len()).into());
+        }
+        Ok(())
+    }
+
+    pub fn remove_chunk(&mut self, chunk_type: &str) -> crate::Result<Chunk> {
+        self.take_chunk(chunk_type)
+            .ok_or_else(|| PngDecodeError::InvalidChunkTypeGiven(chunk_type.to_string()).into())
+    }
+
+    /// Like `remove_chunk`, but treats a missing chunk as `None` rather than
+    /// an error.
+    pub fn take_chunk(&mut self, chunk_type: &str) -> Option<Chunk> {
+        let index = self
+            .chunks
+            .iter()
+            .position(|chunk| chunk.chunk_type().as_str() == chunk_type)?;
+        Some(self.chunks.remove(index))
+    }
+
+    /// The position in the chunk list of the `nth` (zero-based) chunk of `chunk_type`.
+    pub fn find_chunk_index(&self, chunk_type: &str, nth: usize) -> Option<usize> {
+        self.chunks
+            .iter()
+            .enumerate()
+            .filter(|(_, chunk)| chunk.chunk_type().as_str() == chunk_type)
+            .nth(nth)
+            .map(|(index, _)| index)
+    }
+
+    /// Where the chunk at `index` starts in the written file: the 8-byte
+    /// signature plus the size of every chunk before it. `None` if there is no
+    /// chunk at `index`.
+    pub fn byte_offset_of(&self, index: usize) -> Option<u64> {
+        self.chunk_spans().nth(index).map(|span| span.start)
+    }
+
+    /// The chunk, and its index, whose bytes in the written file include
+    /// `offset`: its length, type, data or CRC. Offsets inside the 8-byte
+    /// signature or past the end of the file give `None`.
+    pub fn chunk_at_offset(&self, offset: u64) -> Option<(usize, &Chunk)> {
+        self.chunk_spans()
+            .position(|span| span.contains(&offset))
+            .map(|index| (index, &self.chunks[index]))
+    }
+
+    /// The range of bytes each chunk takes up in the written file, in order.
+    /// Sized by the data actually held, since that is what gets written.
+    fn chunk_spans(&self) -> impl Iterator<Item = Range<u64>> + '_ {
+        let signature = Png::STANDARD_HEADER.len() as u64;
+        self.chunks.iter().scan(signature, |start, chunk| {
+            let span = *start..*start + 12 + chunk.data_len() as u64;
+            *start = span.end;
+            Some(span)
+        })
+    }
+
+    pub fn remove_chunk_at(&mut self, index: usize) -> crate::Result<Chunk> {
+        if index >= self.chunks.len() {
+            return Err(PngDecodeError::IndexOutOfRange(index, self.chunks.len()).into());
+        }
+        Ok(self.chunks.remove(index))
+    }
+
+    /// Removes every chunk of `chunk_type`, returning them in their original order.
+    pub fn remove_all_chunks_by_type(&mut self, chunk_type: &str) -> Vec<Chunk> {
+        let (removed, kept) = std::mem::take(&mut self.chunks)
+            .into_iter()
+            .partition(|chunk| chunk.chunk_type().as_str() == chunk_type);
+        self.chunks = kept;
+        removed
+    }
+
+    /// Removes ancillary chunks that are byte-identical to an earlier chunk,
+    /// keeping the first copy, and returns how many were dropped. Critical
+    /// chunks like `IDAT` legitimately repeat, so they are never removed.
+    pub fn deduplicate_chunks(&mut self) -> usize {
+        self.deduplicate_where(|_| true)
+    }
+
+    /// Like `deduplicate_chunks`, but only considers chunks of `chunk_type`.
+    pub fn deduplicate_chunks_of_type(&mut self, chunk_type: &str) -> usize {
+        self.deduplicate_where(|chunk| chunk.chunk_type().as_str() == chunk_type)
+    }
+
+    fn deduplicate_where(&mut self, candidate: impl Fn(&Chunk) -> bool) -> usize {
+        let before = self.chunks.len();
+        let mut kept: Vec<Chunk> = Vec::with_capacity(before);
+        for chunk in std::mem::take(&mut self.chunks) {
+            if chunk.is_ancillary() && candidate(&chunk) && kept.contains(&chunk) {
+                debug!("Dropping duplicate {} chunk", chunk.chunk_type());
+                continue;
+            }
+            kept.push(chunk);
+        }
+        self.chunks = kept;
+        before - self.chunks.len()
+    }
+
+    /// Checks the chunk layout required by the PNG spec: a single `IHDR` first, a
+    /// single `IEND` last, and all `IDAT` chunks next to each other.
+    pub fn validate_structure(&self) -> crate::Result<()> {
+        let types: Vec<&str> = self
+            .chunks
+            .iter()
+            .map(|chunk| chunk.chunk_type().as_str())
+            .collect();
+        let count = |chunk_type: &str| types.iter().filter(|&&t| t == chunk_type).count();
+
+        match count("IHDR") {
+            0 => return Err(PngStructureError::MissingChunk("IHDR").into()),
+            1 => {}
+            n => return Err(PngStructureError::DuplicateChunk("IHDR", n).into()),
+        }
+        match count("IEND") {
+            0 => return Err(PngStructureError::MissingChunk("IEND").into()),
+            1 => {}
+            n => return Err(PngStructureError::DuplicateChunk("IEND", n).into()),
+        }
+        if types[0] != "IHDR" {
+            return Err(PngStructureError::IhdrNotFirst.into());
+        }
+        if types[types.len() - 1] != "IEND" {
+            return Err(PngStructureError::IendNotLast(types.len() - 1).into());
         }
+
+        let idat: Vec<usize> = (0..types.len()).filter(|&i| types[i] == "IDAT").collect();
+        if let Some(gap) = idat.windows(2).find(|pair| pair[1] != pair[0] + 1) {
+            return Err(PngStructureError::IdatNotContiguous(gap[0] + 1).into());
+        }
+
+        Ok(())
     }
 
-    pub fn header(&self) -> &[u8; 8] {
+    /// Reorders the chunks into a layout `validate_structure` accepts: `IHDR`
+    /// first, then the chunks the spec wants before `PLTE` (`cHRM`, `gAMA`,
+    /// `iCCP`, `sBIT`, `sRGB`, `cICP`), `PLTE`, the ones it wants between
+    /// `PLTE` and `IDAT` (`tRNS`, `bKGD`, `hIST`), the other chunks that must
+    /// precede `IDAT`, every `IDAT` together, and `IEND` last. Any other chunk
+    /// stays on the side of the image data it was found on, with chunks after
+    /// `IEND` moved in front of it, and chunks keep their relative order within
+    /// each group. Fails without changing anything if `IHDR` or `IEND` is
+    /// missing or repeated.
+    pub fn canonicalize(&mut self) -> crate::Result<()> {
+        let first_idat = self
+            .chunks
+            .iter()
+            .position(|chunk| chunk.chunk_type().as_str() == "IDAT")
+            .unwrap_or(self.chunks.len());
+        let mut ranked: Vec<(u8, Chunk)> = self
+            .chunks
+            .iter()
+            .enumerate()
+            .map(|(index, chunk)| {
+                let rank = match chunk.chunk_type().as_str() {
+                    "IHDR" => 0,
+                    "cHRM" | "gAMA" | "iCCP" | "sBIT" | "sRGB" | "cICP" => 1,
+                    "PLTE" => 2,
+                    "tRNS" | "bKGD" | "hIST" => 3,
+                    "pHYs" | "sPLT" | "eXIf" | "acTL" => 4,
+                    "IDAT" => 5,
+                    "IEND" => 7,
+                    _ if index < first_idat => 4,
+                    _ => 6,
+                };
+                (rank, chunk.clone())
+            })
+            .collect();
+        ranked.sort_by_key(|(rank, _)| *rank);
+
+        let canonical = Png::from_chunks(ranked.into_iter().map(|(_, chunk)| chunk).collect());
+        canonical.validate_structure()?;
+        *self = canonical;
+        Ok(())
+    }
+
+    /// A copy with only the public critical chunks, `IHDR`, `PLTE`, `IDAT` and
+    /// `IEND`: everything a decoder needs to show the image and nothing else.
+    pub fn baseline(&self) -> Png {
+        Png::from_chunks(
+            self.chunks
+                .iter()
+                .filter(|chunk| chunk.is_critical() && chunk.chunk_type().is_public())
+                .cloned()
+                .collect(),
+        )
+    }
+
+    /// The 8-byte signature written ahead of the chunks. Parsing rejects any
+    /// other signature, so this is always exactly the bytes that were read.
+    pub fn signature(&self) -> &[u8; 8] {
         &Png::STANDARD_HEADER
     }
 
+    /// Parses the image properties out of the `IHDR` chunk.
+    pub fn header(&self) -> crate::Result<PngHeader> {
+        let ihdr = self
+            .chunk_by_type("IHDR")
+            .ok_or_else(|| PngDecodeError::ChunkTypeNotFound("IHDR".to_string()))?;
+        PngHeader::try_from(ihdr)
+    }
+
+    /// Parses the `gAMA` chunk, if there is one.
+    pub fn gamma(&self) -> Option<crate::Result<Gamma>> {
+        self.chunk_by_type("gAMA").map(Gamma::try_from)
+    }
+
+    /// Parses the `pHYs` chunk, if there is one.
+    pub fn physical_dimensions(&self) -> Option<crate::Result<PhysicalDimensions>> {
+        self.chunk_by_type("pHYs").map(PhysicalDimensions::try_from)
+    }
+
+    /// Parses the `tIME` chunk, if there is one.
+    pub fn time(&self) -> Option<crate::Result<PngTime>> {
+        self.chunk_by_type("tIME").map(PngTime::try_from)
+    }
+
+    /// Parses the rendering intent of the `sRGB` chunk, if there is one.
+    pub fn srgb(&self) -> Option<crate::Result<RenderingIntent>> {
+        self.chunk_by_type("sRGB").map(RenderingIntent::try_from)
+    }
+
+    /// Parses the `bKGD` chunk, if there is one, according to the color type
+    /// in `IHDR`.
+    pub fn background(&self) -> Option<crate::Result<Background>> {
+        self.chunk_by_type("bKGD")
+            .map(|chunk| Background::from_chunk(chunk, self.header()?.color_type()))
+    }
+
+    /// Parses the `PLTE` chunk into RGB triples, if there is one.
+    pub fn palette(&self) -> Option<crate::Result<Vec<[u8; 3]>>> {
+        self.chunk_by_type("PLTE").map(metadata::palette_entries)
+    }
+
+    /// Parses the `cHRM` chunk, if there is one.
+    pub fn chromaticities(&self) -> Option<crate::Result<Chromaticities>> {
+        self.chunk_by_type("cHRM").map(Chromaticities::try_from)
+    }
+
+    /// Parses the `acTL` chunk, if there is one. Only APNGs have it.
+    pub fn animation_control(&self) -> Option<crate::Result<AnimationControl>> {
+        self.chunk_by_type("acTL").map(AnimationControl::try_from)
+    }
+
+    /// Parses every `fcTL` chunk, in file order.
+    pub fn frame_controls(&self) -> crate::Result<Vec<FrameControl>> {
+        self.chunks_by_type("fcTL")
+            .into_iter()
+            .map(FrameControl::try_from)
+            .collect()
+    }
+
+    /// How much a message could hold if embedded in this file: as a new chunk,
+    /// and in the pixels' low bits when the image format allows it.
+    pub fn embedding_capacity(&self) -> crate::Result<EmbedCapacity> {
+        let pixels = match lsb::capacity(self) {
+            Ok(capacity) => Some(capacity),
+            Err(crate::Error::Lsb(LsbError::Unsupported(_))) => None,
+            Err(e) => return Err(e),
+        };
+        Ok(EmbedCapacity {
+            chunk: MAXIMUM_LENGTH as usize,
+            pixels,
+        })
+    }
+
     pub fn chunks(&self) -> &[Chunk] {
         &self.chunks
     }
 
+    pub fn iter(&self) -> Chunks<'_> {
+        Chunks {
+            inner: self.chunks.iter(),
+        }
+    }
+
     pub fn chunk_by_type(&self, chunk_type: &str) -> Option<&Chunk> {
         self.chunks
             .iter()
-            .find(|chunk: &&Chunk| chunk.chunk_type().to_string() == chunk_type)
+            .find(|chunk: &&Chunk| chunk.chunk_type().as_str() == chunk_type)
     }
 
-    pub fn as_bytes(&self) -> Vec<u8> {
-        let chunk_iters: Vec<u8> = self
-            .chunks
-            .iter()
-            .flat_map(|chunk| chunk.as_bytes())
-            .collect();
+    /// Replaces the data of the first chunk of `chunk_type`, keeping its
+    /// position and updating its length and CRC.
+    pub fn replace_chunk_data(&mut self, chunk_type: &str, data: Vec<u8>) -> crate::Result<()> {
+        self.chunks
+            .iter_mut()
+            .find(|chunk| chunk.chunk_type().as_str() == chunk_type)
+            .ok_or_else(|| PngDecodeError::ChunkTypeNotFound(chunk_type.to_string()))?
+            .set_data(data)
+    }
+
+    /// Puts `new` in place of the first chunk of `chunk_type` and returns the
+    /// chunk it replaced. If there is none, `new` is added ahead of `IEND`, or
+    /// at the end when there is no `IEND`.
+    pub fn replace_chunk(&mut self, chunk_type: &str, new: Chunk) -> Option<Chunk> {
+        let position = |chunk_type: &str| {
+            self.chunks
+                .iter()
+                .position(|chunk| chunk.chunk_type().as_str() == chunk_type)
+        };
+        match position(chunk_type) {
+            Some(index) => Some(std::mem::replace(&mut self.chunks[index], new)),
+            None => {
+                let index = position("IEND").unwrap_or(self.chunks.len());
+                self.chunks.insert(index, new);
+                None
+            }
+        }
+    }
 
-        self.header()
+    /// Recomputes every chunk's CRC with `algorithm`, e.g. to write a file
+    /// for a tool that expects a non-standard variant.
+    pub fn recompute_crcs_with(&mut self, algorithm: CrcAlgorithm) {
+        for chunk in &mut self.chunks {
+            chunk.recompute_crc_with(algorithm);
+        }
+    }
+
+    pub fn chunks_by_type(&self, chunk_type: &str) -> Vec<&Chunk> {
+        self.chunks
             .iter()
-            .chain(chunk_iters.iter())
-            .copied()
+            .filter(|chunk| chunk.chunk_type().as_str() == chunk_type)
             .collect()
     }
+
+    /// The size of the encoded file in bytes: the signature plus each chunk's
+    /// 12 bytes of length, type and CRC and its data. Equal to `as_bytes().len()`.
+    pub fn total_size(&self) -> u64 {
+        Png::STANDARD_HEADER.len() as u64 + self.size_by_type().values().sum::<u64>()
+    }
+
+    /// The encoded bytes taken up by the chunks of each type, including the 12
+    /// bytes of per-chunk overhead.
+    pub fn size_by_type(&self) -> BTreeMap<String, u64> {
+        let mut sizes = BTreeMap::new();
+        for chunk in &self.chunks {
+            *sizes.entry(chunk.chunk_type().to_string()).or_default() +=
+                12 + chunk.data_len() as u64;
+        }
+        sizes
+    }
+
+    /// Streams the signature and every chunk to `writer`, so the whole file never
+    /// has to be held in memory at once.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> crate::Result<()> {
+        writer.write_all(self.signature())?;
+        for chunk in &self.chunks {
+            chunk.write_to(writer)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the PNG out and parses the bytes back, checking that every chunk
+    /// comes back equal to the original with a valid CRC. The error names the
+    /// first chunk that did not.
+    pub fn round_trip_ok(&self) -> crate::Result<()> {
+        self.round_trip_ok_with(CrcAlgorithm::default())
+    }
+
+    /// Like `round_trip_ok`, but checks CRCs with `algorithm`.
+    pub fn round_trip_ok_with(&self, algorithm: CrcAlgorithm) -> crate::Result<()> {
+        let reparsed = Png::try_from_unchecked(&self.as_bytes())?;
+        let count = self.chunks.len().max(reparsed.chunks.len());
+        for index in 0..count {
+            let (original, reread) = (self.chunks.get(index), reparsed.chunks.get(index));
+            if original == reread && reread.is_some_and(|chunk| chunk.verify_crc_with(algorithm)) {
+                continue;
+            }
+            let chunk_type = original
+                .or(reread)
+                .map(|chunk| chunk.chunk_type().to_string());
+            return Err(PngDecodeError::RoundTripFailed(index, chunk_type.unwrap()).into());
+        }
+        Ok(())
+    }
+
+    /// The encoded file as a single buffer, sized up front from `total_size` so
+    /// every chunk's data is copied exactly once.
+    pub fn as_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.total_size() as usize);
+        self.write_to(&mut bytes)
+            .expect("writing to a Vec cannot fail");
+        bytes
+    }
+}
+
+/// An iterator over the chunks of a `Png`, created by `Png::iter`.
+#[derive(Debug, Clone)]
+pub struct Chunks<'a> {
+    inner: std::slice::Iter<'a, Chunk>,
+}
+
+impl<'a> Iterator for Chunks<'a> {
+    type Item = &'a Chunk;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for Chunks<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+impl ExactSizeIterator for Chunks<'_> {}
+
+impl<'a> IntoIterator for &'a Png {
+    type Item = &'a Chunk;
+    type IntoIter = Chunks<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the owned chunks of a `Png`, created by `Png::into_iter`.
+#[derive(Debug, Clone)]
+pub struct IntoChunks {
+    inner: std::vec::IntoIter<Chunk>,
+}
+
+impl Iterator for IntoChunks {
+    type Item = Chunk;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for IntoChunks {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+impl ExactSizeIterator for IntoChunks {}
+
+impl IntoIterator for Png {
+    type Item = Chunk;
+    type IntoIter = IntoChunks;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoChunks {
+            inner: self.chunks.into_iter(),
+        }
+    }
+}
+
+/// The number of message bytes a `Png` can hold, from `Png::embedding_capacity`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmbedCapacity {
+    chunk: usize,
+    pixels: Option<usize>,
+}
+
+impl EmbedCapacity {
+    /// The most data a single new chunk can carry.
+    pub fn chunk(&self) -> usize {
+        self.chunk
+    }
+
+    /// The bytes `hide` can store in pixel LSBs, or `None` if the image's
+    /// format is not supported for pixel hiding.
+    pub fn pixels(&self) -> Option<usize> {
+        self.pixels
+    }
+}
+
+impl Display for EmbedCapacity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} bytes in a new chunk, ", self.chunk)?;
+        match self.pixels {
+            Some(pixels) => write!(f, "{pixels} bytes in pixel LSBs"),
+            None => write!(f, "pixel hiding not supported for this image"),
+        }
+    }
 }
 
 #[derive(Debug)]
 pub enum PngDecodeError {
-    InvalidHeader,
+    InvalidSignature,
+    WrongFormat(DetectedFormat),
     InvalidChunkTypeGiven(String),
+    ChunkTypeNotFound(String),
+    ChunkTooLarge(usize),
+    ChunkAfterIend(String),
+    IndexOutOfRange(usize, usize),
+    NthChunkNotFound(String, usize),
+    RoundTripFailed(usize, String),
 }
 
 impl Error for PngDecodeError {}
@@ -112,10 +769,103 @@ impl Error for PngDecodeError {}
 impl fmt::Display for PngDecodeError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            PngDecodeError::InvalidHeader => write!(f, "Invalid Header"),
+            PngDecodeError::InvalidSignature => {
+                write!(f, "Not a PNG file: missing or invalid PNG signature")
+            }
+            PngDecodeError::WrongFormat(format) => {
+                write!(f, "Not a PNG file: this looks like a {format} image")
+            }
             PngDecodeError::InvalidChunkTypeGiven(s) => write!(
                 f,
-                "The chunk type {s}, could not be removed because it does not exisit"
+                "The chunk type {s}, could not be removed because it does not exisit"
+            ),
+            PngDecodeError::ChunkTypeNotFound(s) => {
+                write!(f, "No chunk of type {s} was found")
+            }
+            PngDecodeError::ChunkTooLarge(length) => write!(
+                f,
+                "Chunk data is {length} bytes, more than the maximum of {MAXIMUM_LENGTH}"
+            ),
+            PngDecodeError::ChunkAfterIend(s) => {
+                write!(f, "Cannot append a {s} chunk after IEND")
+            }
+            PngDecodeError::IndexOutOfRange(index, len) => {
+                write!(f, "Chunk index {index} is out of range for {len} chunks")
+            }
+            PngDecodeError::NthChunkNotFound(s, nth) => {
+                write!(f, "There is no chunk of type {s} at index {nth}")
+            }
+            PngDecodeError::RoundTripFailed(index, s) => write!(
+                f,
+                "Self-check failed: chunk {index} ({s}) does not read back as written"
+            ),
+        }
+    }
+}
+
+/// A problem found by `Png::try_from_lenient`, with the byte offset where it
+/// occurred.
+#[derive(Debug)]
+pub struct PngError {
+    offset: usize,
+    error: crate::Error,
+}
+
+impl PngError {
+    fn new(offset: usize, error: crate::Error) -> PngError {
+        PngError { offset, error }
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn error(&self) -> &crate::Error {
+        &self.error
+    }
+}
+
+impl fmt::Display for PngError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "At offset {}: {}", self.offset, self.error)
+    }
+}
+
+impl Error for PngError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+#[derive(Debug)]
+pub enum PngStructureError {
+    MissingChunk(&'static str),
+    DuplicateChunk(&'static str, usize),
+    IhdrNotFirst,
+    IendNotLast(usize),
+    IdatNotContiguous(usize),
+}
+
+impl Error for PngStructureError {}
+
+impl fmt::Display for PngStructureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PngStructureError::MissingChunk(chunk_type) => {
+                write!(f, "The PNG has no {chunk_type} chunk")
+            }
+            PngStructureError::DuplicateChunk(chunk_type, count) => write!(
+                f,
+                "The PNG has {count} {chunk_type} chunks, but exactly one is allowed"
+            ),
+            PngStructureError::IhdrNotFirst => write!(f, "IHDR is not the first chunk"),
+            PngStructureError::IendNotLast(index) => write!(
+                f,
+                "IEND is not the last chunk, there are chunks after it (index {index} is last)"
+            ),
+            PngStructureError::IdatNotContiguous(index) => write!(
+                f,
+                "IDAT chunks are not contiguous, a different chunk is at index {index}"
             ),
         }
     }
@@ -141,6 +891,12 @@ mod tests {
         Png::from_chunks(chunks)
     }
 
+    fn chunk_from_bytes(chunk_type: &str, data: &[u8]) -> Chunk {
+        use std::str::FromStr;
+
+        Chunk::new(ChunkType::from_str(chunk_type).unwrap(), data.to_vec())
+    }
+
     fn chunk_from_strings(chunk_type: &str, data: &str) -> crate::Result<Chunk> {
         use std::str::FromStr;
 
@@ -158,6 +914,33 @@ mod tests {
         assert_eq!(png.chunks().len(), 3);
     }
 
+    #[test]
+    fn test_new() {
+        let mut png = Png::new();
+        assert!(png.chunks().is_empty());
+        assert_eq!(png.as_bytes(), Png::STANDARD_HEADER);
+        assert!(png.validate_structure().is_err());
+
+        let ihdr = [0, 0, 0, 1, 0, 0, 0, 1, 8, 0, 0, 0, 0];
+        png.append_chunk(chunk_from_bytes("IHDR", &ihdr)).unwrap();
+        png.append_chunk(chunk_from_bytes("IDAT", &[])).unwrap();
+        png.append_chunk(chunk_from_bytes("IEND", &[])).unwrap();
+        assert!(png.validate_structure().is_ok());
+        assert_eq!(Png::try_from(png.as_bytes().as_slice()).unwrap(), png);
+    }
+
+    #[test]
+    fn test_clone_is_independent() {
+        let original = testing_png();
+        let mut edited = original.clone();
+        assert_eq!(edited, original);
+
+        edited.remove_chunk("FrSt").unwrap();
+        assert_ne!(edited, original);
+        assert_eq!(original.chunks().len(), 3);
+        assert_eq!(original, testing_png());
+    }
+
     #[test]
     fn test_valid_from_bytes() {
         let chunk_bytes: Vec<u8> = testing_chunks()
@@ -194,6 +977,43 @@ mod tests {
         assert!(png.is_err());
     }
 
+    #[test]
+    fn test_validate_signature() {
+        assert!(Png::validate_signature(&PNG_FILE));
+        assert!(!Png::validate_signature(&PNG_FILE[..7]));
+        assert!(!Png::validate_signature(&[
+            0xff, 0xd8, 0xff, 0xe0, 0, 16, 74, 70
+        ]));
+    }
+
+    #[test]
+    fn test_other_formats_are_named() {
+        let jpeg = [0xff, 0xd8, 0xff, 0xe0, 0, 16, 74, 70, 73, 70, 0];
+        for err in [
+            Png::try_from(&jpeg[..]).unwrap_err(),
+            Png::try_from_unchecked(&jpeg).unwrap_err(),
+            Png::try_from_lenient(&jpeg).1.remove(0).error,
+        ] {
+            assert!(matches!(
+                err,
+                crate::Error::Decode(PngDecodeError::WrongFormat(DetectedFormat::Jpeg))
+            ));
+            assert_eq!(
+                err.to_string(),
+                "Not a PNG file: this looks like a JPEG image"
+            );
+        }
+    }
+
+    #[test]
+    fn test_short_input_is_invalid_signature() {
+        let err = Png::try_from(&PNG_FILE[..4]).unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::Decode(PngDecodeError::InvalidSignature)
+        ));
+    }
+
     #[test]
     fn test_invalid_chunk() {
         let mut chunk_bytes: Vec<u8> = testing_chunks()
@@ -216,6 +1036,190 @@ mod tests {
         assert!(png.is_err());
     }
 
+    #[test]
+    fn test_forged_length_is_rejected() {
+        let mut bytes = Png::STANDARD_HEADER.to_vec();
+        bytes.extend_from_slice(&MAXIMUM_LENGTH.to_be_bytes());
+        bytes.extend_from_slice(b"IDAT\0\0\0\0");
+        let err = Png::try_from(bytes.as_slice()).unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::Chunk(ChunkError::LengthExceedsInput(..))
+        ));
+        assert!(Png::try_from_unchecked(&bytes).is_err());
+
+        // A few stray bytes after the signature used to panic while slicing.
+        let mut bytes = Png::STANDARD_HEADER.to_vec();
+        bytes.extend_from_slice(&[0, 0]);
+        assert!(Png::try_from(bytes.as_slice()).is_err());
+    }
+
+    #[test]
+    fn test_try_from_unchecked() {
+        let mut bytes = PNG_FILE.to_vec();
+        // flip a bit in the IHDR CRC
+        bytes[32] ^= 1;
+
+        assert!(Png::try_from(bytes.as_slice()).is_err());
+
+        let png = Png::try_from_unchecked(&bytes).unwrap();
+        assert_eq!(
+            png.chunks().len(),
+            Png::try_from(&PNG_FILE[..]).unwrap().chunks().len()
+        );
+        assert!(!png.chunks()[0].verify_crc());
+        assert!(png.chunks()[1..].iter().all(|chunk| chunk.verify_crc()));
+    }
+
+    #[test]
+    fn test_try_from_lenient() {
+        let (png, errors) = Png::try_from_lenient(&PNG_FILE);
+        assert!(errors.is_empty());
+        assert_eq!(png.as_bytes(), PNG_FILE);
+
+        let mut bytes = PNG_FILE.to_vec();
+        // flip a bit in the IHDR CRC
+        bytes[32] ^= 1;
+        // and corrupt the length of the sRGB chunk that follows
+        bytes[33] = 0xff;
+
+        let (png, errors) = Png::try_from_lenient(&bytes);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].offset(), 8);
+        assert!(matches!(
+            errors[0].error(),
+            crate::Error::Chunk(ChunkError::InvalidCrc(..))
+        ));
+        assert_eq!(errors[1].offset(), 33);
+
+        let types: Vec<&str> = png
+            .iter()
+            .map(|chunk| chunk.chunk_type().as_str())
+            .collect();
+        assert_eq!(types, ["IHDR", "gAMA", "pHYs", "IDAT", "RuSt", "IEND"]);
+    }
+
+    #[test]
+    fn test_could_be_chunk() {
+        assert!(Png::could_be_chunk(&PNG_FILE[8..]));
+        assert!(Png::could_be_chunk(&[
+            0, 0, 0, 0, b'I', b'E', b'N', b'D', 0, 0, 0, 0
+        ]));
+        // too short, length past the end, and a type that isn't all letters
+        assert!(!Png::could_be_chunk(&[0, 0, 0, 0, b'I', b'E', b'N', b'D']));
+        assert!(!Png::could_be_chunk(&[
+            0, 0, 0, 1, b'I', b'E', b'N', b'D', 0, 0, 0, 0
+        ]));
+        assert!(!Png::could_be_chunk(&[
+            0, 0, 0, 0, b'I', b'E', b'N', b'1', 0, 0, 0, 0
+        ]));
+    }
+
+    #[test]
+    fn test_try_from_lenient_skips_garbage() {
+        let mut bytes = PNG_FILE[..33].to_vec();
+        // A megabyte of a length field followed by non-letters, then the rest.
+        bytes.extend([0x00, 0x00, 0x01, 0x00, 0xff, 0xff, 0xff, 0xff].repeat(1 << 17));
+        bytes.extend(&PNG_FILE[33..]);
+
+        let (png, errors) = Png::try_from_lenient(&bytes);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].offset(), 33);
+        assert_eq!(png.as_bytes(), PNG_FILE);
+    }
+
+    #[test]
+    fn test_metadata() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        assert_eq!(png.gamma().unwrap().unwrap().to_string(), "0.45455");
+        assert!(png.physical_dimensions().unwrap().is_ok());
+        assert!(png.time().is_none());
+        assert_eq!(png.srgb().unwrap().unwrap(), RenderingIntent::Perceptual);
+        assert!(png.chromaticities().is_none());
+        assert!(png.background().is_none());
+        assert!(png.palette().is_none());
+    }
+
+    #[test]
+    fn test_animation() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        assert!(png.animation_control().is_none());
+        assert!(png.frame_controls().unwrap().is_empty());
+
+        let mut fctl = vec![0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 1];
+        fctl.extend([0; 8]);
+        fctl.extend([0, 1, 0, 10, 0, 0]);
+        let png = Png::from_chunks(vec![
+            chunk_from_bytes("acTL", &[0, 0, 0, 2, 0, 0, 0, 0]),
+            chunk_from_bytes("fcTL", &fctl),
+        ]);
+        assert_eq!(
+            png.animation_control().unwrap().unwrap().to_string(),
+            "2 frames, loops forever"
+        );
+        assert_eq!(png.frame_controls().unwrap()[0].delay(), 0.1);
+    }
+
+    #[test]
+    fn test_palette() {
+        let png = Png::from_chunks(vec![chunk_from_bytes("PLTE", &[1, 2, 3, 4, 5, 6])]);
+        assert_eq!(png.palette().unwrap().unwrap(), [[1, 2, 3], [4, 5, 6]]);
+
+        let png = Png::from_chunks(vec![chunk_from_bytes("PLTE", &[1, 2, 3, 4])]);
+        assert!(png.palette().unwrap().is_err());
+    }
+
+    #[test]
+    fn test_background() {
+        use crate::metadata::Background;
+
+        let ihdr = |color_type| {
+            chunk_from_bytes("IHDR", &[0, 0, 0, 1, 0, 0, 0, 1, 8, color_type, 0, 0, 0])
+        };
+        let bkgd = chunk_from_bytes("bKGD", &[0, 1]);
+
+        let png = Png::from_chunks(vec![ihdr(0), bkgd.clone()]);
+        assert_eq!(png.background().unwrap().unwrap(), Background::Gray(1));
+
+        // Two bytes are a gray level, not an RGB triple.
+        let png = Png::from_chunks(vec![ihdr(2), bkgd.clone()]);
+        assert!(png.background().unwrap().is_err());
+
+        let png = Png::from_chunks(vec![bkgd]);
+        assert!(matches!(
+            png.background().unwrap().unwrap_err(),
+            crate::Error::Decode(PngDecodeError::ChunkTypeNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_embedding_capacity() {
+        use std::str::FromStr;
+
+        let rgb = [0, 0, 0, 8, 0, 0, 0, 8, 8, 2, 0, 0, 0];
+        let png = Png::from_chunks(vec![
+            Chunk::new(ChunkType::from_str("IHDR").unwrap(), rgb.to_vec()),
+            Chunk::new(ChunkType::from_str("IEND").unwrap(), vec![]),
+        ]);
+        let capacity = png.embedding_capacity().unwrap();
+        assert_eq!(capacity.chunk(), MAXIMUM_LENGTH as usize);
+        assert_eq!(capacity.pixels(), Some(20));
+        assert_eq!(
+            capacity.to_string(),
+            "2147483647 bytes in a new chunk, 20 bytes in pixel LSBs"
+        );
+
+        let mut indexed = rgb;
+        indexed[9] = 3;
+        let png = Png::from_chunks(vec![
+            Chunk::new(ChunkType::from_str("IHDR").unwrap(), indexed.to_vec()),
+            Chunk::new(ChunkType::from_str("IEND").unwrap(), vec![]),
+        ]);
+        assert_eq!(png.embedding_capacity().unwrap().pixels(), None);
+
+        assert!(testing_png().embedding_capacity().is_err());
+    }
+
     #[test]
     fn test_list_chunks() {
         let png = testing_png();
@@ -223,6 +1227,89 @@ mod tests {
         assert_eq!(chunks.len(), 3);
     }
 
+    #[test]
+    fn test_total_size() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        assert_eq!(png.total_size(), PNG_FILE.len() as u64);
+
+        let png = testing_png();
+        assert_eq!(png.total_size(), png.as_bytes().len() as u64);
+    }
+
+    #[test]
+    fn test_total_size_with_mismatched_length() {
+        use std::str::FromStr;
+
+        // The stored length claims 100 bytes, but only the 4 of data are written.
+        let chunk_type = ChunkType::from_str("ruSt").unwrap();
+        let mismatched = Chunk::new_with_all_fields(100, chunk_type, b"data".to_vec(), 0);
+        let png = Png::from_chunks(vec![mismatched]);
+
+        assert_eq!(png.total_size(), png.as_bytes().len() as u64);
+        assert_eq!(png.size_by_type()["ruSt"], 12 + 4);
+    }
+
+    #[test]
+    fn test_size_by_type() {
+        let mut png = testing_png();
+        png.append_chunk(chunk_from_strings("FrSt", "Again").unwrap())
+            .unwrap();
+
+        let sizes = png.size_by_type();
+        assert_eq!(sizes.len(), 3);
+        assert_eq!(sizes["FrSt"], 12 + 20 + 12 + 5);
+        assert_eq!(sizes["LASt"], 12 + 19);
+    }
+
+    #[test]
+    fn test_as_bytes_allocates_once() {
+        let png = testing_png();
+        let bytes = png.as_bytes();
+        assert_eq!(bytes.len() as u64, png.total_size());
+        assert_eq!(bytes.capacity(), bytes.len());
+    }
+
+    #[test]
+    fn test_write_to() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let mut written = Vec::new();
+        png.write_to(&mut written).unwrap();
+        assert_eq!(written, PNG_FILE);
+    }
+
+    #[test]
+    fn test_iter() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        assert_eq!(png.iter().len(), png.chunks().len());
+
+        let critical: Vec<String> = png
+            .iter()
+            .filter(|chunk| chunk.chunk_type().is_critical())
+            .map(|chunk| chunk.chunk_type().to_string())
+            .collect();
+        assert_eq!(critical, ["IHDR", "IDAT", "RuSt", "IEND"]);
+
+        let last = (&png).into_iter().next_back().unwrap();
+        assert_eq!(&last.chunk_type().to_string(), "IEND");
+    }
+
+    #[test]
+    fn test_into_iter_owned() {
+        let png = testing_png();
+        let mut chunks = png.clone().into_iter();
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks.next_back().unwrap().chunk_type().as_str(), "LASt");
+
+        // Parse, transform and rebuild without cloning any chunk.
+        let rebuilt = Png::from_chunks(
+            png.into_iter()
+                .filter(|chunk| chunk.chunk_type().as_str() != "miDl")
+                .collect(),
+        );
+        let types: Vec<&str> = rebuilt.iter().map(|c| c.chunk_type().as_str()).collect();
+        assert_eq!(types, ["FrSt", "LASt"]);
+    }
+
     #[test]
     fn test_chunk_by_type() {
         let png = testing_png();
@@ -231,24 +1318,359 @@ mod tests {
         assert_eq!(&chunk.data_as_string().unwrap(), "I am the first chunk");
     }
 
+    #[test]
+    fn test_chunks_by_type() {
+        let mut png = testing_png();
+        png.append_chunk(chunk_from_strings("FrSt", "I am another first chunk").unwrap())
+            .unwrap();
+
+        let chunks = png.chunks_by_type("FrSt");
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(&chunks[0].data_as_string().unwrap(), "I am the first chunk");
+        assert_eq!(
+            &chunks[1].data_as_string().unwrap(),
+            "I am another first chunk"
+        );
+        assert!(png.chunks_by_type("NoNe").is_empty());
+    }
+
     #[test]
     fn test_append_chunk() {
         let mut png = testing_png();
-        png.append_chunk(chunk_from_strings("TeSt", "Message").unwrap());
+        png.append_chunk(chunk_from_strings("TeSt", "Message").unwrap())
+            .unwrap();
         let chunk = png.chunk_by_type("TeSt").unwrap();
         assert_eq!(&chunk.chunk_type().to_string(), "TeSt");
         assert_eq!(&chunk.data_as_string().unwrap(), "Message");
     }
 
+    #[test]
+    fn test_append_chunk_after_iend() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let err = png
+            .append_chunk(chunk_from_strings("TeSt", "Message").unwrap())
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::Decode(PngDecodeError::ChunkAfterIend(_))
+        ));
+        assert!(png.chunk_by_type("TeSt").is_none());
+    }
+
+    #[test]
+    fn test_insert_chunk_before() {
+        let mut png = testing_png();
+        png.insert_chunk_before("LASt", chunk_from_strings("TeSt", "Message").unwrap())
+            .unwrap();
+
+        let types: Vec<String> = png
+            .chunks()
+            .iter()
+            .map(|chunk| chunk.chunk_type().to_string())
+            .collect();
+        assert_eq!(types, ["FrSt", "miDl", "TeSt", "LASt"]);
+    }
+
+    #[test]
+    fn test_insert_chunk_before_missing_target() {
+        let mut png = testing_png();
+        let result =
+            png.insert_chunk_before("IEND", chunk_from_strings("TeSt", "Message").unwrap());
+
+        assert!(result.is_err());
+        assert_eq!(png.chunks().len(), 3);
+    }
+
+    #[test]
+    fn test_replace_chunk_data() {
+        let mut png = testing_png();
+        png.replace_chunk_data("miDl", b"Replaced".to_vec())
+            .unwrap();
+
+        let chunk = &png.chunks()[1];
+        assert_eq!(&chunk.chunk_type().to_string(), "miDl");
+        assert_eq!(&chunk.data_as_string().unwrap(), "Replaced");
+        assert_eq!(chunk.length(), 8);
+        assert!(chunk.verify_crc());
+
+        let err = png.replace_chunk_data("NoNe", vec![]).unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::Decode(PngDecodeError::ChunkTypeNotFound(_))
+        ));
+        assert_eq!(png.chunks().len(), 3);
+    }
+
+    #[test]
+    fn test_replace_chunk() {
+        let mut png = testing_png();
+        let old = png
+            .replace_chunk("miDl", chunk_from_bytes("miDl", b"Replaced"))
+            .unwrap();
+        assert_eq!(old.data(), b"I am another chunk");
+        assert_eq!(png.chunks()[1].data(), b"Replaced");
+        assert_eq!(png.chunks().len(), 3);
+
+        assert!(png
+            .replace_chunk("NeWw", chunk_from_bytes("NeWw", b"Added"))
+            .is_none());
+        assert_eq!(png.chunks().len(), 4);
+        assert_eq!(png.chunks()[3].data(), b"Added");
+
+        png.append_chunk(chunk_from_bytes("IEND", b"")).unwrap();
+        png.replace_chunk("teSt", chunk_from_bytes("teSt", b"Before IEND"));
+        let types: Vec<&str> = png.iter().map(|c| c.chunk_type().as_str()).collect();
+        assert_eq!(types, ["FrSt", "miDl", "LASt", "NeWw", "teSt", "IEND"]);
+    }
+
     #[test]
     fn test_remove_chunk() {
         let mut png = testing_png();
-        png.append_chunk(chunk_from_strings("TeSt", "Message").unwrap());
+        png.append_chunk(chunk_from_strings("TeSt", "Message").unwrap())
+            .unwrap();
         png.remove_chunk("TeSt").unwrap();
         let chunk = png.chunk_by_type("TeSt");
         assert!(chunk.is_none());
     }
 
+    #[test]
+    fn test_find_chunk_index() {
+        let mut png = testing_png();
+        png.append_chunk(chunk_from_strings("FrSt", "Again").unwrap())
+            .unwrap();
+
+        assert_eq!(png.find_chunk_index("FrSt", 0), Some(0));
+        assert_eq!(png.find_chunk_index("FrSt", 1), Some(3));
+        assert_eq!(png.find_chunk_index("FrSt", 2), None);
+        assert_eq!(png.find_chunk_index("NoNe", 0), None);
+    }
+
+    #[test]
+    fn test_chunk_at_offset() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let type_at = |offset| {
+            png.chunk_at_offset(offset)
+                .map(|(index, chunk)| (index, chunk.chunk_type().to_string()))
+        };
+
+        assert_eq!(type_at(0), None);
+        assert_eq!(type_at(7), None);
+        // IHDR is 12 + 13 bytes, so it covers offsets 8 to 32.
+        assert_eq!(type_at(8), Some((0, String::from("IHDR"))));
+        assert_eq!(type_at(32), Some((0, String::from("IHDR"))));
+        assert_eq!(type_at(33), Some((1, String::from("sRGB"))));
+
+        let len = PNG_FILE.len() as u64;
+        let last = png.chunks().len() - 1;
+        assert_eq!(type_at(len - 1), Some((last, String::from("IEND"))));
+        assert_eq!(type_at(len), None);
+        assert_eq!(type_at(u64::MAX), None);
+    }
+
+    #[test]
+    fn test_chunk_at_offset_with_mismatched_length() {
+        use std::str::FromStr;
+
+        // Claims 100 bytes but holds 4, so it spans 8..24 once written.
+        let chunk_type = ChunkType::from_str("ruSt").unwrap();
+        let mismatched = Chunk::new_with_all_fields(100, chunk_type, b"data".to_vec(), 0);
+        let png = Png::from_chunks(vec![mismatched, chunk_from_strings("IEND", "").unwrap()]);
+
+        assert_eq!(png.chunk_at_offset(23).unwrap().0, 0);
+        assert_eq!(png.chunk_at_offset(24).unwrap().0, 1);
+        assert_eq!(png.chunk_at_offset(png.as_bytes().len() as u64), None);
+    }
+
+    #[test]
+    fn test_byte_offset_of_with_mismatched_length() {
+        use std::str::FromStr;
+
+        let chunk_type = ChunkType::from_str("ruSt").unwrap();
+        let mismatched = Chunk::new_with_all_fields(100, chunk_type, b"data".to_vec(), 0);
+        let png = Png::from_chunks(vec![mismatched, chunk_from_strings("IEND", "").unwrap()]);
+
+        let offset = png.byte_offset_of(1).unwrap();
+        assert_eq!(offset, 8 + 12 + 4);
+        assert_eq!(&png.as_bytes()[offset as usize + 4..][..4], b"IEND");
+    }
+
+    #[test]
+    fn test_byte_offset_of() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        assert_eq!(png.byte_offset_of(0), Some(8));
+        assert_eq!(png.byte_offset_of(1), Some(33));
+
+        let bytes = png.as_bytes();
+        for (index, chunk) in png.iter().enumerate() {
+            let offset = png.byte_offset_of(index).unwrap();
+            assert_eq!(png.chunk_at_offset(offset).unwrap().0, index);
+            assert_eq!(
+                &bytes[offset as usize + 4..][..4],
+                &chunk.chunk_type().bytes()
+            );
+        }
+
+        let last = png.chunks().len() - 1;
+        assert_eq!(png.byte_offset_of(last), Some(PNG_FILE.len() as u64 - 12));
+        assert_eq!(png.byte_offset_of(last + 1), None);
+        assert_eq!(Png::new().byte_offset_of(0), None);
+    }
+
+    #[test]
+    fn test_take_chunk() {
+        let mut png = testing_png();
+        let chunk = png.take_chunk("miDl").unwrap();
+        assert_eq!(chunk.data(), b"I am another chunk");
+        assert_eq!(png.chunks().len(), 2);
+
+        assert!(png.take_chunk("miDl").is_none());
+        assert_eq!(png.chunks().len(), 2);
+    }
+
+    #[test]
+    fn test_remove_chunk_at() {
+        let mut png = testing_png();
+        let chunk = png.remove_chunk_at(1).unwrap();
+        assert_eq!(&chunk.chunk_type().to_string(), "miDl");
+        assert_eq!(png.chunks().len(), 2);
+
+        let err = png.remove_chunk_at(2).unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::Decode(PngDecodeError::IndexOutOfRange(2, 2))
+        ));
+    }
+
+    #[test]
+    fn test_deduplicate_chunks() {
+        let mut png = Png::from_chunks(vec![
+            chunk_from_bytes("IHDR", &[0; 13]),
+            chunk_from_bytes("ruSt", b"one"),
+            chunk_from_bytes("IDAT", b"same"),
+            chunk_from_bytes("IDAT", b"same"),
+            chunk_from_bytes("ruSt", b"two"),
+            chunk_from_bytes("ruSt", b"one"),
+            chunk_from_bytes("tEXt", b"a\0b"),
+            chunk_from_bytes("tEXt", b"a\0b"),
+            chunk_from_bytes("IEND", &[]),
+        ]);
+
+        let mut scoped = png.clone();
+        assert_eq!(scoped.deduplicate_chunks_of_type("tEXt"), 1);
+        assert_eq!(scoped.chunks_by_type("ruSt").len(), 3);
+
+        assert_eq!(png.deduplicate_chunks(), 2);
+        let types: Vec<&str> = png
+            .iter()
+            .map(|chunk| chunk.chunk_type().as_str())
+            .collect();
+        assert_eq!(
+            types,
+            ["IHDR", "ruSt", "IDAT", "IDAT", "ruSt", "tEXt", "IEND"]
+        );
+        assert_eq!(png.chunks_by_type("ruSt")[1].data(), b"two");
+        assert_eq!(png.deduplicate_chunks(), 0);
+    }
+
+    #[test]
+    fn test_remove_all_chunks_by_type() {
+        let mut png = testing_png();
+        png.append_chunk(chunk_from_strings("TeSt", "Message").unwrap())
+            .unwrap();
+        png.append_chunk(chunk_from_strings("TeSt", "Another message").unwrap())
+            .unwrap();
+
+        let removed = png.remove_all_chunks_by_type("TeSt");
+        assert_eq!(removed.len(), 2);
+        assert_eq!(&removed[1].data_as_string().unwrap(), "Another message");
+        assert_eq!(png.chunks().len(), 3);
+        assert!(png.remove_all_chunks_by_type("TeSt").is_empty());
+    }
+
+    #[test]
+    fn test_validate_structure() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        assert!(png.validate_structure().is_ok());
+
+        let mut chunks = Png::try_from(&PNG_FILE[..]).unwrap().chunks;
+        chunks.push(chunk_from_strings("TeSt", "Message").unwrap());
+        let png = Png::from_chunks(chunks);
+        let err = png.validate_structure().unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::Structure(PngStructureError::IendNotLast(_))
+        ));
+
+        let png = testing_png();
+        let err = png.validate_structure().unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::Structure(PngStructureError::MissingChunk("IHDR"))
+        ));
+    }
+
+    #[test]
+    fn test_validate_structure_idat_not_contiguous() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        png.insert_chunk_before("IEND", chunk_from_strings("TeSt", "Message").unwrap())
+            .unwrap();
+        assert!(png.validate_structure().is_ok());
+
+        png.insert_chunk_before("IEND", chunk_from_strings("IDAT", "").unwrap())
+            .unwrap();
+        let err = png.validate_structure().unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::Structure(PngStructureError::IdatNotContiguous(_))
+        ));
+    }
+
+    #[test]
+    fn test_canonicalize() {
+        let types = |png: &Png| -> Vec<String> {
+            png.iter()
+                .map(|chunk| chunk.chunk_type().to_string())
+                .collect()
+        };
+        let mut png = Png::from_chunks(
+            [
+                "tEXt", "IHDR", "IDAT", "gAMA", "ruSt", "IDAT", "PLTE", "tRNS", "IEND", "zzZz",
+            ]
+            .iter()
+            .map(|chunk_type| chunk_from_strings(chunk_type, "").unwrap())
+            .collect(),
+        );
+        assert!(png.validate_structure().is_err());
+
+        png.canonicalize().unwrap();
+        assert_eq!(
+            types(&png),
+            ["IHDR", "gAMA", "PLTE", "tRNS", "tEXt", "IDAT", "IDAT", "ruSt", "zzZz", "IEND"]
+        );
+        assert!(png.validate_structure().is_ok());
+
+        let canonical = png.clone();
+        png.canonicalize().unwrap();
+        assert_eq!(png, canonical);
+
+        let mut png = testing_png();
+        let err = png.canonicalize().unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::Structure(PngStructureError::MissingChunk("IHDR"))
+        ));
+        assert_eq!(png, testing_png());
+    }
+
+    #[test]
+    fn test_header() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let header = png.header().unwrap();
+        assert_eq!(header.to_string(), "50x50, 8-bit RGBA, non-interlaced");
+
+        assert!(testing_png().header().is_err());
+    }
+
     #[test]
     fn test_png_from_image_file() {
         let png = Png::try_from(&PNG_FILE[..]);
@@ -263,6 +1685,133 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_round_trip_is_byte_exact() {
+        // Stored lengths and CRCs are written back as read, even when wrong.
+        let mut bytes = PNG_FILE.to_vec();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+        assert_eq!(Png::try_from_unchecked(&bytes).unwrap().as_bytes(), bytes);
+
+        // Nothing but the standard signature is accepted, so nothing else
+        // could need preserving.
+        let mut bytes = PNG_FILE.to_vec();
+        bytes[1] = b'p';
+        assert!(Png::try_from(bytes.as_slice()).is_err());
+        assert!(Png::try_from_unchecked(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_baseline() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        png.insert_chunk_before("IEND", chunk_from_bytes("ruSt", b"message"))
+            .unwrap();
+        png.insert_chunk_before("IEND", chunk_from_bytes("CgBI", b"private"))
+            .unwrap();
+
+        let baseline = png.baseline();
+        let types: Vec<&str> = baseline
+            .iter()
+            .map(|chunk| chunk.chunk_type().as_str())
+            .collect();
+        assert_eq!(types, ["IHDR", "IDAT", "IEND"]);
+        assert!(baseline.validate_structure().is_ok());
+        assert_eq!(baseline.chunks()[1], *png.chunk_by_type("IDAT").unwrap());
+    }
+
+    #[test]
+    fn test_parse_with_other_crc_algorithm() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        png.recompute_crcs_with(CrcAlgorithm::Bzip2);
+        let bytes = png.as_bytes();
+
+        assert!(Png::try_from(bytes.as_slice()).is_err());
+        let options = ParseOptions::default().with_crc_algorithm(CrcAlgorithm::Bzip2);
+        assert_eq!(Png::parse_with(&bytes, &options).unwrap(), png);
+        assert!(png.round_trip_ok_with(CrcAlgorithm::Bzip2).is_ok());
+        assert!(png.round_trip_ok().is_err());
+    }
+
+    #[test]
+    fn test_parse_with_max_chunk_size() {
+        // IHDR, the first chunk, carries 13 bytes of data.
+        let options = ParseOptions::default().with_max_chunk_size(12);
+        assert!(matches!(
+            Png::parse_with(&PNG_FILE, &options).unwrap_err(),
+            crate::Error::Chunk(ChunkError::ExceedsSizeLimit(13, 12))
+        ));
+        let largest = Png::try_from(&PNG_FILE[..])
+            .unwrap()
+            .iter()
+            .map(Chunk::data_len)
+            .max()
+            .unwrap();
+        let options = ParseOptions::default().with_max_chunk_size(largest as u32);
+        assert!(Png::parse_with(&PNG_FILE, &options).is_ok());
+    }
+
+    #[test]
+    fn test_parse_owned_shares_buffer() {
+        let bytes = PNG_FILE.to_vec();
+        let buffer = bytes.as_ptr_range();
+        let png = Png::try_from(bytes).unwrap();
+        let idat = png.chunk_by_type("IDAT").unwrap().data();
+        assert!(buffer.contains(&idat.as_ptr()));
+
+        // Clones and unmodified chunks keep pointing at the same bytes.
+        let mut copy = png.clone();
+        copy.insert_chunk_before("IEND", chunk_from_bytes("ruSt", b"message"))
+            .unwrap();
+        assert_eq!(
+            copy.chunk_by_type("IDAT").unwrap().data().as_ptr(),
+            idat.as_ptr()
+        );
+        assert_eq!(Png::try_from(copy.as_bytes()).unwrap(), copy);
+    }
+
+    #[test]
+    fn test_try_from_unchecked_with_max_chunk_size() {
+        let mut bytes = PNG_FILE.to_vec();
+        // flip a bit in the IHDR CRC, which an unchecked parse lets through
+        bytes[32] ^= 1;
+
+        let options = ParseOptions::default().with_max_chunk_size(12);
+        assert!(matches!(
+            Png::try_from_unchecked_with(&bytes, &options).unwrap_err(),
+            crate::Error::Chunk(ChunkError::ExceedsSizeLimit(13, 12))
+        ));
+        let options = ParseOptions::default().with_max_chunk_size(MAXIMUM_LENGTH);
+        assert!(Png::try_from_unchecked_with(&bytes, &options).is_ok());
+    }
+
+    #[test]
+    fn test_round_trip_ok() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        assert!(png.round_trip_ok().is_ok());
+        png.insert_chunk_before("IEND", chunk_from_bytes("ruSt", b"message"))
+            .unwrap();
+        assert!(png.round_trip_ok().is_ok());
+        assert!(Png::new().round_trip_ok().is_ok());
+
+        let mut bytes = PNG_FILE.to_vec();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+        let png = Png::try_from_unchecked(&bytes).unwrap();
+        let err = png.round_trip_ok().unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::Decode(PngDecodeError::RoundTripFailed(index, ref s))
+                if index == png.chunks().len() - 1 && s == "IEND"
+        ));
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Self-check failed: chunk {} (IEND) does not read back as written",
+                png.chunks().len() - 1
+            )
+        );
+    }
+
     #[test]
     fn test_png_trait_impls() {
         let chunk_bytes: Vec<u8> = testing_chunks()
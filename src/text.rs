@@ -0,0 +1,511 @@
+#![allow(dead_code)]
+
+use crate::chunk::Chunk;
+use crate::chunk_type::ChunkType;
+use crate::zlib;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// A `tEXt` chunk: a Latin-1 keyword and value separated by a null byte.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TextChunk {
+    keyword: String,
+    text: String,
+}
+
+impl TryFrom<&Chunk> for TextChunk {
+    type Error = crate::Error;
+
+    fn try_from(chunk: &Chunk) -> Result<Self, Self::Error> {
+        if chunk.chunk_type().as_str() != "tEXt" {
+            return Err(TextError::WrongChunkType(chunk.chunk_type().to_string()).into());
+        }
+
+        let data = chunk.data();
+        let separator = data
+            .iter()
+            .position(|&byte| byte == 0)
+            .ok_or(TextError::MissingSeparator)?;
+
+        let keyword = from_latin1(&data[..separator]);
+        validate_keyword(&keyword)?;
+
+        Ok(TextChunk {
+            keyword,
+            text: from_latin1(&data[separator + 1..]),
+        })
+    }
+}
+
+impl FromStr for TextChunk {
+    type Err = crate::Error;
+
+    /// Parses `KEYWORD=VALUE`, splitting on the first `=`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (keyword, text) = s.split_once('=').ok_or(TextError::MissingSeparator)?;
+        TextChunk::new(keyword, text)
+    }
+}
+
+impl fmt::Display for TextChunk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.keyword, self.text)
+    }
+}
+
+impl TextChunk {
+    pub fn new(keyword: &str, text: &str) -> crate::Result<TextChunk> {
+        validate_keyword(keyword)?;
+        to_latin1(text)?;
+
+        Ok(TextChunk {
+            keyword: keyword.to_string(),
+            text: text.to_string(),
+        })
+    }
+
+    pub fn keyword(&self) -> &str {
+        &self.keyword
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn to_chunk(&self) -> crate::Result<Chunk> {
+        let data = [to_latin1(&self.keyword)?, vec![0], to_latin1(&self.text)?].concat();
+        Ok(Chunk::new(ChunkType::from_str("tEXt")?, data))
+    }
+}
+
+/// A `zTXt` chunk: a Latin-1 keyword, a null byte, a compression method byte
+/// (always 0, zlib) and the zlib-compressed Latin-1 text.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CompressedTextChunk {
+    keyword: String,
+    text: String,
+}
+
+impl TryFrom<&Chunk> for CompressedTextChunk {
+    type Error = crate::Error;
+
+    fn try_from(chunk: &Chunk) -> Result<Self, Self::Error> {
+        if chunk.chunk_type().as_str() != "zTXt" {
+            return Err(TextError::WrongChunkType(chunk.chunk_type().to_string()).into());
+        }
+
+        let data = chunk.data();
+        let separator = data
+            .iter()
+            .position(|&byte| byte == 0)
+            .ok_or(TextError::MissingSeparator)?;
+
+        let keyword = from_latin1(&data[..separator]);
+        validate_keyword(&keyword)?;
+
+        let (method, compressed) = match &data[separator + 1..] {
+            [method, compressed @ ..] => (*method, compressed),
+            _ => return Err(TextError::MissingSeparator.into()),
+        };
+        if method != 0 {
+            return Err(TextError::UnknownCompressionMethod(method).into());
+        }
+        let text = zlib::decompress(compressed)
+            .map_err(|e| TextError::CorruptCompressedText(keyword.clone(), e.to_string()))?;
+
+        Ok(CompressedTextChunk {
+            keyword,
+            text: from_latin1(&text),
+        })
+    }
+}
+
+impl fmt::Display for CompressedTextChunk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.keyword, self.text)
+    }
+}
+
+impl CompressedTextChunk {
+    pub fn new(keyword: &str, text: &str) -> crate::Result<CompressedTextChunk> {
+        validate_keyword(keyword)?;
+        to_latin1(text)?;
+
+        Ok(CompressedTextChunk {
+            keyword: keyword.to_string(),
+            text: text.to_string(),
+        })
+    }
+
+    pub fn keyword(&self) -> &str {
+        &self.keyword
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn to_chunk(&self) -> crate::Result<Chunk> {
+        let data = [
+            to_latin1(&self.keyword)?,
+            vec![0, 0],
+            zlib::compress(&to_latin1(&self.text)?),
+        ]
+        .concat();
+        Ok(Chunk::new(ChunkType::from_str("zTXt")?, data))
+    }
+}
+
+/// An `iTXt` chunk: UTF-8 text with a Latin-1 keyword, an optional language tag
+/// and translated keyword, and optionally zlib-compressed text.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct InternationalTextChunk {
+    keyword: String,
+    compressed: bool,
+    language: String,
+    translated_keyword: String,
+    text: String,
+}
+
+impl TryFrom<&Chunk> for InternationalTextChunk {
+    type Error = crate::Error;
+
+    fn try_from(chunk: &Chunk) -> Result<Self, Self::Error> {
+        if chunk.chunk_type().as_str() != "iTXt" {
+            return Err(TextError::WrongChunkType(chunk.chunk_type().to_string()).into());
+        }
+
+        let mut fields = chunk.data().splitn(2, |&byte| byte == 0);
+        let keyword = from_latin1(fields.next().unwrap_or_default());
+        validate_keyword(&keyword)?;
+
+        let rest = fields.next().ok_or(TextError::MissingSeparator)?;
+        let (compression_flag, compression_method, rest) = match rest {
+            [flag, method, rest @ ..] => (*flag, *method, rest),
+            _ => return Err(TextError::MissingSeparator.into()),
+        };
+        let compressed = match (compression_flag, compression_method) {
+            (0, _) => false,
+            (1, 0) => true,
+            (1, method) => return Err(TextError::UnknownCompressionMethod(method).into()),
+            (flag, _) => return Err(TextError::InvalidCompressionFlag(flag).into()),
+        };
+
+        let mut fields = rest.splitn(3, |&byte| byte == 0);
+        let language = String::from_utf8(fields.next().unwrap_or_default().to_vec())?;
+        let translated_keyword = fields.next().ok_or(TextError::MissingSeparator)?;
+        let translated_keyword = String::from_utf8(translated_keyword.to_vec())?;
+        let text = fields.next().ok_or(TextError::MissingSeparator)?;
+        let text = match compressed {
+            true => String::from_utf8(zlib::decompress(text)?)?,
+            false => String::from_utf8(text.to_vec())?,
+        };
+
+        Ok(InternationalTextChunk {
+            keyword,
+            compressed,
+            language,
+            translated_keyword,
+            text,
+        })
+    }
+}
+
+impl fmt::Display for InternationalTextChunk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.keyword)?;
+        if !self.language.is_empty() {
+            write!(f, " [{}]", self.language)?;
+        }
+        if !self.translated_keyword.is_empty() {
+            write!(f, " ({})", self.translated_keyword)?;
+        }
+        write!(f, ": {}", self.text)
+    }
+}
+
+impl InternationalTextChunk {
+    pub fn new(
+        keyword: &str,
+        language: &str,
+        translated_keyword: &str,
+        text: &str,
+        compressed: bool,
+    ) -> crate::Result<InternationalTextChunk> {
+        validate_keyword(keyword)?;
+        if !language
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-')
+        {
+            return Err(TextError::InvalidLanguageTag(language.to_string()).into());
+        }
+        if translated_keyword.contains('\0') || text.contains('\0') {
+            return Err(TextError::UnexpectedNull.into());
+        }
+
+        Ok(InternationalTextChunk {
+            keyword: keyword.to_string(),
+            compressed,
+            language: language.to_string(),
+            translated_keyword: translated_keyword.to_string(),
+            text: text.to_string(),
+        })
+    }
+
+    pub fn keyword(&self) -> &str {
+        &self.keyword
+    }
+
+    pub fn is_compressed(&self) -> bool {
+        self.compressed
+    }
+
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    pub fn translated_keyword(&self) -> &str {
+        &self.translated_keyword
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn to_chunk(&self) -> crate::Result<Chunk> {
+        let text = match self.compressed {
+            true => zlib::compress(self.text.as_bytes()),
+            false => self.text.as_bytes().to_vec(),
+        };
+        let data = [
+            to_latin1(&self.keyword)?.as_slice(),
+            &[0, u8::from(self.compressed), 0],
+            self.language.as_bytes(),
+            &[0],
+            self.translated_keyword.as_bytes(),
+            &[0],
+            &text,
+        ]
+        .concat();
+
+        Ok(Chunk::new(ChunkType::from_str("iTXt")?, data))
+    }
+}
+
+/// Checks a keyword against the PNG spec: 1-79 printable Latin-1 characters,
+/// with no leading, trailing or consecutive spaces.
+pub fn validate_keyword(keyword: &str) -> crate::Result<()> {
+    let length = keyword.chars().count();
+    if !(1..=79).contains(&length) {
+        return Err(TextError::InvalidKeywordLength(length).into());
+    }
+
+    if let Some(c) = keyword
+        .chars()
+        .find(|&c| !matches!(c as u32, 32..=126 | 161..=255))
+    {
+        return Err(TextError::InvalidKeywordCharacter(c).into());
+    }
+
+    if keyword.starts_with(' ') || keyword.ends_with(' ') || keyword.contains("  ") {
+        return Err(TextError::InvalidKeywordSpacing.into());
+    }
+
+    Ok(())
+}
+
+fn from_latin1(bytes: &[u8]) -> String {
+    bytes.iter().map(|&byte| char::from(byte)).collect()
+}
+
+fn to_latin1(text: &str) -> crate::Result<Vec<u8>> {
+    text.chars()
+        .map(|c| u8::try_from(c).map_err(|_| TextError::NotLatin1(c).into()))
+        .collect()
+}
+
+#[derive(Debug)]
+pub enum TextError {
+    WrongChunkType(String),
+    MissingSeparator,
+    InvalidKeywordLength(usize),
+    InvalidKeywordCharacter(char),
+    InvalidKeywordSpacing,
+    NotLatin1(char),
+    InvalidLanguageTag(String),
+    InvalidCompressionFlag(u8),
+    UnknownCompressionMethod(u8),
+    CorruptCompressedText(String, String),
+    UnexpectedNull,
+}
+
+impl fmt::Display for TextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextError::WrongChunkType(chunk_type) => {
+                write!(f, "Unexpected chunk type {chunk_type}")
+            }
+            TextError::MissingSeparator => {
+                write!(f, "The keyword and text must be separated")
+            }
+            TextError::InvalidKeywordLength(length) => write!(
+                f,
+                "Keywords must be 1 to 79 characters long, found {length}"
+            ),
+            TextError::InvalidKeywordCharacter(c) => {
+                write!(
+                    f,
+                    "Keywords may only contain printable Latin-1, found {c:?}"
+                )
+            }
+            TextError::InvalidKeywordSpacing => write!(
+                f,
+                "Keywords may not have leading, trailing or consecutive spaces"
+            ),
+            TextError::NotLatin1(c) => {
+                write!(f, "tEXt chunks can only hold Latin-1 text, found {c:?}")
+            }
+            TextError::InvalidLanguageTag(tag) => write!(
+                f,
+                "Language tags may only contain ASCII letters, digits and hyphens, found {tag:?}"
+            ),
+            TextError::InvalidCompressionFlag(flag) => {
+                write!(f, "Invalid compression flag: {flag}")
+            }
+            TextError::UnknownCompressionMethod(method) => {
+                write!(f, "Unknown compression method: {method}")
+            }
+            TextError::CorruptCompressedText(keyword, reason) => {
+                write!(f, "Could not decompress the text of {keyword:?}: {reason}")
+            }
+            TextError::UnexpectedNull => {
+                write!(f, "Text fields may not contain null characters")
+            }
+        }
+    }
+}
+
+impl Error for TextError {}
+
+// ----------TESTS-------------//
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_chunk_round_trip() {
+        let text = TextChunk::from_str("Author=Ferris the Crab").unwrap();
+        let chunk = text.to_chunk().unwrap();
+
+        assert_eq!(chunk.data(), b"Author\0Ferris the Crab");
+        assert_eq!(TextChunk::try_from(&chunk).unwrap(), text);
+        assert_eq!(text.to_string(), "Author: Ferris the Crab");
+    }
+
+    #[test]
+    fn test_text_chunk_latin1() {
+        let text = TextChunk::new("Comment", "caf\u{e9}").unwrap();
+        let chunk = text.to_chunk().unwrap();
+
+        assert_eq!(chunk.data(), b"Comment\0caf\xe9");
+        assert_eq!(TextChunk::try_from(&chunk).unwrap().text(), "caf\u{e9}");
+        assert!(TextChunk::new("Comment", "\u{1f980}").is_err());
+    }
+
+    #[test]
+    fn test_invalid_keywords() {
+        assert!(validate_keyword("").is_err());
+        assert!(validate_keyword(&"k".repeat(80)).is_err());
+        assert!(validate_keyword(" Title").is_err());
+        assert!(validate_keyword("Creation  Time").is_err());
+        assert!(validate_keyword("Tab\tbed").is_err());
+        assert!(validate_keyword("Creation Time").is_ok());
+    }
+
+    #[test]
+    fn test_international_text_chunk_round_trip() {
+        for compressed in [false, true] {
+            let text = InternationalTextChunk::new(
+                "Title",
+                "de-DE",
+                "Titel",
+                "Gr\u{fc}\u{df}e aus K\u{f6}ln \u{1f980}",
+                compressed,
+            )
+            .unwrap();
+            let chunk = text.to_chunk().unwrap();
+
+            assert_eq!(chunk.data()[6], u8::from(compressed));
+            assert_eq!(InternationalTextChunk::try_from(&chunk).unwrap(), text);
+        }
+    }
+
+    #[test]
+    fn test_international_text_chunk_display() {
+        let text = InternationalTextChunk::new("Title", "", "", "Hello", false).unwrap();
+        assert_eq!(text.to_string(), "Title: Hello");
+
+        let text = InternationalTextChunk::new("Title", "de", "Titel", "Hallo", false).unwrap();
+        assert_eq!(text.to_string(), "Title [de] (Titel): Hallo");
+    }
+
+    #[test]
+    fn test_international_text_chunk_from_bytes() {
+        let chunk = Chunk::new(
+            ChunkType::from_str("iTXt").unwrap(),
+            b"Title\0\0\0en\0\0Hello".to_vec(),
+        );
+        let text = InternationalTextChunk::try_from(&chunk).unwrap();
+
+        assert_eq!(text.keyword(), "Title");
+        assert_eq!(text.language(), "en");
+        assert_eq!(text.translated_keyword(), "");
+        assert_eq!(text.text(), "Hello");
+        assert!(!text.is_compressed());
+    }
+
+    #[test]
+    fn test_compressed_text_chunk_round_trip() {
+        let text = CompressedTextChunk::new("Comment", "caf\u{e9} ".repeat(50).as_str()).unwrap();
+        let chunk = text.to_chunk().unwrap();
+
+        assert!(chunk.data().starts_with(b"Comment\0\0"));
+        assert!(chunk.data().len() < text.text().len());
+        assert_eq!(CompressedTextChunk::try_from(&chunk).unwrap(), text);
+        assert!(text.to_string().starts_with("Comment: caf\u{e9} "));
+    }
+
+    #[test]
+    fn test_compressed_text_chunk_unknown_method() {
+        let data = [b"Comment\0\x01".as_slice(), &zlib::compress(b"hi")].concat();
+        let chunk = Chunk::new(ChunkType::from_str("zTXt").unwrap(), data);
+        let err = CompressedTextChunk::try_from(&chunk).unwrap_err();
+        assert!(err.to_string().contains("compression method"), "{err}");
+    }
+
+    #[test]
+    fn test_compressed_text_chunk_corrupt_data() {
+        let chunk = Chunk::new(
+            ChunkType::from_str("zTXt").unwrap(),
+            b"Comment\0\0not zlib".to_vec(),
+        );
+        let err = CompressedTextChunk::try_from(&chunk).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Could not decompress the text of \"Comment\""),
+            "{err}"
+        );
+
+        let missing_method =
+            Chunk::new(ChunkType::from_str("zTXt").unwrap(), b"Comment\0".to_vec());
+        assert!(CompressedTextChunk::try_from(&missing_method).is_err());
+    }
+
+    #[test]
+    fn test_text_chunk_missing_separator() {
+        let chunk = Chunk::new(ChunkType::from_str("tEXt").unwrap(), b"Author".to_vec());
+        assert!(TextChunk::try_from(&chunk).is_err());
+    }
+}
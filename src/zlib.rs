@@ -0,0 +1,229 @@
+#![allow(dead_code)]
+
+//! zlib (RFC 1950) compression for the payloads PNG stores compressed: `zTXt`,
+//! `iTXt` and `IDAT`, plus messages from `encode --compress`. Decompression is
+//! capped, since a few hundred bytes of an untrusted file can inflate to
+//! gigabytes.
+
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use std::error::Error;
+use std::fmt;
+use std::io::{Read, Write};
+
+/// Marks a message compressed by `encode --compress`. It is followed by a
+/// format version byte and then the zlib stream.
+pub const MESSAGE_MAGIC: &[u8; 6] = b"pngmeZ";
+const MESSAGE_VERSION: u8 = 1;
+
+/// The most `decompress` will inflate, well above any text chunk or message
+/// and the image data of all but huge images.
+pub const DEFAULT_MAX_OUTPUT: usize = 256 * 1024 * 1024;
+
+/// Compresses a message and prefixes it with `MESSAGE_MAGIC` and the format
+/// version, so `decompress_message` can recognize it.
+pub fn compress_message(message: &[u8]) -> Vec<u8> {
+    [
+        MESSAGE_MAGIC.as_slice(),
+        &[MESSAGE_VERSION],
+        &compress(message),
+    ]
+    .concat()
+}
+
+pub fn is_compressed_message(data: &[u8]) -> bool {
+    data.starts_with(MESSAGE_MAGIC)
+}
+
+/// Inflates a message produced by `compress_message`.
+pub fn decompress_message(data: &[u8]) -> crate::Result<Vec<u8>> {
+    let rest = data
+        .strip_prefix(MESSAGE_MAGIC.as_slice())
+        .ok_or(ZlibError::NotACompressedMessage)?;
+    match rest.split_first() {
+        Some((&MESSAGE_VERSION, stream)) => {
+            decompress(stream).map_err(|e| ZlibError::CorruptMessage(e.to_string()).into())
+        }
+        Some((&version, _)) => Err(ZlibError::UnknownMessageVersion(version).into()),
+        None => Err(ZlibError::CorruptMessage(String::from("no data after the header")).into()),
+    }
+}
+
+/// Compresses `data` into a zlib stream.
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(data)
+        .and_then(|_| encoder.finish())
+        .expect("writing to a Vec cannot fail")
+}
+
+/// Decompresses a zlib stream, verifying its header and Adler-32 checksum,
+/// and failing once it inflates past `DEFAULT_MAX_OUTPUT`.
+pub fn decompress(data: &[u8]) -> crate::Result<Vec<u8>> {
+    decompress_with_limit(data, DEFAULT_MAX_OUTPUT)
+}
+
+/// Like `decompress`, but fails once the output would exceed `max_output`
+/// bytes, for callers that know how much data to expect.
+pub fn decompress_with_limit(data: &[u8], max_output: usize) -> crate::Result<Vec<u8>> {
+    let mut output = Vec::new();
+    ZlibDecoder::new(data)
+        .take(max_output as u64 + 1)
+        .read_to_end(&mut output)
+        .map_err(|e| ZlibError::Corrupt(e.to_string()))?;
+    if output.len() > max_output {
+        return Err(ZlibError::OutputTooLarge(max_output).into());
+    }
+    Ok(output)
+}
+
+#[derive(Debug)]
+pub enum ZlibError {
+    Corrupt(String),
+    OutputTooLarge(usize),
+    NotACompressedMessage,
+    UnknownMessageVersion(u8),
+    CorruptMessage(String),
+}
+
+impl fmt::Display for ZlibError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ZlibError::Corrupt(reason) => write!(f, "Corrupt zlib stream: {reason}"),
+            ZlibError::OutputTooLarge(limit) => write!(
+                f,
+                "The compressed data inflates to more than the limit of {limit} bytes"
+            ),
+            ZlibError::NotACompressedMessage => {
+                write!(f, "The data is not a message compressed by pngme")
+            }
+            ZlibError::UnknownMessageVersion(version) => write!(
+                f,
+                "The message was compressed with format version {version}, which this pngme does not know"
+            ),
+            ZlibError::CorruptMessage(reason) => {
+                write!(f, "The compressed message is corrupt or truncated: {reason}")
+            }
+        }
+    }
+}
+
+impl Error for ZlibError {}
+
+// ----------TESTS-------------//
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let data = "This is where your secret message will be! ".repeat(50);
+        let compressed = compress(data.as_bytes());
+
+        assert!(compressed.len() < data.len());
+        assert_eq!(decompress(&compressed).unwrap(), data.as_bytes());
+    }
+
+    #[test]
+    fn test_round_trip_empty() {
+        assert_eq!(decompress(&compress(&[])).unwrap(), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn test_decompress_stored_block() {
+        // "hello" stored uncompressed, as produced by `zlib.compress(b"hello", 0)`
+        let stream = [
+            0x78, 0x01, 0x01, 0x05, 0x00, 0xfa, 0xff, 0x68, 0x65, 0x6c, 0x6c, 0x6f, 0x06, 0x2c,
+            0x02, 0x15,
+        ];
+        assert_eq!(decompress(&stream).unwrap(), b"hello");
+    }
+
+    #[test]
+    fn test_decompress_dynamic_block() {
+        // `zlib.compress(TEXT, 9)`, which picks a dynamic Huffman block
+        const TEXT: &str = "In the beginning the Universe was created. \
+            This has made a lot of people very angry and been widely regarded as a bad move.";
+        let stream = [
+            0x78, 0xda, 0x1d, 0x8c, 0xc1, 0x09, 0x80, 0x30, 0x10, 0x04, 0x5b, 0xd9, 0x0a, 0xd2,
+            0x87, 0x7f, 0x2d, 0xe0, 0xf4, 0xd6, 0xe4, 0x20, 0x5e, 0x24, 0x09, 0x11, 0xbb, 0x37,
+            0xf8, 0x59, 0x98, 0x81, 0xd9, 0xc5, 0xd1, 0x13, 0xb1, 0x33, 0x9a, 0xbb, 0x79, 0xfc,
+            0x69, 0x73, 0x1b, 0xac, 0x8d, 0x78, 0xa4, 0xe1, 0xa8, 0x94, 0x4e, 0x0d, 0x58, 0x93,
+            0x35, 0xa4, 0x69, 0x2e, 0x51, 0x42, 0x90, 0x4b, 0x47, 0x39, 0x71, 0xb3, 0xdc, 0x99,
+            0x98, 0xc1, 0x0b, 0xf1, 0xf8, 0xaf, 0xce, 0x43, 0x3a, 0x1e, 0x53, 0xe6, 0x17, 0x95,
+            0x51, 0xaa, 0x52, 0x31, 0x5b, 0xc1, 0x2e, 0x8a, 0xab, 0x0c, 0x86, 0x0f, 0xa2, 0x35,
+            0x2b, 0xa3,
+        ];
+        assert_eq!(decompress(&stream).unwrap(), TEXT.as_bytes());
+    }
+
+    #[test]
+    fn test_decompress_bad_checksum() {
+        let mut compressed = compress(b"message");
+        let last = compressed.len() - 1;
+        compressed[last] ^= 0xff;
+
+        assert!(decompress(&compressed).is_err());
+    }
+
+    #[test]
+    fn test_message_round_trip() {
+        let message = "Hello, hello, hello, hello!".repeat(20);
+        let compressed = compress_message(message.as_bytes());
+
+        assert!(compressed.starts_with(b"pngmeZ\x01"));
+        assert!(is_compressed_message(&compressed));
+        assert!(compressed.len() < message.len());
+        assert_eq!(decompress_message(&compressed).unwrap(), message.as_bytes());
+        assert!(!is_compressed_message(message.as_bytes()));
+    }
+
+    #[test]
+    fn test_message_errors() {
+        let compressed = compress_message(b"Hello, world!");
+
+        let truncated = decompress_message(&compressed[..compressed.len() - 3]).unwrap_err();
+        assert!(
+            truncated
+                .to_string()
+                .starts_with("The compressed message is corrupt or truncated"),
+            "{truncated}"
+        );
+        assert!(decompress_message(MESSAGE_MAGIC).is_err());
+
+        let mut future = compressed.clone();
+        future[MESSAGE_MAGIC.len()] = 2;
+        assert!(decompress_message(&future)
+            .unwrap_err()
+            .to_string()
+            .contains("format version 2"));
+
+        assert!(decompress_message(b"plain").is_err());
+    }
+
+    #[test]
+    fn test_decompress_limit() {
+        let bomb = compress(&vec![0u8; 1 << 20]);
+        assert!(bomb.len() < 2048);
+
+        assert_eq!(
+            decompress_with_limit(&bomb, 1 << 20).unwrap().len(),
+            1 << 20
+        );
+        let err = decompress_with_limit(&bomb, (1 << 20) - 1).unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::Zlib(ZlibError::OutputTooLarge(_))
+        ));
+    }
+
+    #[test]
+    fn test_decompress_truncated() {
+        let compressed = compress(b"This is where your secret message will be!");
+        assert!(decompress(&compressed[..compressed.len() - 5]).is_err());
+        assert!(decompress(b"not zlib").is_err());
+    }
+}